@@ -1,4 +1,172 @@
-fn main() {
-    // Migration admin stub; to be wired with sqlx_migrations later.
-    println!("messaging-admin (migrations stub)");
+use std::env;
+
+use anyhow::{bail, Context, Result};
+use messaging_core::dead_letters::{self, DeadLetter, Selection};
+use sqlx::{postgres::PgPoolOptions, PgPool};
+
+const USAGE: &str = "Usage:
+  messaging-admin dead-letters list [--limit N] [--offset N]
+  messaging-admin dead-letters show <id>
+  messaging-admin dead-letters requeue (--all | <id>...)
+  messaging-admin dead-letters purge (--all | <id>...)
+  messaging-admin help
+
+ENV:
+  DATABASE_URL  Postgres connection URL";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("dead-letters") => dead_letters_cmd(&args[1..]).await,
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        // Scripts must see a non-zero status for a missing or mistyped command
+        Some(other) => bail!("unknown command: {other}\n\n{USAGE}"),
+        None => bail!("missing command\n\n{USAGE}"),
+    }
+}
+
+async fn connect() -> Result<PgPool> {
+    let database_url = env::var("DATABASE_URL").context("DATABASE_URL is required")?;
+    PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .context("failed to connect to database")
+}
+
+async fn dead_letters_cmd(args: &[String]) -> Result<()> {
+    let Some(sub) = args.first() else {
+        bail!("missing dead-letters subcommand\n\n{USAGE}");
+    };
+    let rest = &args[1..];
+    match sub.as_str() {
+        "list" => {
+            let limit = flag_value(rest, "--limit")?.unwrap_or(50);
+            let offset = flag_value(rest, "--offset")?.unwrap_or(0);
+            let pool = connect().await?;
+            let rows = dead_letters::list(&pool, limit, offset).await?;
+            let total = dead_letters::count(&pool).await?;
+            println!("ID\tCHANNEL\tATTEMPTS\tDEAD_AT\tERROR");
+            for d in &rows {
+                print_summary(d);
+            }
+            println!("({} of {} dead letters)", rows.len(), total);
+        }
+        "show" => {
+            let id = parse_id(rest.first().context("show requires an id")?)?;
+            let pool = connect().await?;
+            match dead_letters::get(&pool, id).await? {
+                Some(d) => print_detail(&d),
+                None => bail!("no dead letter with id {id}"),
+            }
+        }
+        "requeue" => {
+            let ids = parse_selection(rest)?;
+            let pool = connect().await?;
+            let n = dead_letters::requeue(&pool, selection(&ids)).await?;
+            println!("Requeued {n} dead letter(s).");
+        }
+        "purge" => {
+            let ids = parse_selection(rest)?;
+            let pool = connect().await?;
+            let n = dead_letters::purge(&pool, selection(&ids)).await?;
+            println!("Purged {n} dead letter(s).");
+        }
+        other => bail!("unknown dead-letters subcommand: {other}\n\n{USAGE}"),
+    }
+    Ok(())
+}
+
+fn flag_value(args: &[String], flag: &str) -> Result<Option<i64>> {
+    match args.iter().position(|a| a == flag) {
+        Some(i) => {
+            let v = args
+                .get(i + 1)
+                .with_context(|| format!("{flag} requires a value"))?;
+            Ok(Some(
+                v.parse()
+                    .with_context(|| format!("{flag} must be a number"))?,
+            ))
+        }
+        None => Ok(None),
+    }
+}
+
+fn parse_id(s: &str) -> Result<i64> {
+    s.parse()
+        .with_context(|| format!("invalid dead letter id: {s}"))
+}
+
+/// `None` means `--all`.
+fn parse_selection(args: &[String]) -> Result<Option<Vec<i64>>> {
+    if args.iter().any(|a| a == "--all") {
+        if args.len() > 1 {
+            bail!("--all cannot be combined with ids");
+        }
+        return Ok(None);
+    }
+    if args.is_empty() {
+        bail!("specify one or more ids, or --all");
+    }
+    args.iter()
+        .map(|a| parse_id(a))
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+fn selection(ids: &Option<Vec<i64>>) -> Selection<'_> {
+    match ids {
+        Some(ids) => Selection::Ids(ids),
+        None => Selection::All,
+    }
+}
+
+fn print_summary(d: &DeadLetter) {
+    println!(
+        "{}\t{}\t{}\t{}\t{}: {}",
+        d.id,
+        d.channel.as_deref().unwrap_or("-"),
+        d.attempts,
+        d.updated_at.to_rfc3339(),
+        d.error_code.as_deref().unwrap_or("-"),
+        // Worker errors can carry backtraces; keep the listing to one line per event
+        d.error_message
+            .as_deref()
+            .and_then(|m| m.lines().next())
+            .unwrap_or("-"),
+    );
+}
+
+fn print_detail(d: &DeadLetter) {
+    println!("id:                  {}", d.id);
+    println!("event_type:          {}", d.event_type);
+    println!(
+        "channel:             {}",
+        d.channel.as_deref().unwrap_or("-")
+    );
+    println!("from:                {}", d.from.as_deref().unwrap_or("-"));
+    println!("to:                  {}", d.to.as_deref().unwrap_or("-"));
+    println!(
+        "provider_message_id: {}",
+        d.provider_message_id.as_deref().unwrap_or("-")
+    );
+    println!("attempts:            {}", d.attempts);
+    println!(
+        "error_code:          {}",
+        d.error_code.as_deref().unwrap_or("-")
+    );
+    println!(
+        "error_message:       {}",
+        d.error_message.as_deref().unwrap_or("-")
+    );
+    println!("received_at:         {}", d.received_at.to_rfc3339());
+    println!("dead_at:             {}", d.updated_at.to_rfc3339());
+    println!(
+        "payload:             {}",
+        d.payload.as_deref().unwrap_or("null")
+    );
 }
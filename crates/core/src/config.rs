@@ -140,7 +140,7 @@ impl Config {
             .ok()
             .and_then(|v| v.parse::<u32>().ok())
             .unwrap_or(1);
-        if argon2_memory_mb < 8 || argon2_memory_mb > 1024 {
+        if !(8..=1024).contains(&argon2_memory_mb) {
             return Err("ARGON2_MEMORY_MB must be in 8..=1024".to_string());
         }
         if !(1..=10).contains(&argon2_time_cost) {
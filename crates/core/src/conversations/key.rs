@@ -1,4 +1,17 @@
-use super::{normalize_email::normalize_email, normalize_phone::normalize_phone, ConversationKey};
+use std::sync::Arc;
+
+use super::normalize_email::{self, normalize_email, EmailRules};
+use super::normalize_phone::{self, normalize_phone, PhoneRegion};
+use super::ConversationKey;
+
+/// Version of the normalization behind [`derive_key`], stored as `conversations.key_version`.
+/// Bump it whenever normalization changes, so `db-migrate rekey` can find the stale keys.
+///
+/// 1. Phone numbers kept as digits with an optional leading `+`; emails lowercased with
+///    plus-tags stripped.
+/// 2. Phone numbers in E.164 (default region for national numbers); emails parsed per RFC 5322
+///    and normalized with per-domain rules.
+pub const KEY_VERSION: i32 = 2;
 
 /// Supported channels for normalization
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -8,17 +21,56 @@ pub enum ChannelKind {
     Mms,
 }
 
-fn normalize(channel: &ChannelKind, addr: &str) -> String {
-    match channel {
-        ChannelKind::Email => normalize_email(addr),
-        ChannelKind::Sms | ChannelKind::Mms => normalize_phone(addr),
+impl ChannelKind {
+    /// Parse a `conversations.channel` value.
+    pub fn parse(channel: &str) -> Option<Self> {
+        match channel {
+            "email" => Some(ChannelKind::Email),
+            "sms" => Some(ChannelKind::Sms),
+            "mms" => Some(ChannelKind::Mms),
+            _ => None,
+        }
+    }
+}
+
+/// The settings addresses are normalized with before they go into a key. A server reads them
+/// from its config at startup; keys derived with different settings do not match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyNormalization {
+    /// Region of phone numbers written without a country code
+    pub phone_region: &'static PhoneRegion,
+    /// Per-domain email rules, already merged over the built-in ones
+    pub email_rules: Arc<EmailRules>,
+}
+
+impl Default for KeyNormalization {
+    fn default() -> Self {
+        Self {
+            phone_region: normalize_phone::default_region(),
+            email_rules: Arc::new(normalize_email::builtin_rules()),
+        }
+    }
+}
+
+impl KeyNormalization {
+    /// Normalize one address of a `channel` conversation.
+    pub fn normalize(&self, channel: &ChannelKind, addr: &str) -> String {
+        match channel {
+            ChannelKind::Email => normalize_email(addr, &self.email_rules),
+            ChannelKind::Sms | ChannelKind::Mms => normalize_phone(addr, self.phone_region),
+        }
     }
 }
 
 /// Build canonical conversation key and ordered participants
-pub fn derive_key(channel: ChannelKind, a: &str, b: &str) -> ConversationKey {
-    let na = normalize(&channel, a);
-    let nb = normalize(&channel, b);
+pub fn derive_key(
+    channel: ChannelKind,
+    a: &str,
+    b: &str,
+    norm: &KeyNormalization,
+) -> ConversationKey {
+    let na = norm.normalize(&channel, a);
+    let nb = norm.normalize(&channel, b);
     let (pa, pb) = if na <= nb { (na, nb) } else { (nb, na) };
     let chan = match channel {
         ChannelKind::Email => "email",
@@ -28,6 +80,7 @@ pub fn derive_key(channel: ChannelKind, a: &str, b: &str) -> ConversationKey {
     .to_string();
     let key = format!("{}:{}<->{}", chan, pa, pb);
     ConversationKey {
+        version: KEY_VERSION,
         channel: chan,
         participant_a: pa,
         participant_b: pb,
@@ -41,7 +94,12 @@ mod tests {
 
     #[test]
     fn orders_participants() {
-        let k = derive_key(ChannelKind::Email, "B@example.com", "a@example.com");
+        let k = derive_key(
+            ChannelKind::Email,
+            "B@example.com",
+            "a@example.com",
+            &KeyNormalization::default(),
+        );
         assert_eq!(k.participant_a, "a@example.com");
         assert_eq!(k.participant_b, "b@example.com");
         assert_eq!(k.key, "email:a@example.com<->b@example.com");
@@ -49,7 +107,12 @@ mod tests {
 
     #[test]
     fn normalizes_phone_digits() {
-        let k = derive_key(ChannelKind::Sms, "+1 (555) 000-1234", "5550001234");
+        let k = derive_key(
+            ChannelKind::Sms,
+            "+1 (555) 000-1234",
+            "5550001234",
+            &KeyNormalization::default(),
+        );
         assert_eq!(k.participant_a, "+15550001234");
         assert_eq!(k.participant_b, "+15550001234");
         assert_eq!(k.key, "sms:+15550001234<->+15550001234");
@@ -42,6 +42,7 @@ mod tests {
     #[test]
     fn logs_created() {
         let k = ConversationKey {
+            version: crate::conversations::key::KEY_VERSION,
             channel: "email".into(),
             participant_a: "a".into(),
             participant_b: "b".into(),
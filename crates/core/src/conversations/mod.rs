@@ -10,6 +10,8 @@ pub mod upsert;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConversationKey {
+    /// Normalization scheme the key was derived with (see [`key::KEY_VERSION`])
+    pub version: i32,
     pub channel: String,
     pub participant_a: String,
     pub participant_b: String,
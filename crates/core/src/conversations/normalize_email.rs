@@ -1,31 +1,446 @@
-/// Normalize email addresses: lowercase entire address and strip plus-tags.
-pub fn normalize_email(addr: &str) -> String {
-    let lower = addr.to_ascii_lowercase();
-    if let Some(at_pos) = lower.find('@') {
-        let (local, domain) = lower.split_at(at_pos);
-        let local_base = local.split('+').next().unwrap_or(local);
-        format!("{}{}", local_base, domain)
+//! Email address parsing and normalization.
+//!
+//! Addresses are parsed as RFC 5322 mailboxes: a bare `addr-spec` (`bob@example.com`) or a
+//! `name-addr` with a display name (`"Bob" <bob@example.com>`); comments in parentheses are
+//! ignored. The domain is always case-insensitive. What happens to the local part depends on the
+//! [`EmailRule`] for the domain: plus-tags can be stripped, dots ignored (Gmail), and case kept
+//! for servers whose local parts are case-sensitive. A quoted local part that is not a valid
+//! dot-atom (e.g. `"john doe"@example.com`) only gets the case rule.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How the local part of addresses at one domain is normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmailRule {
+    /// Drop dots from the local part (`j.doe` and `jdoe` are the same mailbox)
+    pub ignore_dots: bool,
+    /// Drop everything from the first `+` in the local part
+    pub strip_plus_tags: bool,
+    /// Keep the case of the local part instead of lowercasing it
+    pub case_sensitive: bool,
+}
+
+/// Rule for domains without an entry of their own (and without a `*` entry).
+impl Default for EmailRule {
+    fn default() -> Self {
+        Self {
+            ignore_dots: false,
+            strip_plus_tags: true,
+            case_sensitive: false,
+        }
+    }
+}
+
+/// Rules by lowercase domain; `*` matches every domain without its own entry.
+pub type EmailRules = BTreeMap<String, EmailRule>;
+
+/// Built-in rules: Gmail ignores dots and plus-tags in local parts.
+pub fn builtin_rules() -> EmailRules {
+    let gmail = EmailRule {
+        ignore_dots: true,
+        ..EmailRule::default()
+    };
+    ["gmail.com", "googlemail.com"]
+        .into_iter()
+        .map(|d| (d.to_string(), gmail))
+        .collect()
+}
+
+/// [`builtin_rules`] with `overrides` on top (domains lowercased).
+pub fn merged_rules(overrides: &EmailRules) -> EmailRules {
+    let mut rules = builtin_rules();
+    for (domain, rule) in overrides {
+        rules.insert(domain.to_lowercase(), *rule);
+    }
+    rules
+}
+
+/// The rule for `domain` (lowercase): its own entry, else `*`, else [`EmailRule::default`].
+pub fn rule_for(rules: &EmailRules, domain: &str) -> EmailRule {
+    rules
+        .get(domain)
+        .or_else(|| rules.get("*"))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Why an address could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailError {
+    Empty,
+    MissingAt,
+    InvalidLocalPart,
+    InvalidDomain,
+    /// Unbalanced quotes, brackets or parentheses, or a bad display name
+    Malformed,
+}
+
+impl EmailError {
+    /// Machine-readable code (`missing_at`, ...)
+    pub fn code(&self) -> &'static str {
+        match self {
+            EmailError::Empty => "empty",
+            EmailError::MissingAt => "missing_at",
+            EmailError::InvalidLocalPart => "invalid_local_part",
+            EmailError::InvalidDomain => "invalid_domain",
+            EmailError::Malformed => "malformed",
+        }
+    }
+}
+
+impl std::fmt::Display for EmailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EmailError::Empty => "email address is empty",
+            EmailError::MissingAt => "email address has no '@'",
+            EmailError::InvalidLocalPart => "email address has an invalid local part",
+            EmailError::InvalidDomain => "email address has an invalid domain",
+            EmailError::Malformed => "email address is malformed",
+        })
+    }
+}
+
+impl std::error::Error for EmailError {}
+
+/// A parsed mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mailbox {
+    pub display_name: Option<String>,
+    /// Unquoted when it is a dot-atom, else the quoted string (quotes included)
+    pub local: String,
+    /// As written; compare lowercased
+    pub domain: String,
+}
+
+impl Mailbox {
+    fn local_is_quoted(&self) -> bool {
+        self.local.starts_with('"')
+    }
+}
+
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+fn is_dot_atom(s: &str) -> bool {
+    !s.is_empty()
+        && s.split('.')
+            .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+/// True when `domain` is a valid host name (labels of letters, digits and inner hyphens).
+pub fn is_valid_domain(domain: &str) -> bool {
+    domain.len() <= 253
+        && !domain.is_empty()
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || !c.is_ascii())
+        })
+}
+
+/// Contents of the quoted string `s` (quotes included), or `None` when it is not one.
+fn unquote(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next()?),
+            '"' => return None,
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Remove `(comments)` outside quoted strings and domain literals.
+fn strip_comments(s: &str) -> Result<String, EmailError> {
+    let mut out = String::with_capacity(s.len());
+    let (mut depth, mut quoted, mut literal) = (0usize, false, false);
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if depth > 0 {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '\\' if quoted => {
+                out.push(c);
+                out.extend(chars.next());
+                continue;
+            }
+            '"' if !literal => quoted = !quoted,
+            '[' if !quoted => literal = true,
+            ']' if !quoted => literal = false,
+            '(' if !quoted && !literal => {
+                depth = 1;
+                continue;
+            }
+            ')' if !quoted && !literal => return Err(EmailError::Malformed),
+            _ => {}
+        }
+        out.push(c);
+    }
+    if depth > 0 || quoted || literal {
+        return Err(EmailError::Malformed);
+    }
+    Ok(out)
+}
+
+/// Byte offsets of `target` outside quoted strings.
+fn unquoted_positions(s: &str, target: char) -> Vec<usize> {
+    let mut found = Vec::new();
+    let (mut quoted, mut escaped) = (false, false);
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if !quoted && c == target {
+            found.push(i);
+        }
+    }
+    found
+}
+
+fn parse_display_name(s: &str) -> Result<Option<String>, EmailError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    if s.starts_with('"') {
+        return unquote(s).map(Some).ok_or(EmailError::Malformed);
+    }
+    // A phrase of atoms; dots are allowed as in the obsolete syntax ("J. Doe")
+    if s.split_whitespace()
+        .all(|w| w.chars().all(|c| is_atext(c) || c == '.'))
+    {
+        Ok(Some(s.split_whitespace().collect::<Vec<_>>().join(" ")))
     } else {
-        lower
+        Err(EmailError::Malformed)
+    }
+}
+
+fn parse_addr_spec(s: &str) -> Result<(String, String), EmailError> {
+    let at = *unquoted_positions(s, '@')
+        .last()
+        .ok_or(EmailError::MissingAt)?;
+    let (local, domain) = (s[..at].trim(), s[at + 1..].trim());
+    let local = if local.starts_with('"') {
+        let text = unquote(local).ok_or(EmailError::InvalidLocalPart)?;
+        if is_dot_atom(&text) {
+            text
+        } else if text.is_empty() {
+            return Err(EmailError::InvalidLocalPart);
+        } else {
+            quote(&text)
+        }
+    } else if is_dot_atom(local) {
+        local.to_string()
+    } else {
+        return Err(EmailError::InvalidLocalPart);
+    };
+    if local.len() > 64 {
+        return Err(EmailError::InvalidLocalPart);
+    }
+    let domain_ok = match domain.strip_prefix('[') {
+        Some(rest) => rest.strip_suffix(']').is_some_and(|lit| {
+            !lit.is_empty() && !lit.contains(|c: char| c == '[' || c == '\\' || c.is_whitespace())
+        }),
+        None => is_valid_domain(domain),
+    };
+    if !domain_ok {
+        return Err(EmailError::InvalidDomain);
+    }
+    Ok((local, domain.to_string()))
+}
+
+/// Parse an RFC 5322 mailbox (`addr-spec` or `display-name <addr-spec>`).
+pub fn parse_address(raw: &str) -> Result<Mailbox, EmailError> {
+    let s = strip_comments(raw)?;
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(EmailError::Empty);
+    }
+    let opens = unquoted_positions(s, '<');
+    let closes = unquoted_positions(s, '>');
+    let (display_name, spec) = match (opens.as_slice(), closes.as_slice()) {
+        ([], []) => (None, s),
+        ([open], [close]) if *close == s.len() - 1 && open < close => {
+            (parse_display_name(&s[..*open])?, &s[open + 1..*close])
+        }
+        _ => return Err(EmailError::Malformed),
+    };
+    let (local, domain) = parse_addr_spec(spec.trim())?;
+    Ok(Mailbox {
+        display_name,
+        local,
+        domain,
+    })
+}
+
+/// Normalize `raw` to `local@domain` under `rules`, dropping any display name.
+pub fn normalize_with(raw: &str, rules: &EmailRules) -> Result<String, EmailError> {
+    let mailbox = parse_address(raw)?;
+    let domain = mailbox.domain.to_lowercase();
+    let rule = rule_for(rules, &domain);
+    let mut local = mailbox.local.clone();
+    if !mailbox.local_is_quoted() {
+        if rule.strip_plus_tags {
+            // "+tag@x" has nothing before the tag, so it is left alone
+            if let Some(base) = local.split('+').next().filter(|b| !b.is_empty()) {
+                local = base.to_string();
+            }
+        }
+        if rule.ignore_dots {
+            local.retain(|c| c != '.');
+        }
+    }
+    if !rule.case_sensitive {
+        local = local.to_lowercase();
     }
+    Ok(format!("{local}@{domain}"))
+}
+
+/// Normalize an address for conversation keys with `rules`. An address that does not parse is
+/// only lowercased, so key derivation never fails.
+pub fn normalize_email(addr: &str, rules: &EmailRules) -> String {
+    normalize_with(addr, rules).unwrap_or_else(|_| addr.trim().to_ascii_lowercase())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn builtin(raw: &str) -> String {
+        normalize_with(raw, &builtin_rules()).unwrap()
+    }
+
     #[test]
     fn lowers_case() {
-        assert_eq!(normalize_email("USER@Example.COM"), "user@example.com");
+        assert_eq!(
+            normalize_email("USER@Example.COM", &builtin_rules()),
+            "user@example.com"
+        );
     }
 
     #[test]
     fn strips_plus_tag() {
-        assert_eq!(normalize_email("user+tag@example.com"), "user@example.com");
+        assert_eq!(
+            normalize_email("user+tag@example.com", &builtin_rules()),
+            "user@example.com"
+        );
     }
 
     #[test]
     fn leaves_no_at() {
-        assert_eq!(normalize_email("not-an-email"), "not-an-email");
+        assert_eq!(
+            normalize_email("not-an-email", &builtin_rules()),
+            "not-an-email"
+        );
+    }
+
+    #[test]
+    fn parses_display_names_and_comments() {
+        let m = parse_address("\"Bob \\\"B\\\" Smith\" <Bob@X.com>").unwrap();
+        assert_eq!(m.display_name.as_deref(), Some("Bob \"B\" Smith"));
+        assert_eq!((m.local.as_str(), m.domain.as_str()), ("Bob", "X.com"));
+        let m = parse_address("J. Doe <jdoe@example.com>").unwrap();
+        assert_eq!(m.display_name.as_deref(), Some("J. Doe"));
+        assert_eq!(builtin("<bob@x.com>"), "bob@x.com");
+        assert_eq!(builtin("bob(work)@x.com (Bob)"), "bob@x.com");
+        assert_eq!(builtin("\"bob\"@x.com"), "bob@x.com");
+        assert_eq!(builtin("\"John Doe\"@X.com"), "\"john doe\"@x.com");
+        assert_eq!(builtin("\"a@b\"@x.com"), "\"a@b\"@x.com");
+        assert_eq!(builtin("user@[192.0.2.1]"), "user@[192.0.2.1]");
+    }
+
+    #[test]
+    fn rejects_invalid_addresses() {
+        assert_eq!(parse_address("  "), Err(EmailError::Empty));
+        assert_eq!(parse_address("bob.example.com"), Err(EmailError::MissingAt));
+        assert_eq!(
+            parse_address("bob..x@x.com"),
+            Err(EmailError::InvalidLocalPart)
+        );
+        assert_eq!(
+            parse_address(".bob@x.com"),
+            Err(EmailError::InvalidLocalPart)
+        );
+        assert_eq!(
+            parse_address("bob smith@x.com"),
+            Err(EmailError::InvalidLocalPart)
+        );
+        assert_eq!(parse_address("bob@-x.com"), Err(EmailError::InvalidDomain));
+        assert_eq!(parse_address("bob@x..com"), Err(EmailError::InvalidDomain));
+        assert_eq!(parse_address("bob@"), Err(EmailError::InvalidDomain));
+        assert_eq!(parse_address("Bob <bob@x.com"), Err(EmailError::Malformed));
+        assert_eq!(
+            parse_address("<a@x.com> <b@x.com>"),
+            Err(EmailError::Malformed)
+        );
+        assert_eq!(
+            parse_address("\"Bob <bob@x.com>"),
+            Err(EmailError::Malformed)
+        );
+        assert_eq!(parse_address("bob@x.com (open"), Err(EmailError::Malformed));
+    }
+
+    #[test]
+    fn rules_apply_per_domain() {
+        assert_eq!(builtin("J.Doe+news@GMail.com"), "jdoe@gmail.com");
+        assert_eq!(builtin("J.Doe+news@example.com"), "j.doe@example.com");
+        assert_eq!(builtin("+tag@example.com"), "+tag@example.com");
+
+        let mut rules = builtin_rules();
+        rules.insert(
+            "example.com".into(),
+            EmailRule {
+                strip_plus_tags: false,
+                case_sensitive: true,
+                ..EmailRule::default()
+            },
+        );
+        rules.insert(
+            "*".into(),
+            EmailRule {
+                strip_plus_tags: false,
+                ..EmailRule::default()
+            },
+        );
+        let norm = |raw| normalize_with(raw, &rules).unwrap();
+        assert_eq!(norm("Bob+Ops@Example.COM"), "Bob+Ops@example.com");
+        assert_eq!(norm("Bob+Ops@other.org"), "bob+ops@other.org");
+        assert_eq!(norm("b.o.b+x@gmail.com"), "bob@gmail.com");
     }
 }
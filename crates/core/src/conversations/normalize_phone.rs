@@ -1,25 +1,279 @@
-/// Normalize phone numbers: keep leading '+' if present, retain digits only.
-pub fn normalize_phone(raw: &str) -> String {
-    let mut out = String::new();
-    for (i, ch) in raw.chars().enumerate() {
-        if ch.is_ascii_digit() || (ch == '+' && i == 0) {
-            out.push(ch);
+//! E.164 phone normalization.
+//!
+//! Numbers written with a leading `+` (or the region's international prefix, e.g. `011` / `00`)
+//! are taken as international; anything else is a national number of the default region, whose
+//! trunk prefix (`1` in NANP, `0` in most of Europe) is dropped. The national significant number
+//! must have a length valid for its country code. Calling codes missing from [`REGIONS`] only get
+//! the generic E.164 check (at most 15 digits).
+
+/// Numbering rules of one region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhoneRegion {
+    /// ISO 3166-1 alpha-2 code, e.g. `US`
+    pub iso: &'static str,
+    pub calling_code: &'static str,
+    /// Valid lengths of the national significant number
+    pub min_len: usize,
+    pub max_len: usize,
+    /// Dialled before national numbers inside the region
+    pub trunk_prefix: Option<&'static str>,
+    /// Dialled before international numbers from inside the region
+    pub intl_prefix: &'static str,
+}
+
+const fn r(
+    iso: &'static str,
+    calling_code: &'static str,
+    min_len: usize,
+    max_len: usize,
+    trunk_prefix: Option<&'static str>,
+    intl_prefix: &'static str,
+) -> PhoneRegion {
+    PhoneRegion {
+        iso,
+        calling_code,
+        min_len,
+        max_len,
+        trunk_prefix,
+        intl_prefix,
+    }
+}
+
+/// Built-in regions. Regions sharing a calling code (US/CA) share its length rules.
+pub const REGIONS: &[PhoneRegion] = &[
+    r("US", "1", 10, 10, Some("1"), "011"),
+    r("CA", "1", 10, 10, Some("1"), "011"),
+    r("RU", "7", 10, 10, Some("8"), "810"),
+    r("EG", "20", 8, 10, Some("0"), "00"),
+    r("ZA", "27", 9, 9, Some("0"), "00"),
+    r("GR", "30", 10, 10, None, "00"),
+    r("NL", "31", 9, 9, Some("0"), "00"),
+    r("BE", "32", 8, 9, Some("0"), "00"),
+    r("FR", "33", 9, 9, Some("0"), "00"),
+    r("ES", "34", 9, 9, None, "00"),
+    r("HU", "36", 8, 9, Some("06"), "00"),
+    r("IT", "39", 6, 11, None, "00"),
+    r("RO", "40", 9, 9, Some("0"), "00"),
+    r("CH", "41", 9, 9, Some("0"), "00"),
+    r("AT", "43", 4, 13, Some("0"), "00"),
+    r("GB", "44", 9, 10, Some("0"), "00"),
+    r("DK", "45", 8, 8, None, "00"),
+    r("SE", "46", 7, 10, Some("0"), "00"),
+    r("NO", "47", 8, 8, None, "00"),
+    r("PL", "48", 9, 9, None, "00"),
+    r("DE", "49", 5, 13, Some("0"), "00"),
+    r("PE", "51", 8, 9, Some("0"), "00"),
+    r("MX", "52", 10, 10, None, "00"),
+    r("AR", "54", 10, 10, Some("0"), "00"),
+    r("BR", "55", 10, 11, Some("0"), "00"),
+    r("CL", "56", 9, 9, None, "00"),
+    r("CO", "57", 10, 10, Some("0"), "00"),
+    r("MY", "60", 8, 10, Some("0"), "00"),
+    r("AU", "61", 9, 9, Some("0"), "0011"),
+    r("ID", "62", 8, 12, Some("0"), "001"),
+    r("PH", "63", 8, 10, Some("0"), "00"),
+    r("NZ", "64", 8, 10, Some("0"), "00"),
+    r("SG", "65", 8, 8, None, "000"),
+    r("TH", "66", 8, 9, Some("0"), "001"),
+    r("JP", "81", 9, 10, Some("0"), "010"),
+    r("KR", "82", 8, 10, Some("0"), "001"),
+    r("VN", "84", 9, 10, Some("0"), "00"),
+    r("CN", "86", 10, 11, Some("0"), "00"),
+    r("TR", "90", 10, 10, Some("0"), "00"),
+    r("IN", "91", 10, 10, Some("0"), "00"),
+    r("PK", "92", 9, 10, Some("0"), "00"),
+    r("MA", "212", 9, 9, Some("0"), "00"),
+    r("NG", "234", 8, 10, Some("0"), "009"),
+    r("KE", "254", 9, 9, Some("0"), "000"),
+    r("PT", "351", 9, 9, None, "00"),
+    r("IE", "353", 7, 9, Some("0"), "00"),
+    r("FI", "358", 5, 12, Some("0"), "00"),
+    r("UA", "380", 9, 9, Some("0"), "00"),
+    r("CZ", "420", 9, 9, None, "00"),
+    r("HK", "852", 8, 8, None, "001"),
+    r("TW", "886", 8, 9, Some("0"), "002"),
+    r("AE", "971", 8, 9, Some("0"), "00"),
+    r("IL", "972", 8, 9, Some("0"), "00"),
+    r("SA", "966", 9, 9, Some("0"), "00"),
+];
+
+/// Why a number is not a valid E.164 number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneError {
+    Empty,
+    /// Letters or symbols other than spaces, `-`, `.`, `/` and parentheses
+    InvalidCharacters,
+    /// Wrong number of digits for the country code
+    InvalidLength,
+    UnknownRegion,
+}
+
+impl PhoneError {
+    /// Machine-readable code (`invalid_length`, ...)
+    pub fn code(&self) -> &'static str {
+        match self {
+            PhoneError::Empty => "empty",
+            PhoneError::InvalidCharacters => "invalid_characters",
+            PhoneError::InvalidLength => "invalid_length",
+            PhoneError::UnknownRegion => "unknown_region",
         }
     }
-    out
+}
+
+impl std::fmt::Display for PhoneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PhoneError::Empty => "phone number is empty",
+            PhoneError::InvalidCharacters => "phone number contains invalid characters",
+            PhoneError::InvalidLength => "phone number has an invalid length for its country code",
+            PhoneError::UnknownRegion => "unknown phone region",
+        })
+    }
+}
+
+impl std::error::Error for PhoneError {}
+
+/// Look up a region by ISO code (case-insensitive).
+pub fn region(iso: &str) -> Option<&'static PhoneRegion> {
+    REGIONS.iter().find(|r| r.iso.eq_ignore_ascii_case(iso))
+}
+
+/// Region for numbers without a country code when none is configured (US).
+pub fn default_region() -> &'static PhoneRegion {
+    &REGIONS[0]
+}
+
+/// Check the digits after `+` against the length rules of their calling code.
+fn check_international(digits: &str) -> Result<(), PhoneError> {
+    // ITU calling codes are prefix-free, so at most one entry matches
+    let known = REGIONS.iter().find(|r| digits.starts_with(r.calling_code));
+    let ok = match known {
+        Some(r) => (r.min_len..=r.max_len).contains(&(digits.len() - r.calling_code.len())),
+        None => (4..=15).contains(&digits.len()),
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(PhoneError::InvalidLength)
+    }
+}
+
+/// Convert `raw` to E.164 (`+` and digits), reading numbers without a country code as national
+/// numbers of `region`.
+pub fn to_e164(raw: &str, region: &PhoneRegion) -> Result<String, PhoneError> {
+    let trimmed = raw.trim();
+    let (plus, rest) = match trimmed.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    let mut digits = String::with_capacity(rest.len());
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '/' | '(' | ')' => {}
+            _ => return Err(PhoneError::InvalidCharacters),
+        }
+    }
+    if digits.is_empty() {
+        return Err(PhoneError::Empty);
+    }
+    let international = if plus {
+        digits
+    } else if let Some(after) = digits.strip_prefix(region.intl_prefix) {
+        after.to_string()
+    } else {
+        let national = match region.trunk_prefix {
+            // A '0' trunk prefix never starts a national number; others (NANP '1', Russia '8')
+            // are only stripped from numbers too long to be national
+            Some(t) if digits.len() > region.max_len => digits.strip_prefix(t).unwrap_or(&digits),
+            Some(t) if digits.starts_with(t) && t.starts_with('0') => &digits[t.len()..],
+            _ => &digits,
+        };
+        format!("{}{}", region.calling_code, national)
+    };
+    check_international(&international)?;
+    Ok(format!("+{international}"))
+}
+
+/// Normalize a phone number for conversation keys: E.164 in `region`, so "+1 555 000 1234" and
+/// "555-000-1234" give the same key. A number that does not parse keeps its leading '+' and
+/// digits only.
+pub fn normalize_phone(raw: &str, region: &PhoneRegion) -> String {
+    to_e164(raw, region).unwrap_or_else(|_| {
+        let mut out = String::new();
+        for (i, ch) in raw.chars().enumerate() {
+            if ch.is_ascii_digit() || (ch == '+' && i == 0) {
+                out.push(ch);
+            }
+        }
+        out
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn us() -> &'static PhoneRegion {
+        region("US").unwrap()
+    }
+
     #[test]
     fn keeps_leading_plus_and_digits() {
-        assert_eq!(normalize_phone("+1 (555) 000-1234"), "+15550001234");
+        assert_eq!(normalize_phone("+1 (555) 000-1234", us()), "+15550001234");
     }
 
     #[test]
     fn strips_formatting_no_plus() {
-        assert_eq!(normalize_phone("(555) 000-1234"), "5550001234");
+        assert_eq!(normalize_phone("(555) 000-1234", us()), "+15550001234");
+    }
+
+    #[test]
+    fn national_numbers_use_the_region() {
+        assert_eq!(to_e164("555-000-1234", us()).unwrap(), "+15550001234");
+        assert_eq!(to_e164("1 555 000 1234", us()).unwrap(), "+15550001234");
+        assert_eq!(
+            to_e164("011 44 20 7946 0958", us()).unwrap(),
+            "+442079460958"
+        );
+        let gb = region("gb").unwrap();
+        assert_eq!(to_e164("020 7946 0958", gb).unwrap(), "+442079460958");
+        assert_eq!(to_e164("00 1 555 000 1234", gb).unwrap(), "+15550001234");
+        let de = region("DE").unwrap();
+        assert_eq!(to_e164("030 123456", de).unwrap(), "+4930123456");
+    }
+
+    #[test]
+    fn lengths_are_checked_per_country_code() {
+        assert_eq!(to_e164("+1555000123", us()), Err(PhoneError::InvalidLength));
+        assert_eq!(
+            to_e164("+155500012345", us()),
+            Err(PhoneError::InvalidLength)
+        );
+        assert_eq!(to_e164("+44 20 7946 0958", us()).unwrap(), "+442079460958");
+        assert_eq!(to_e164("+44 20 7946", us()), Err(PhoneError::InvalidLength));
+        // Unlisted calling code: generic E.164 limit only
+        assert_eq!(to_e164("+299 123456", us()).unwrap(), "+299123456");
+        assert_eq!(
+            to_e164("+299 1234567890123", us()),
+            Err(PhoneError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn rejects_unparseable_numbers() {
+        assert_eq!(to_e164("", us()), Err(PhoneError::Empty));
+        assert_eq!(to_e164("+", us()), Err(PhoneError::Empty));
+        assert_eq!(
+            to_e164("+1555SMS", us()),
+            Err(PhoneError::InvalidCharacters)
+        );
+        assert_eq!(
+            to_e164("555+0001234", us()),
+            Err(PhoneError::InvalidCharacters)
+        );
+        assert_eq!(region("XX"), None);
+        // Unparseable input still gets a (digits-only) key
+        assert_eq!(normalize_phone("+1555SMS", us()), "+1555");
     }
 }
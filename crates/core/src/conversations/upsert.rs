@@ -1,4 +1,5 @@
-use crate::conversations::{key::derive_key, key::ChannelKind, ConversationKey};
+use crate::conversations::key::{derive_key, ChannelKind, KeyNormalization};
+use crate::conversations::ConversationKey;
 use sqlx::{PgPool, Postgres, Row, Transaction};
 use tracing::{error, info, instrument};
 
@@ -10,15 +11,16 @@ pub enum UpsertOutcome {
 }
 
 /// Upsert conversation returning id and derived key. Message count & last_activity updated by caller.
-#[instrument(skip(pool))]
+#[instrument(skip(pool, norm))]
 pub async fn upsert_conversation(
     pool: &PgPool,
+    norm: &KeyNormalization,
     channel: ChannelKind,
     from: &str,
     to: &str,
     activity_ts: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>,
 ) -> UpsertOutcome {
-    let k = derive_key(channel.clone(), from, to);
+    let k = derive_key(channel.clone(), from, to, norm);
     // Use transaction for atomicity
     let mut tx: Transaction<'_, Postgres> = match pool.begin().await {
         Ok(t) => t,
@@ -57,8 +59,8 @@ pub async fn upsert_conversation(
         Ok(None) => {
             // Insert new conversation
             let ins = sqlx::query(
-                r#"INSERT INTO conversations(channel, participant_a, participant_b, message_count, last_activity_at, key)
-                   VALUES ($1,$2,$3,0,$4,$5)
+                r#"INSERT INTO conversations(channel, participant_a, participant_b, message_count, last_activity_at, key, key_version)
+                   VALUES ($1,$2,$3,0,$4,$5,$6)
                    ON CONFLICT (channel, participant_a, participant_b) DO UPDATE
                      SET last_activity_at = GREATEST(conversations.last_activity_at, EXCLUDED.last_activity_at)
                    RETURNING id"#,
@@ -68,6 +70,7 @@ pub async fn upsert_conversation(
             .bind(&k.participant_b)
             .bind(activity_ts)
             .bind(&k.key)
+            .bind(k.version)
             .fetch_one(&mut *tx)
             .await;
             match ins {
@@ -105,6 +108,7 @@ mod tests {
         let ts = sqlx::types::chrono::Utc::now();
         let first = upsert_conversation(
             &pool,
+            &KeyNormalization::default(),
             ChannelKind::Email,
             "a@example.com",
             "b@example.com",
@@ -117,6 +121,7 @@ mod tests {
         }
         let again = upsert_conversation(
             &pool,
+            &KeyNormalization::default(),
             ChannelKind::Email,
             "b@example.com",
             "a@example.com",
@@ -0,0 +1,117 @@
+//! Dead-letter inspection and replay for `inbound_events`.
+//!
+//! The inbound worker moves events to `status='dead'` once `worker_max_retries` is exceeded.
+//! These queries back both the server's `/admin/dead-letters` endpoints and the
+//! `messaging-admin dead-letters` subcommands so the two stay in lockstep.
+
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{PgPool, Row};
+
+/// A dead `inbound_events` row. `payload` is only populated by [`get`].
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub event_type: String,
+    pub channel: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub provider_message_id: Option<String>,
+    pub attempts: i32,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    pub received_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Raw JSON payload text
+    pub payload: Option<String>,
+}
+
+/// Which dead letters a bulk operation applies to.
+#[derive(Debug, Clone, Copy)]
+pub enum Selection<'a> {
+    Ids(&'a [i64]),
+    All,
+}
+
+const COLUMNS: &str = r#"id, event_type, channel, "from", "to", provider_message_id, attempts,
+    error_code, error_message, received_at, updated_at"#;
+
+fn from_row(row: &sqlx::postgres::PgRow, with_payload: bool) -> Result<DeadLetter, sqlx::Error> {
+    Ok(DeadLetter {
+        id: row.try_get("id")?,
+        event_type: row.try_get("event_type")?,
+        channel: row.try_get("channel")?,
+        from: row.try_get("from")?,
+        to: row.try_get("to")?,
+        provider_message_id: row.try_get("provider_message_id")?,
+        attempts: row.try_get("attempts")?,
+        error_code: row.try_get("error_code")?,
+        error_message: row.try_get("error_message")?,
+        received_at: row.try_get("received_at")?,
+        updated_at: row.try_get("updated_at")?,
+        payload: if with_payload {
+            row.try_get("payload")?
+        } else {
+            None
+        },
+    })
+}
+
+/// Most recently dead-lettered first.
+pub async fn list(pool: &PgPool, limit: i64, offset: i64) -> Result<Vec<DeadLetter>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {COLUMNS} FROM inbound_events WHERE status='dead'
+            ORDER BY updated_at DESC, id DESC LIMIT $1 OFFSET $2"
+    );
+    let rows = sqlx::query(&sql)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+    rows.iter().map(|r| from_row(r, false)).collect()
+}
+
+pub async fn count(pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM inbound_events WHERE status='dead'")
+        .fetch_one(pool)
+        .await
+}
+
+/// Fetch one dead letter including its payload; `None` if missing or not dead.
+pub async fn get(pool: &PgPool, id: i64) -> Result<Option<DeadLetter>, sqlx::Error> {
+    let sql = format!(
+        "SELECT {COLUMNS}, payload::text AS payload FROM inbound_events WHERE id=$1 AND status='dead'"
+    );
+    let row = sqlx::query(&sql).bind(id).fetch_optional(pool).await?;
+    row.map(|r| from_row(&r, true)).transpose()
+}
+
+/// Return dead letters to `pending` with a fresh retry budget, available immediately.
+pub async fn requeue(pool: &PgPool, selection: Selection<'_>) -> Result<u64, sqlx::Error> {
+    const SET: &str = "UPDATE inbound_events SET status='pending', attempts=0, available_at=now(),
+            processor_id=NULL, updated_at=now() WHERE status='dead'";
+    let res = match selection {
+        Selection::Ids(ids) => {
+            sqlx::query(&format!("{SET} AND id = ANY($1)"))
+                .bind(ids)
+                .execute(pool)
+                .await?
+        }
+        Selection::All => sqlx::query(SET).execute(pool).await?,
+    };
+    Ok(res.rows_affected())
+}
+
+/// Permanently delete dead letters.
+pub async fn purge(pool: &PgPool, selection: Selection<'_>) -> Result<u64, sqlx::Error> {
+    const DELETE: &str = "DELETE FROM inbound_events WHERE status='dead'";
+    let res = match selection {
+        Selection::Ids(ids) => {
+            sqlx::query(&format!("{DELETE} AND id = ANY($1)"))
+                .bind(ids)
+                .execute(pool)
+                .await?
+        }
+        Selection::All => sqlx::query(DELETE).execute(pool).await?,
+    };
+    Ok(res.rows_affected())
+}
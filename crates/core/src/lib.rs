@@ -2,6 +2,8 @@
 
 pub mod config;
 pub mod conversations;
+pub mod dead_letters;
 pub mod logging;
+pub mod sms;
 
 pub use config::Config;
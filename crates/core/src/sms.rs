@@ -0,0 +1,213 @@
+//! SMS encoding detection and segment counting.
+//!
+//! A body that fits the GSM 03.38 alphabet (basic table plus the extension table, whose
+//! characters take an escape septet each) is sent as GSM-7; anything else as UCS-2 (UTF-16 code
+//! units). A single segment holds 160 septets or 70 code units. Longer bodies are split into
+//! concatenated segments whose 6-byte UDH leaves 153 septets or 67 code units each; an escape
+//! sequence or a surrogate pair is never split across segments.
+
+use std::borrow::Cow;
+
+/// GSM 03.38 basic character table (excluding the escape code 0x1B)
+const GSM7_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+/// GSM 03.38 extension table: each character costs two septets
+const GSM7_EXTENSION: &str = "\u{0C}^{}\\[~]|€";
+
+const GSM7_SINGLE: usize = 160;
+const GSM7_MULTI: usize = 153;
+const UCS2_SINGLE: usize = 70;
+const UCS2_MULTI: usize = 67;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsEncoding {
+    Gsm7,
+    Ucs2,
+}
+
+impl SmsEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmsEncoding::Gsm7 => "gsm7",
+            SmsEncoding::Ucs2 => "ucs2",
+        }
+    }
+}
+
+/// How a body is encoded and how many segments it takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segmentation {
+    pub encoding: SmsEncoding,
+    /// Septets (GSM-7) or UTF-16 code units (UCS-2)
+    pub units: usize,
+    /// An empty body still takes one segment
+    pub segments: usize,
+}
+
+/// Septets needed for `c` in GSM-7, or `None` when it is outside the alphabet.
+fn gsm7_cost(c: char) -> Option<usize> {
+    if GSM7_BASIC.contains(c) {
+        Some(1)
+    } else if GSM7_EXTENSION.contains(c) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// True when every character of `text` can be sent as GSM-7.
+pub fn is_gsm7(text: &str) -> bool {
+    text.chars().all(|c| gsm7_cost(c).is_some())
+}
+
+/// Detect the encoding of `text` and count its segments.
+pub fn segment(text: &str) -> Segmentation {
+    let gsm7: Option<Vec<usize>> = text.chars().map(gsm7_cost).collect();
+    let (encoding, costs, single, multi) = match gsm7 {
+        Some(costs) => (SmsEncoding::Gsm7, costs, GSM7_SINGLE, GSM7_MULTI),
+        None => (
+            SmsEncoding::Ucs2,
+            text.chars().map(char::len_utf16).collect(),
+            UCS2_SINGLE,
+            UCS2_MULTI,
+        ),
+    };
+    let units = costs.iter().sum();
+    let segments = if units <= single {
+        1
+    } else {
+        // Fill each segment, moving a character that does not fit whole to the next one
+        let (mut segments, mut used) = (1, 0);
+        for cost in costs {
+            if used + cost > multi {
+                segments += 1;
+                used = 0;
+            }
+            used += cost;
+        }
+        segments
+    };
+    Segmentation {
+        encoding,
+        units,
+        segments,
+    }
+}
+
+/// GSM-7 stand-in for a common character outside the alphabet.
+fn gsm7_substitute(c: char) -> Option<&'static str> {
+    Some(match c {
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' | '`' | '´' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' | '«' | '»' => "\"",
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2212}' => "-",
+        '\u{2026}' => "...",
+        '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2009}' | '\u{202F}' => " ",
+        '\u{2022}' => "*",
+        '\t' => " ",
+        _ => return None,
+    })
+}
+
+/// Replace smart quotes, dashes, ellipses and unusual spaces with GSM-7 equivalents. Other
+/// characters are left alone, so the result may still need UCS-2.
+pub fn transliterate(text: &str) -> Cow<'_, str> {
+    if !text.chars().any(|c| gsm7_substitute(c).is_some()) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match gsm7_substitute(c) {
+            Some(s) => out.push_str(s),
+            None => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Segment `text`, transliterating it first when `transliterate` is set and that makes the whole
+/// body GSM-7. Returns the body to send alongside its segmentation.
+pub fn prepare(text: &str, transliterate: bool) -> (Cow<'_, str>, Segmentation) {
+    if transliterate && !is_gsm7(text) {
+        let replaced = self::transliterate(text);
+        if is_gsm7(&replaced) {
+            let seg = segment(&replaced);
+            return (replaced, seg);
+        }
+    }
+    (Cow::Borrowed(text), segment(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_ascii_is_gsm7() {
+        let s = segment("Hello, world!");
+        assert_eq!(s.encoding, SmsEncoding::Gsm7);
+        assert_eq!((s.units, s.segments), (13, 1));
+        assert_eq!(segment("").segments, 1);
+    }
+
+    #[test]
+    fn gsm7_segment_boundaries() {
+        assert_eq!(segment(&"a".repeat(160)).segments, 1);
+        assert_eq!(segment(&"a".repeat(161)).segments, 2);
+        assert_eq!(segment(&"a".repeat(306)).segments, 2);
+        assert_eq!(segment(&"a".repeat(307)).segments, 3);
+    }
+
+    #[test]
+    fn extension_characters_take_two_septets() {
+        let s = segment("€[]");
+        assert_eq!(s.encoding, SmsEncoding::Gsm7);
+        assert_eq!(s.units, 6);
+        assert_eq!(segment(&"€".repeat(80)).segments, 1);
+        assert_eq!(segment(&"€".repeat(81)).segments, 2);
+        // 152 septets then an escape sequence: it moves whole to the second segment
+        let body = format!("{}€{}", "a".repeat(152), "a".repeat(10));
+        let s = segment(&body);
+        assert_eq!((s.units, s.segments), (164, 2));
+        let body = format!("{}€", "a".repeat(152 + 153));
+        assert_eq!(segment(&body).segments, 3);
+    }
+
+    #[test]
+    fn non_gsm_characters_switch_to_ucs2() {
+        let s = segment("Grüße, Ελλάδα");
+        assert_eq!(s.encoding, SmsEncoding::Ucs2);
+        assert_eq!(segment(&"ж".repeat(70)).segments, 1);
+        assert_eq!(segment(&"ж".repeat(71)).segments, 2);
+        assert_eq!(segment(&"ж".repeat(134)).segments, 2);
+        assert_eq!(segment(&"ж".repeat(135)).segments, 3);
+    }
+
+    #[test]
+    fn surrogate_pairs_are_not_split() {
+        let s = segment("👍");
+        assert_eq!((s.encoding, s.units), (SmsEncoding::Ucs2, 2));
+        assert_eq!(segment(&"👍".repeat(35)).segments, 1);
+        // 66 units then a pair that would straddle the boundary
+        let body = format!("{}👍{}", "ж".repeat(66), "ж".repeat(5));
+        let s = segment(&body);
+        assert_eq!((s.units, s.segments), (73, 2));
+    }
+
+    #[test]
+    fn transliteration_keeps_bodies_in_gsm7() {
+        let text = "\u{201C}Don\u{2019}t\u{201D} \u{2013} wait\u{2026}";
+        assert_eq!(transliterate(text), "\"Don't\" - wait...");
+        let (body, seg) = prepare(text, true);
+        assert_eq!(body, "\"Don't\" - wait...");
+        assert_eq!(seg.encoding, SmsEncoding::Gsm7);
+        let (body, seg) = prepare(text, false);
+        assert_eq!(body, text);
+        assert_eq!(seg.encoding, SmsEncoding::Ucs2);
+        // Transliteration cannot save a body with emoji, so it is left as written
+        let (body, seg) = prepare("\u{2019}👍", true);
+        assert_eq!(body, "\u{2019}👍");
+        assert_eq!(seg.encoding, SmsEncoding::Ucs2);
+        assert!(matches!(transliterate("plain"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,4 @@
+-- Wake inbound workers on new events via LISTEN/NOTIFY (DOWN)
+
+DROP TRIGGER IF EXISTS trg_inbound_events_notify ON inbound_events;
+DROP FUNCTION IF EXISTS notify_inbound_event();
@@ -0,0 +1,15 @@
+-- Wake inbound workers on new events via LISTEN/NOTIFY (UP)
+-- Payload is the inbound_events.id; workers treat it as a hint and still claim via SKIP LOCKED.
+
+CREATE OR REPLACE FUNCTION notify_inbound_event()
+RETURNS TRIGGER AS $$
+BEGIN
+  PERFORM pg_notify('inbound_events', NEW.id::text);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_inbound_events_notify ON inbound_events;
+CREATE TRIGGER trg_inbound_events_notify
+  AFTER INSERT ON inbound_events
+  FOR EACH ROW EXECUTE FUNCTION notify_inbound_event();
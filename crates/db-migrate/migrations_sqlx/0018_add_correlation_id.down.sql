@@ -0,0 +1,5 @@
+-- Request correlation IDs on queued events and persisted messages (DOWN)
+
+DROP INDEX IF EXISTS idx_messages_correlation_id;
+ALTER TABLE messages DROP COLUMN IF EXISTS correlation_id;
+ALTER TABLE inbound_events DROP COLUMN IF EXISTS correlation_id;
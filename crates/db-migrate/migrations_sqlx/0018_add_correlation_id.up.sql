@@ -0,0 +1,8 @@
+-- Request correlation IDs on queued events and persisted messages (UP)
+-- Nullable: rows written before this migration, and work not started by an HTTP request, have none.
+
+ALTER TABLE inbound_events ADD COLUMN IF NOT EXISTS correlation_id TEXT;
+ALTER TABLE messages ADD COLUMN IF NOT EXISTS correlation_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_messages_correlation_id
+  ON messages (correlation_id) WHERE correlation_id IS NOT NULL;
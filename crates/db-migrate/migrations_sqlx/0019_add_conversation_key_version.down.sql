@@ -0,0 +1,4 @@
+-- Version of the normalization each conversation key was derived with (DOWN)
+
+DROP INDEX IF EXISTS idx_conversations_key_version;
+ALTER TABLE conversations DROP COLUMN IF EXISTS key_version;
@@ -0,0 +1,7 @@
+-- Version of the normalization each conversation key was derived with (UP)
+-- Existing rows predate versioning and carry version 1; `db-migrate rekey` moves them to the
+-- current version, merging conversations whose keys now collide.
+
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS key_version INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_conversations_key_version ON conversations (key_version);
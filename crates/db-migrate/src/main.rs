@@ -5,6 +5,7 @@ use chrono::Utc;
 use sqlx::{migrate::Migrator, postgres::PgPoolOptions, Row};
 
 mod backfill_conversations;
+mod rekey_conversations;
 
 // Point to the dedicated SQLx migrations directory that contains only .up/.down.sql files
 static MIGRATIONS: Migrator = sqlx::migrate!("./migrations_sqlx");
@@ -19,9 +20,10 @@ async fn main() -> Result<()> {
             create_new_migration_pair(&name)
         }
         Some("status") => status().await,
+        Some("rekey") => rekey(args.collect()).await,
         _ => {
             eprintln!(
-                "Usage:\n  db-migrate apply\n  db-migrate new <name>\n  db-migrate status\n\nENV:\n  DATABASE_URL  Postgres connection URL"
+                "Usage:\n  db-migrate apply\n  db-migrate new <name>\n  db-migrate status\n  db-migrate rekey [--all] [--dry-run] [--batch-size N] [--config <api config toml>]\n    (stop the server first: messages written to a conversation being merged fail)\n\nENV:\n  DATABASE_URL  Postgres connection URL\n  API_CONFIG_FILE / API_PHONE_DEFAULT_REGION  normalization settings for rekey"
             );
             Ok(())
         }
@@ -47,6 +49,56 @@ async fn apply() -> Result<()> {
     Ok(())
 }
 
+/// Re-key conversations stored under an older key version (see `rekey_conversations`).
+async fn rekey(args: Vec<String>) -> Result<()> {
+    let mut dry_run = false;
+    let mut scope = rekey_conversations::RekeyScope::Outdated;
+    let mut batch_size = 500i64;
+    let mut config = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--all" => scope = rekey_conversations::RekeyScope::All,
+            "--batch-size" => {
+                batch_size = args
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|n| *n > 0)
+                    .context("--batch-size needs a positive number")?;
+            }
+            "--config" => {
+                config = Some(PathBuf::from(args.next().context("--config needs a path")?))
+            }
+            other => bail!("unknown rekey option {other:?}"),
+        }
+    }
+    let keys =
+        rekey_conversations::NormalizationConfig::load(config.as_deref())?.key_normalization()?;
+
+    let database_url =
+        env::var("DATABASE_URL").context("DATABASE_URL is required to re-key conversations")?;
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+        .context("failed to connect to database")?;
+
+    let report =
+        rekey_conversations::rekey_conversations(&pool, &keys, scope, batch_size, dry_run).await?;
+    println!(
+        "{}Re-keyed conversations to key version {}: scanned={} rekeyed={} merged={} messages_moved={} skipped={}",
+        if dry_run { "[dry run] " } else { "" },
+        messaging_core::conversations::key::KEY_VERSION,
+        report.scanned,
+        report.rekeyed,
+        report.merged,
+        report.messages_moved,
+        report.skipped
+    );
+    Ok(())
+}
+
 async fn status() -> Result<()> {
     let database_url =
         env::var("DATABASE_URL").context("DATABASE_URL is required to get status")?;
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use messaging_core::conversations::key::{derive_key, ChannelKind, KeyNormalization, KEY_VERSION};
+use messaging_core::conversations::normalize_email::{self, EmailRule};
+use messaging_core::conversations::normalize_phone;
+use serde::Deserialize;
+use sqlx::{Acquire, PgPool, Postgres, Row, Transaction};
+use tracing::{info, warn};
+
+/// The server's API config keys that change how addresses normalize; the rest of the file is
+/// ignored. Re-keying must use the settings the server runs with, or it would split the
+/// conversations it is meant to merge.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NormalizationConfig {
+    pub phone_default_region: Option<String>,
+    pub email_domain_rules: BTreeMap<String, EmailRule>,
+}
+
+impl NormalizationConfig {
+    /// Read `path`, else `API_CONFIG_FILE`, else `crates/server/config/default.toml` when it
+    /// exists. `API_PHONE_DEFAULT_REGION` overrides the file, as it does for the server.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var("API_CONFIG_FILE").ok().map(PathBuf::from))
+            .or_else(|| {
+                let default = PathBuf::from("crates/server/config/default.toml");
+                default.exists().then_some(default)
+            });
+        let mut cfg = match path {
+            Some(p) => {
+                let contents = std::fs::read_to_string(&p)
+                    .with_context(|| format!("cannot read {}", p.display()))?;
+                toml::from_str(&contents)
+                    .with_context(|| format!("cannot parse {}", p.display()))?
+            }
+            None => Self::default(),
+        };
+        if let Ok(region) = std::env::var("API_PHONE_DEFAULT_REGION") {
+            cfg.phone_default_region = Some(region);
+        }
+        Ok(cfg)
+    }
+
+    /// The normalization `derive_key` runs with under these settings.
+    pub fn key_normalization(&self) -> Result<KeyNormalization> {
+        let phone_region = match self.phone_default_region.as_deref() {
+            Some(region) => normalize_phone::region(region)
+                .ok_or_else(|| anyhow!("phone_default_region {region:?}: unknown phone region"))?,
+            None => normalize_phone::default_region(),
+        };
+        Ok(KeyNormalization {
+            phone_region,
+            email_rules: Arc::new(normalize_email::merged_rules(&self.email_domain_rules)),
+        })
+    }
+}
+
+/// Which conversations a re-keying run recomputes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RekeyScope {
+    /// Only those stored under an older `key_version`
+    Outdated,
+    /// Every conversation; needed after `phone_default_region` or `email_domain_rules` change,
+    /// since those do not bump the key version
+    All,
+}
+
+/// How often a conversation is retried when a concurrent writer takes its new key first.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// What a re-keying run did (or, for a dry run, would do).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RekeyReport {
+    /// Conversations read (below the current key version unless re-keying all)
+    pub scanned: u64,
+    /// Conversations whose key changed
+    pub rekeyed: u64,
+    /// Conversations merged into another one that now has the same key
+    pub merged: u64,
+    /// Messages re-pointed by merges
+    pub messages_moved: u64,
+    /// Conversations with an unknown channel, left at their old version
+    pub skipped: u64,
+}
+
+enum Outcome {
+    /// Absorbed by a merge earlier in the run
+    Gone,
+    Unchanged,
+    Rekeyed,
+    Merged {
+        absorbed: u64,
+        moved: u64,
+    },
+}
+
+/// Recompute the key of the conversations in `scope` with the current `derive_key`, in batches
+/// of `batch_size`. A conversation whose new key belongs to another conversation is merged into
+/// the older of the two: its messages are re-pointed, the survivor's `message_count` is
+/// recounted, and the other row is deleted. A key taken by a concurrent writer mid-run is
+/// merged the same way. Each batch is one transaction. A dry run rolls every batch back, so it
+/// holds no locks past its batch; a conversation that would merge into one re-keyed in an
+/// earlier batch is reported as re-keyed instead (a larger `batch_size` avoids that).
+pub async fn rekey_conversations(
+    pool: &PgPool,
+    keys: &KeyNormalization,
+    scope: RekeyScope,
+    batch_size: i64,
+    dry_run: bool,
+) -> Result<RekeyReport> {
+    info!(
+        target = "rekey",
+        key_version = KEY_VERSION,
+        ?scope,
+        dry_run,
+        "Starting conversation re-keying"
+    );
+    let mut report = RekeyReport::default();
+    let mut last_id = 0i64;
+    loop {
+        let mut tx = pool.begin().await?;
+        // Rows without participants predate conversation keys and have nothing to re-key
+        let rows = sqlx::query(
+            r#"SELECT id, channel, participant_a, participant_b, key FROM conversations
+               WHERE (key_version < $1 OR $4) AND id > $2 AND channel IS NOT NULL
+                 AND participant_a IS NOT NULL AND participant_b IS NOT NULL
+               ORDER BY id
+               LIMIT $3"#,
+        )
+        .bind(KEY_VERSION)
+        .bind(last_id)
+        .bind(batch_size)
+        .bind(scope == RekeyScope::All)
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &rows {
+            let id: i64 = row.get("id");
+            last_id = id;
+            report.scanned += 1;
+            let channel: String = row.get("channel");
+            let Some(kind) = ChannelKind::parse(&channel) else {
+                warn!(target = "rekey", conversation_id = id, channel = %channel, "Skipping conversation with unknown channel");
+                report.skipped += 1;
+                continue;
+            };
+            let old_key: Option<String> = row.get("key");
+            let (a, b): (String, String) = (row.get("participant_a"), row.get("participant_b"));
+            match rekey_one(&mut tx, keys, id, kind, &a, &b, old_key.as_deref()).await? {
+                Outcome::Gone | Outcome::Unchanged => {}
+                Outcome::Rekeyed => report.rekeyed += 1,
+                Outcome::Merged { absorbed, moved } => {
+                    report.merged += absorbed;
+                    report.messages_moved += moved;
+                }
+            }
+        }
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        if rows.len() < batch_size as usize {
+            break;
+        }
+    }
+    info!(
+        target = "rekey",
+        scanned = report.scanned,
+        rekeyed = report.rekeyed,
+        merged = report.merged,
+        messages_moved = report.messages_moved,
+        skipped = report.skipped,
+        dry_run,
+        "Conversation re-keying complete"
+    );
+    Ok(report)
+}
+
+async fn rekey_one(
+    tx: &mut Transaction<'_, Postgres>,
+    keys: &KeyNormalization,
+    id: i64,
+    kind: ChannelKind,
+    a: &str,
+    b: &str,
+    old_key: Option<&str>,
+) -> Result<Outcome> {
+    let exists: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM conversations WHERE id = $1 FOR UPDATE")
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+    if exists.is_none() {
+        return Ok(Outcome::Gone);
+    }
+    let k = derive_key(kind, a, b, keys);
+    let (mut target, mut absorbed, mut moved) = (id, 0u64, 0u64);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let holder: Option<i64> = sqlx::query_scalar(
+            r#"SELECT id FROM conversations
+               WHERE channel = $1 AND participant_a = $2 AND participant_b = $3 AND id <> $4
+               FOR UPDATE"#,
+        )
+        .bind(&k.channel)
+        .bind(&k.participant_a)
+        .bind(&k.participant_b)
+        .bind(target)
+        .fetch_optional(&mut **tx)
+        .await?;
+        if let Some(holder) = holder {
+            let (survivor, gone) = (target.min(holder), target.max(holder));
+            let n = merge(tx, survivor, gone).await?;
+            info!(target = "rekey", survivor, absorbed = gone, messages_moved = n, key = %k.key, "Merged conversations sharing a key");
+            target = survivor;
+            absorbed += 1;
+            moved += n;
+        }
+        // A writer that creates a conversation under the new key after the lookup makes the
+        // update violate idx_conversations_unique_key; undo just the update and merge again
+        let mut savepoint = tx.begin().await?;
+        let updated = sqlx::query(
+            r#"UPDATE conversations
+               SET participant_a = $2, participant_b = $3, key = $4, key_version = $5
+               WHERE id = $1"#,
+        )
+        .bind(target)
+        .bind(&k.participant_a)
+        .bind(&k.participant_b)
+        .bind(&k.key)
+        .bind(k.version)
+        .execute(&mut *savepoint)
+        .await;
+        match updated {
+            Ok(_) => {
+                savepoint.commit().await?;
+                break;
+            }
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() && attempt < MAX_ATTEMPTS => {
+                savepoint.rollback().await?;
+                warn!(target = "rekey", conversation_id = target, key = %k.key, attempt, "Key taken by a concurrent writer; merging again");
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(if absorbed > 0 {
+        Outcome::Merged { absorbed, moved }
+    } else if old_key == Some(k.key.as_str()) {
+        Outcome::Unchanged
+    } else {
+        Outcome::Rekeyed
+    })
+}
+
+/// Move everything from `absorbed` to `survivor`, delete `absorbed`, and recount the survivor.
+/// Returns the number of messages moved.
+async fn merge(tx: &mut Transaction<'_, Postgres>, survivor: i64, absorbed: i64) -> Result<u64> {
+    let moved = sqlx::query("UPDATE messages SET conversation_id = $1 WHERE conversation_id = $2")
+        .bind(survivor)
+        .bind(absorbed)
+        .execute(&mut **tx)
+        .await?
+        .rows_affected();
+    sqlx::query("UPDATE messages SET conversation_ref = $1 WHERE conversation_ref = $2")
+        .bind(survivor)
+        .bind(absorbed)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"INSERT INTO conversation_participants (conversation_id, contact_id, role)
+           SELECT $1, contact_id, role FROM conversation_participants WHERE conversation_id = $2
+           ON CONFLICT DO NOTHING"#,
+    )
+    .bind(survivor)
+    .bind(absorbed)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query(
+        r#"UPDATE conversations s
+           SET message_count = (SELECT COUNT(*) FROM messages WHERE conversation_id = $1),
+               last_activity_at = GREATEST(s.last_activity_at, a.last_activity_at)
+           FROM conversations a
+           WHERE s.id = $1 AND a.id = $2"#,
+    )
+    .bind(survivor)
+    .bind(absorbed)
+    .execute(&mut **tx)
+    .await?;
+    sqlx::query("DELETE FROM conversations WHERE id = $1")
+        .bind(absorbed)
+        .execute(&mut **tx)
+        .await?;
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::PgPool;
+
+    async fn conversation(pool: &PgPool, channel: &str, a: &str, b: &str, count: i32) -> i64 {
+        let id: i64 = sqlx::query_scalar(
+            r#"INSERT INTO conversations (customer_id, channel, participant_a, participant_b, message_count, key)
+               VALUES (1, $1, $2, $3, $4, $1 || ':' || $2 || '<->' || $3) RETURNING id"#,
+        )
+        .bind(channel)
+        .bind(a)
+        .bind(b)
+        .bind(count)
+        .fetch_one(pool)
+        .await
+        .expect("conversation");
+        for _ in 0..count {
+            sqlx::query(
+                r#"INSERT INTO messages (conversation_id, conversation_ref, provider_id, direction, sent_at)
+                   VALUES ($1, $1, 1, 'outbound', now())"#,
+            )
+            .bind(id)
+            .execute(pool)
+            .await
+            .expect("message");
+        }
+        id
+    }
+
+    #[sqlx::test(migrations = "./migrations_sqlx")]
+    async fn rekey_merges_conversations_that_now_collide(pool: PgPool) -> Result<()> {
+        sqlx::query("INSERT INTO customers (id, name) VALUES (1, 'c')")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "INSERT INTO providers (id, customer_id, kind, name) VALUES (1, 1, 'sms', 'p')",
+        )
+        .execute(&pool)
+        .await?;
+        // Version 1 keys: the national number kept its digits, Gmail kept its dots
+        let keys = KeyNormalization::default();
+        let canonical = conversation(&pool, "sms", "+15550001234", "+15550009876", 2).await;
+        let national = conversation(&pool, "sms", "+15550009876", "5550001234", 1).await;
+        let gmail = conversation(&pool, "email", "bob@x.com", "j.doe@gmail.com", 1).await;
+
+        let expected = RekeyReport {
+            scanned: 3,
+            rekeyed: 1,
+            merged: 1,
+            messages_moved: 1,
+            skipped: 0,
+        };
+        assert_eq!(
+            rekey_conversations(&pool, &keys, RekeyScope::Outdated, 2, true).await?,
+            expected
+        );
+        let left: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM conversations WHERE key_version = 1")
+                .fetch_one(&pool)
+                .await?;
+        assert_eq!(left, 3, "dry run changed nothing");
+
+        assert_eq!(
+            rekey_conversations(&pool, &keys, RekeyScope::Outdated, 2, false).await?,
+            expected
+        );
+        let row = sqlx::query("SELECT message_count, key_version FROM conversations WHERE id = $1")
+            .bind(canonical)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(row.get::<i32, _>("message_count"), 3);
+        assert_eq!(row.get::<i32, _>("key_version"), KEY_VERSION);
+        let gone: Option<i64> = sqlx::query_scalar("SELECT id FROM conversations WHERE id = $1")
+            .bind(national)
+            .fetch_optional(&pool)
+            .await?;
+        assert!(gone.is_none());
+        let repointed: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM messages WHERE conversation_id = $1 AND conversation_ref = $1",
+        )
+        .bind(canonical)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(repointed, 3);
+        let key: String = sqlx::query_scalar("SELECT key FROM conversations WHERE id = $1")
+            .bind(gmail)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(key, "email:bob@x.com<->jdoe@gmail.com");
+
+        // Everything is current now
+        assert_eq!(
+            rekey_conversations(&pool, &keys, RekeyScope::Outdated, 2, false).await?,
+            RekeyReport::default()
+        );
+
+        // Changed email rules leave the key version alone, so only a full run sees them
+        let dotted = conversation(&pool, "email", "b.ob@x.com", "carol@y.com", 1).await;
+        let plain = conversation(&pool, "email", "bob@x.com", "carol@y.com", 1).await;
+        rekey_conversations(&pool, &keys, RekeyScope::Outdated, 2, false).await?;
+        let rules = NormalizationConfig {
+            email_domain_rules: [(
+                "x.com".to_string(),
+                EmailRule {
+                    ignore_dots: true,
+                    ..EmailRule::default()
+                },
+            )]
+            .into(),
+            ..NormalizationConfig::default()
+        };
+        let keys = rules.key_normalization()?;
+        assert_eq!(
+            rekey_conversations(&pool, &keys, RekeyScope::Outdated, 2, false).await?,
+            RekeyReport::default()
+        );
+        assert_eq!(
+            rekey_conversations(&pool, &keys, RekeyScope::All, 2, false).await?,
+            RekeyReport {
+                scanned: 4,
+                rekeyed: 0,
+                merged: 1,
+                messages_moved: 1,
+                skipped: 0,
+            }
+        );
+        let row = sqlx::query("SELECT key, message_count FROM conversations WHERE id = $1")
+            .bind(dotted)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(row.get::<String, _>("key"), "email:bob@x.com<->carol@y.com");
+        assert_eq!(row.get::<i32, _>("message_count"), 2);
+        let gone: Option<i64> = sqlx::query_scalar("SELECT id FROM conversations WHERE id = $1")
+            .bind(plain)
+            .fetch_optional(&pool)
+            .await?;
+        assert!(gone.is_none());
+        Ok(())
+    }
+}
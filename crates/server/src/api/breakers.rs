@@ -0,0 +1,114 @@
+//! Admin circuit breaker control (`/admin/breakers`).
+//!
+//! Lists every provider breaker plus one `http.<group>` breaker per HTTP route group, and lets
+//! operators force a
+//! breaker open (planned provider maintenance), force it closed, or reset it to automatic
+//! operation. Every action is logged as `breaker_admin`.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::errors;
+use crate::middleware::circuit_breaker::CircuitBreaker;
+use crate::state::breakers::http_breaker_name;
+use crate::types::{BreakerAction, BreakerActionRequest, BreakerDto, BreakersDto};
+
+pub const ADMIN_PATH: &str = "/admin/breakers";
+
+/// Scope of the HTTP middleware breakers, listed as `http.<route group>`.
+pub const HTTP_BREAKER: &str = "http";
+
+fn to_dto(name: &str, scope: &str, breaker: &CircuitBreaker) -> BreakerDto {
+    let snap = breaker.snapshot();
+    BreakerDto {
+        name: name.to_string(),
+        scope: scope.to_string(),
+        state: snap.state.as_str().to_string(),
+        failures: snap.failures,
+        forced: snap.forced,
+        state_age_ms: snap.in_state_for.as_millis() as u64,
+        window_calls: snap.window_calls,
+        trips: snap.trips,
+        open_for_secs: snap.open_for.as_secs(),
+        policy: (&breaker.policy()).into(),
+    }
+}
+
+/// Provider breakers win over `http.<group>` should a provider ever be named that way.
+fn lookup<'a>(
+    state: &'a crate::AppState,
+    name: &str,
+) -> Option<(&'static str, &'a CircuitBreaker)> {
+    if let Some(b) = state.provider_breakers.get(name) {
+        return Some(("provider", b));
+    }
+    let group = name.strip_prefix(HTTP_BREAKER)?.strip_prefix('.')?;
+    state.http_breakers.get(group).map(|b| (HTTP_BREAKER, b))
+}
+
+/// GET /admin/breakers
+pub(crate) async fn list(State(state): State<crate::AppState>) -> Response {
+    let mut breakers: Vec<BreakerDto> = state
+        .provider_breakers
+        .iter()
+        .map(|(name, b)| to_dto(name, "provider", b))
+        .collect();
+    breakers.sort_by(|a, b| a.name.cmp(&b.name));
+    breakers.extend(
+        state
+            .http_breakers
+            .iter()
+            .map(|(group, b)| to_dto(&http_breaker_name(group), HTTP_BREAKER, b)),
+    );
+    (StatusCode::OK, Json(BreakersDto { breakers })).into_response()
+}
+
+/// GET /admin/breakers/{name}
+pub(crate) async fn show(
+    State(state): State<crate::AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    match lookup(&state, &name) {
+        Some((scope, b)) => (StatusCode::OK, Json(to_dto(&name, scope, b))).into_response(),
+        None => errors::not_found(format!("No breaker named {name}")).into_response(),
+    }
+}
+
+/// POST /admin/breakers/{name}  body: {"action":"open"|"close"|"reset","reason":".."}
+pub(crate) async fn act(
+    State(state): State<crate::AppState>,
+    Path(name): Path<String>,
+    Json(body): Json<BreakerActionRequest>,
+) -> Response {
+    let Some((scope, breaker)) = lookup(&state, &name) else {
+        return errors::not_found(format!("No breaker named {name}")).into_response();
+    };
+    let (action, before) = match body.action {
+        BreakerAction::Open => ("force_open", breaker.force_open()),
+        BreakerAction::Close => ("force_close", breaker.force_close()),
+        BreakerAction::Reset => ("reset", breaker.reset()),
+    };
+    let after = breaker.peek();
+    if before != after {
+        crate::metrics::record_breaker_transition();
+        if scope == "provider" {
+            crate::metrics::record_provider_breaker_transition(&name);
+        }
+    }
+    tracing::warn!(
+        target = "server",
+        event = "breaker_admin",
+        breaker = %name,
+        scope,
+        action,
+        from = before.as_str(),
+        to = after.as_str(),
+        reason = body.reason.as_deref().unwrap_or(""),
+        "circuit breaker changed by operator"
+    );
+    (StatusCode::OK, Json(to_dto(&name, scope, breaker))).into_response()
+}
@@ -0,0 +1,27 @@
+//! Admin config endpoints: `GET /admin/config` shows the live `ApiConfig`,
+//! `POST /admin/config/reload` re-reads it (same as SIGHUP; see `crate::reload`).
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::errors;
+
+pub const CONFIG_PATH: &str = "/admin/config";
+pub const RELOAD_PATH: &str = "/admin/config/reload";
+
+/// GET /admin/config
+pub(crate) async fn show(State(state): State<crate::AppState>) -> Response {
+    (StatusCode::OK, Json(&*state.api())).into_response()
+}
+
+/// POST /admin/config/reload
+pub(crate) async fn reload(State(state): State<crate::AppState>) -> Response {
+    match crate::reload::reload(&state, "admin") {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => errors::invalid_config(e.errors()).into_response(),
+    }
+}
@@ -179,6 +179,7 @@ pub(crate) async fn list_messages(
                                 page,
                                 page_size,
                                 snippet_len,
+                                state.keys(),
                             );
                             if !msgs.is_empty() {
                                 return (
@@ -201,7 +202,13 @@ pub(crate) async fn list_messages(
             Err(_) => (Vec::new(), 0),
         }
     } else if state.inmemory_fallback_enabled() {
-        crate::store::conversations::list_messages(&id, page, page_size, snippet_len)
+        crate::store::conversations::list_messages(
+            &id,
+            page,
+            page_size,
+            snippet_len,
+            state.keys(),
+        )
     } else {
         (Vec::new(), 0)
     };
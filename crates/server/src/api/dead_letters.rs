@@ -0,0 +1,212 @@
+//! Admin endpoints for dead-lettered inbound events (`/admin/dead-letters`).
+//!
+//! Queries live in `messaging_core::dead_letters`, shared with the `messaging-admin` CLI.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use messaging_core::dead_letters::{self, DeadLetter, Selection};
+use serde_json::json;
+use tracing::info;
+
+use crate::api::conversations::PagingQuery;
+use crate::errors;
+use crate::types::{DeadLetterDto, DeadLetterSelection, ListResponse, PageMeta};
+
+fn to_dto(d: DeadLetter) -> DeadLetterDto {
+    DeadLetterDto {
+        id: d.id.to_string(),
+        event_type: d.event_type,
+        channel: d.channel,
+        from: d.from,
+        to: d.to,
+        provider_message_id: d.provider_message_id,
+        attempts: d.attempts,
+        error_code: d.error_code,
+        error_message: d.error_message,
+        received_at: d.received_at.to_rfc3339(),
+        dead_at: d.updated_at.to_rfc3339(),
+        payload: d
+            .payload
+            .map(|p| serde_json::from_str(&p).unwrap_or(serde_json::Value::String(p))),
+    }
+}
+
+type ApiError = (StatusCode, Json<errors::ErrorResponse>);
+
+fn db_required(state: &crate::AppState) -> Result<sqlx::PgPool, ApiError> {
+    state
+        .db()
+        .ok_or_else(|| errors::service_unavailable("Dead letters require a database"))
+}
+
+fn db_error(e: sqlx::Error) -> Response {
+    tracing::warn!(target="server", event="dead_letters_db_error", error=%e, "dead-letter query failed");
+    errors::internal_error("Dead-letter query failed").into_response()
+}
+
+fn selection(body: &DeadLetterSelection) -> Result<Selection<'_>, ApiError> {
+    match (body.all, body.ids.is_empty()) {
+        (true, true) => Ok(Selection::All),
+        (false, false) => Ok(Selection::Ids(&body.ids)),
+        (true, false) => Err(errors::bad_request("Specify either ids or all, not both")),
+        (false, true) => Err(errors::bad_request("Specify ids or all=true")),
+    }
+}
+
+/// GET /admin/dead-letters
+pub(crate) async fn list(
+    State(state): State<crate::AppState>,
+    Query(paging): Query<PagingQuery>,
+) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    let page = paging.page.unwrap_or(1).max(1);
+    let page_size = match paging.page_size.unwrap_or(50) {
+        0 => 50,
+        n => n.min(50),
+    };
+    // i64 so that any u32 page is representable (a huge page is just past the end)
+    let offset = (i64::from(page) - 1) * i64::from(page_size);
+    let rows = match dead_letters::list(&pool, page_size as i64, offset).await {
+        Ok(rows) => rows,
+        Err(e) => return db_error(e),
+    };
+    let total = match dead_letters::count(&pool).await {
+        Ok(n) => n as u64,
+        Err(e) => return db_error(e),
+    };
+    let resp = ListResponse {
+        items: rows.into_iter().map(to_dto).collect(),
+        meta: PageMeta {
+            page,
+            page_size,
+            total,
+        },
+    };
+    (StatusCode::OK, Json(resp)).into_response()
+}
+
+/// GET /admin/dead-letters/{id}
+pub(crate) async fn show(State(state): State<crate::AppState>, Path(id): Path<i64>) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    match dead_letters::get(&pool, id).await {
+        Ok(Some(d)) => (StatusCode::OK, Json(to_dto(d))).into_response(),
+        Ok(None) => errors::not_found(format!("No dead letter with id {id}")).into_response(),
+        Err(e) => db_error(e),
+    }
+}
+
+/// POST /admin/dead-letters/{id}/requeue
+pub(crate) async fn requeue_one(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    match dead_letters::requeue(&pool, Selection::Ids(&[id])).await {
+        Ok(0) => errors::not_found(format!("No dead letter with id {id}")).into_response(),
+        Ok(n) => {
+            info!(
+                target = "server",
+                event = "dead_letters_requeued",
+                count = n,
+                inbound_event_id = id,
+                "dead letter requeued"
+            );
+            (StatusCode::OK, Json(json!({"requeued": n}))).into_response()
+        }
+        Err(e) => db_error(e),
+    }
+}
+
+/// POST /admin/dead-letters/requeue  body: {"ids":[..]} | {"all":true}
+pub(crate) async fn requeue_many(
+    State(state): State<crate::AppState>,
+    Json(body): Json<DeadLetterSelection>,
+) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    let sel = match selection(&body) {
+        Ok(s) => s,
+        Err(e) => return e.into_response(),
+    };
+    match dead_letters::requeue(&pool, sel).await {
+        Ok(n) => {
+            info!(
+                target = "server",
+                event = "dead_letters_requeued",
+                count = n,
+                all = body.all,
+                "dead letters requeued"
+            );
+            (StatusCode::OK, Json(json!({"requeued": n}))).into_response()
+        }
+        Err(e) => db_error(e),
+    }
+}
+
+/// DELETE /admin/dead-letters/{id}
+pub(crate) async fn purge_one(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    match dead_letters::purge(&pool, Selection::Ids(&[id])).await {
+        Ok(0) => errors::not_found(format!("No dead letter with id {id}")).into_response(),
+        Ok(n) => {
+            info!(
+                target = "server",
+                event = "dead_letters_purged",
+                count = n,
+                inbound_event_id = id,
+                "dead letter purged"
+            );
+            (StatusCode::OK, Json(json!({"purged": n}))).into_response()
+        }
+        Err(e) => db_error(e),
+    }
+}
+
+/// POST /admin/dead-letters/purge  body: {"ids":[..]} | {"all":true}
+pub(crate) async fn purge_many(
+    State(state): State<crate::AppState>,
+    Json(body): Json<DeadLetterSelection>,
+) -> Response {
+    let pool = match db_required(&state) {
+        Ok(p) => p,
+        Err(e) => return e.into_response(),
+    };
+    let sel = match selection(&body) {
+        Ok(s) => s,
+        Err(e) => return e.into_response(),
+    };
+    match dead_letters::purge(&pool, sel).await {
+        Ok(n) => {
+            info!(
+                target = "server",
+                event = "dead_letters_purged",
+                count = n,
+                all = body.all,
+                "dead letters purged"
+            );
+            (StatusCode::OK, Json(json!({"purged": n}))).into_response()
+        }
+        Err(e) => db_error(e),
+    }
+}
@@ -4,8 +4,11 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use messaging_core::sms::Segmentation;
 use serde_json::json;
+use std::borrow::Cow;
 
+use crate::correlation::CorrelationId;
 use crate::errors;
 use crate::queue::inbound_events::InboundEvent;
 use crate::store::messages as message_store;
@@ -14,15 +17,26 @@ use crate::types::{EmailRequest, SmsRequest, Validate};
 pub(crate) async fn post_sms(
     State(state): State<crate::AppState>,
     headers: HeaderMap,
-    Json(body): Json<SmsRequest>,
+    Json(mut body): Json<SmsRequest>,
 ) -> Response {
-    if let Err(msg) = body.validate(&state.api) {
-        return errors::bad_request(msg).into_response();
+    if let Err(e) = body.validate(&state.api(), state.keys()) {
+        return errors::invalid_request(e).into_response();
     }
+    // Segmentation (GSM-7 / UCS-2); the stored and dispatched body is the transliterated one
+    let segmentation = if body.r#type.eq_ignore_ascii_case("mms") {
+        None
+    } else {
+        let (text, seg) = messaging_core::sms::prepare(&body.body, state.api().sms_transliterate);
+        if let Cow::Owned(text) = text {
+            body.body = text;
+        }
+        Some(seg)
+    };
     // Idempotency: if key exists and seen already, return 202 without re-enqueueing
-    if let Some(key) = headers.get("idempotency-key").and_then(|v| v.to_str().ok()) {
+    let idempotency_key = headers.get("idempotency-key").and_then(|v| v.to_str().ok());
+    if let Some(key) = idempotency_key {
         if !state.idempotency.seen_or_insert(key) {
-            return (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response();
+            return sms_accepted(segmentation);
         }
     }
     // Per-sender rate limiting
@@ -31,14 +45,24 @@ pub(crate) async fn post_sms(
     }
     // Basic validation: attachments count
     if let Some(ref atts) = body.attachments {
-        if atts.len() > state.api.max_attachments {
+        if atts.len() > state.api().max_attachments {
             return errors::bad_request("too many attachments").into_response();
         }
     }
 
+    // Backpressure: claim queue capacity before any side effects so a 202 always means queued.
+    // The outbound queue is in memory: a crash before dispatch loses the event.
+    let slot = match state.reserve_queue_slot().await {
+        Ok(slot) => slot,
+        Err(e) => {
+            return state.reject_unqueued(idempotency_key, format!("Cannot accept message: {e}"))
+        }
+    };
+
     // Persist outbound: always in-memory for conversation listing fallback; additionally into DB if available
     let msg_id = if body.r#type.eq_ignore_ascii_case("mms") {
         message_store::insert_outbound_mms(
+            state.keys(),
             &body.from,
             &body.to,
             &body.body,
@@ -47,6 +71,7 @@ pub(crate) async fn post_sms(
         )
     } else {
         message_store::insert_outbound_sms(
+            state.keys(),
             &body.from,
             &body.to,
             &body.body,
@@ -65,6 +90,7 @@ pub(crate) async fn post_sms(
         };
         if let Err(e) = crate::store_db::messages::insert_outbound(
             &pool,
+            state.keys(),
             channel,
             &body.from,
             &body.to,
@@ -87,10 +113,25 @@ pub(crate) async fn post_sms(
         occurred_at: body.timestamp.clone(),
         idempotency_key: None,
         source: "api".to_string(),
+        trace_context: crate::telemetry::current_carrier(),
+        correlation_id: CorrelationId::current(),
     };
-    let _ = state.queue.enqueue(event).await;
+    slot.send(event);
+    if let Some(seg) = segmentation {
+        crate::metrics::record_sms_segments(seg.encoding.as_str(), seg.segments as u64);
+    }
 
-    (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response()
+    sms_accepted(segmentation)
+}
+
+/// 202 for an SMS/MMS send; SMS bodies report their segment count and encoding.
+fn sms_accepted(segmentation: Option<Segmentation>) -> Response {
+    let mut body = json!({ "status": "accepted" });
+    if let Some(seg) = segmentation {
+        body["segments"] = json!(seg.segments);
+        body["encoding"] = json!(seg.encoding.as_str());
+    }
+    (StatusCode::ACCEPTED, Json(body)).into_response()
 }
 
 pub(crate) async fn post_email(
@@ -98,10 +139,11 @@ pub(crate) async fn post_email(
     headers: HeaderMap,
     Json(body): Json<EmailRequest>,
 ) -> Response {
-    if let Err(msg) = body.validate(&state.api) {
-        return errors::bad_request(msg).into_response();
+    if let Err(e) = body.validate(&state.api(), state.keys()) {
+        return errors::invalid_request(e).into_response();
     }
-    if let Some(key) = headers.get("idempotency-key").and_then(|v| v.to_str().ok()) {
+    let idempotency_key = headers.get("idempotency-key").and_then(|v| v.to_str().ok());
+    if let Some(key) = idempotency_key {
         if !state.idempotency.seen_or_insert(key) {
             return (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response();
         }
@@ -110,12 +152,19 @@ pub(crate) async fn post_email(
         return errors::too_many_requests("Too many requests for sender").into_response();
     }
     if let Some(ref atts) = body.attachments {
-        if atts.len() > state.api.max_attachments {
+        if atts.len() > state.api().max_attachments {
             return errors::bad_request("too many attachments").into_response();
         }
     }
+    let slot = match state.reserve_queue_slot().await {
+        Ok(slot) => slot,
+        Err(e) => {
+            return state.reject_unqueued(idempotency_key, format!("Cannot accept message: {e}"))
+        }
+    };
     // Persist outbound email: in-memory + DB if available
     let msg_id = message_store::insert_outbound_email(
+        state.keys(),
         &body.from,
         &body.to,
         &body.body,
@@ -126,6 +175,7 @@ pub(crate) async fn post_email(
         crate::store_db::seed::seed_minimum_if_needed(&pool).await;
         if let Err(e) = crate::store_db::messages::insert_outbound(
             &pool,
+            state.keys(),
             "email",
             &body.from,
             &body.to,
@@ -148,8 +198,10 @@ pub(crate) async fn post_email(
         occurred_at: body.timestamp.clone(),
         idempotency_key: None,
         source: "api".to_string(),
+        trace_context: crate::telemetry::current_carrier(),
+        correlation_id: CorrelationId::current(),
     };
-    let _ = state.queue.enqueue(event).await;
+    slot.send(event);
 
     (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response()
 }
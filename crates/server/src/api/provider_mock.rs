@@ -1,43 +1,133 @@
+use std::collections::BTreeMap;
+
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde_json::json;
-use std::sync::{OnceLock, RwLock};
 use tracing::info;
 
+use crate::config::ApiConfig;
+use crate::correlation::CorrelationId;
 use crate::errors;
+use crate::providers::common::MOCK_PROVIDERS;
+use crate::providers::latency::LatencyDistribution;
+use crate::providers::scenario::Scenario;
 use crate::queue::inbound_events::InboundEvent;
 use crate::store::messages as message_store;
 use crate::store_db::inbound_events::insert_inbound_event;
 use crate::types::{ProviderInboundRequest, Validate};
 
+/// Mock provider behaviour: global percentages, seed and latency, plus per-provider overrides.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProviderMockConfig {
     pub timeout_pct: u32,
     pub error_pct: u32,
     pub ratelimit_pct: u32,
     pub seed: Option<u64>,
+    /// Simulated latency (see `providers::latency`); omitted from a PUT, it is kept
+    #[serde(default)]
+    pub latency: Option<LatencyDistribution>,
+    /// `provider_dispatch_timeout_ms`; omitted from a PUT, it is kept
+    #[serde(default)]
+    pub dispatch_timeout_ms: Option<u64>,
+    /// Overrides keyed by provider (`sms-mms`, `email`); `null` fields use the global value.
+    /// Omitted from a PUT, the current overrides are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub providers: Option<BTreeMap<String, ProviderMockOverrides>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ProviderMockOverrides {
+    pub timeout_pct: Option<u32>,
+    pub error_pct: Option<u32>,
+    pub ratelimit_pct: Option<u32>,
+    pub seed: Option<u64>,
+    /// Scripted behaviour (see `providers::scenario`); `null` removes it
+    pub scenario: Option<Scenario>,
+    pub latency: Option<LatencyDistribution>,
 }
 
 impl ProviderMockConfig {
-    fn from_api(cfg: &crate::config::ApiConfig) -> Self {
+    fn from_api(cfg: &ApiConfig) -> Self {
+        let providers = MOCK_PROVIDERS
+            .iter()
+            .map(|p| (p.to_string(), ProviderMockOverrides::from_api(cfg, p)))
+            .collect();
         Self {
             timeout_pct: cfg.provider_timeout_pct,
             error_pct: cfg.provider_error_pct,
             ratelimit_pct: cfg.provider_ratelimit_pct,
             seed: cfg.provider_seed,
+            latency: Some(cfg.provider_latency),
+            dispatch_timeout_ms: Some(cfg.provider_dispatch_timeout_ms),
+            providers: Some(providers),
+        }
+    }
+
+    /// `cfg` with these settings applied.
+    fn apply_to(&self, cfg: &ApiConfig) -> ApiConfig {
+        let mut next = cfg.clone();
+        next.provider_timeout_pct = self.timeout_pct;
+        next.provider_error_pct = self.error_pct;
+        next.provider_ratelimit_pct = self.ratelimit_pct;
+        next.provider_seed = self.seed;
+        if let Some(latency) = self.latency {
+            next.provider_latency = latency;
+        }
+        if let Some(ms) = self.dispatch_timeout_ms {
+            next.provider_dispatch_timeout_ms = ms;
+        }
+        for (provider, o) in self.providers.iter().flatten() {
+            match provider.as_str() {
+                "sms-mms" => {
+                    next.provider_sms_timeout_pct = o.timeout_pct;
+                    next.provider_sms_error_pct = o.error_pct;
+                    next.provider_sms_ratelimit_pct = o.ratelimit_pct;
+                    next.provider_sms_seed = o.seed;
+                }
+                "email" => {
+                    next.provider_email_timeout_pct = o.timeout_pct;
+                    next.provider_email_error_pct = o.error_pct;
+                    next.provider_email_ratelimit_pct = o.ratelimit_pct;
+                    next.provider_email_seed = o.seed;
+                }
+                _ => continue,
+            }
+            set_entry(&mut next.provider_scenarios, provider, o.scenario.clone());
+            set_entry(&mut next.provider_latency_overrides, provider, o.latency);
         }
+        next
     }
 }
 
-fn global_config() -> &'static RwLock<ProviderMockConfig> {
-    static CELL: OnceLock<RwLock<ProviderMockConfig>> = OnceLock::new();
-    CELL.get_or_init(|| {
-        RwLock::new(ProviderMockConfig {
-            timeout_pct: 0,
-            error_pct: 0,
-            ratelimit_pct: 0,
-            seed: None,
-        })
-    })
+fn set_entry<T>(map: &mut BTreeMap<String, T>, provider: &str, value: Option<T>) {
+    match value {
+        Some(v) => map.insert(provider.to_string(), v),
+        None => map.remove(provider),
+    };
+}
+
+impl ProviderMockOverrides {
+    fn from_api(cfg: &ApiConfig, provider: &str) -> Self {
+        match provider {
+            "sms-mms" => Self {
+                timeout_pct: cfg.provider_sms_timeout_pct,
+                error_pct: cfg.provider_sms_error_pct,
+                ratelimit_pct: cfg.provider_sms_ratelimit_pct,
+                seed: cfg.provider_sms_seed,
+                scenario: cfg.provider_scenarios.get("sms-mms").cloned(),
+                latency: cfg.provider_latency_overrides.get("sms-mms").copied(),
+            },
+            "email" => Self {
+                timeout_pct: cfg.provider_email_timeout_pct,
+                error_pct: cfg.provider_email_error_pct,
+                ratelimit_pct: cfg.provider_email_ratelimit_pct,
+                seed: cfg.provider_email_seed,
+                scenario: cfg.provider_scenarios.get("email").cloned(),
+                latency: cfg.provider_latency_overrides.get("email").copied(),
+            },
+            _ => Self::default(),
+        }
+    }
 }
 
 /// POST /api/provider/mock/inbound
@@ -48,11 +138,11 @@ pub(crate) async fn post_inbound(
 ) -> axum::response::Response {
     // Validate basic shape per variant
     let valid = match &body {
-        ProviderInboundRequest::Sms(s) | ProviderInboundRequest::Mms(s) => s.validate(&state.api),
-        ProviderInboundRequest::Email(e) => e.validate(&state.api),
+        ProviderInboundRequest::Sms(s) | ProviderInboundRequest::Mms(s) => s.validate(&state.api(), state.keys()),
+        ProviderInboundRequest::Email(e) => e.validate(&state.api(), state.keys()),
     };
-    if let Err(msg) = valid {
-        return errors::bad_request(msg).into_response();
+    if let Err(e) = valid {
+        return errors::invalid_request(e).into_response();
     }
 
     // Normalize event name by variant for downstream handlers
@@ -91,7 +181,7 @@ pub(crate) async fn post_inbound(
             | crate::types::ProviderInboundRequest::Mms(_) => None,
             crate::types::ProviderInboundRequest::Email(_e) => None,
         };
-        let _ = insert_inbound_event(
+        if let Err(e) = insert_inbound_event(
             &pool,
             ch,
             &from,
@@ -99,7 +189,14 @@ pub(crate) async fn post_inbound(
             provider_message_id.as_deref(),
             payload,
         )
-        .await;
+        .await
+        {
+            tracing::warn!(target="server", event="inbound_event_persist_fail", error=%e, mock=true, "failed to persist inbound event");
+            return state.reject_unqueued(
+                None,
+                "Cannot accept inbound event: inbound queue unavailable",
+            );
+        }
     } else {
         let event = InboundEvent {
             event_name: event_name.to_string(),
@@ -107,44 +204,56 @@ pub(crate) async fn post_inbound(
             occurred_at,
             idempotency_key: None,
             source: "provider.mock".to_string(),
+            trace_context: crate::telemetry::current_carrier(),
+            correlation_id: CorrelationId::current(),
+        };
+        let slot = match state.reserve_queue_slot().await {
+            Ok(slot) => slot,
+            Err(e) => {
+                return state.reject_unqueued(None, format!("Cannot accept inbound event: {e}"))
+            }
         };
         // Persist inbound to in-memory store (legacy mock flow)
-        let _stored_id = message_store::insert_inbound(&body);
-        let _ = state.queue.enqueue(event).await;
+        let _stored_id = message_store::insert_inbound(&body, state.keys());
+        slot.send(event);
     }
 
     (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response()
 }
 
 /// GET /api/provider/mock/config
+/// The global provider percentages and seed, and each provider's overrides, from the live
+/// `ApiConfig`.
 pub(crate) async fn get_config(State(state): State<crate::AppState>) -> axum::response::Response {
-    // Initialize from ApiConfig only if global is still defaults
-    let lock = global_config();
-    {
-        let current = lock.read().unwrap();
-        // If all zeros and None, seed from ApiConfig
-        if current.timeout_pct == 0
-            && current.error_pct == 0
-            && current.ratelimit_pct == 0
-            && current.seed.is_none()
-        {
-            drop(current);
-            let mut w = lock.write().unwrap();
-            *w = ProviderMockConfig::from_api(&state.api);
-        }
-    }
-    let cfg = lock.read().unwrap().clone();
+    let cfg = ProviderMockConfig::from_api(&state.api());
     info!(target = "server", event = "mock_config_get", mock = true, timeout_pct = %cfg.timeout_pct, error_pct = %cfg.error_pct, ratelimit_pct = %cfg.ratelimit_pct, seed = ?cfg.seed, "served mock provider config");
     Json(cfg).into_response()
 }
 
 /// PUT /api/provider/mock/config
-pub(crate) async fn put_config(Json(body): Json<ProviderMockConfig>) -> axum::response::Response {
-    let lock = global_config();
-    {
-        let mut w = lock.write().unwrap();
-        *w = body.clone();
+/// Updates the live `ApiConfig` (validated and logged like a reload), so the next dispatch uses
+/// it. Seeded providers restart their outcome sequence from the seed, and scenarios start over.
+pub(crate) async fn put_config(
+    State(state): State<crate::AppState>,
+    Json(body): Json<ProviderMockConfig>,
+) -> axum::response::Response {
+    let unknown: Vec<String> = body
+        .providers
+        .iter()
+        .flatten()
+        .filter(|(p, _)| !MOCK_PROVIDERS.contains(&p.as_str()))
+        .map(|(p, _)| format!("unknown provider {p:?}"))
+        .collect();
+    if !unknown.is_empty() {
+        return errors::invalid_config(unknown).into_response();
+    }
+    let applied = crate::reload::apply_with(&state, "provider_mock", true, |current| {
+        body.apply_to(current)
+    });
+    if let Err(e) = applied {
+        return errors::invalid_config(e.errors()).into_response();
     }
-    info!(target = "server", event = "mock_config_put", mock = true, timeout_pct = %body.timeout_pct, error_pct = %body.error_pct, ratelimit_pct = %body.ratelimit_pct, seed = ?body.seed, "updated mock provider config");
-    Json(body).into_response()
+    let live = state.api();
+    info!(target = "server", event = "mock_config_put", mock = true, timeout_pct = %body.timeout_pct, error_pct = %body.error_pct, ratelimit_pct = %body.ratelimit_pct, seed = ?body.seed, providers = ?body.providers, "updated mock provider config");
+    Json(ProviderMockConfig::from_api(&live)).into_response()
 }
@@ -0,0 +1,64 @@
+//! Admin queue inspection (`/admin/queues`): inbound_events backlog and the outbound buffer.
+//!
+//! Each call also refreshes the `queue_*` gauges in `/metrics`; between calls the
+//! background sampler (`worker::queue_metrics`) keeps them current.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::errors;
+use crate::metrics;
+use crate::store_db::inbound_events::{queue_stats, InboundQueueStats};
+use crate::types::{InboundQueueDto, OutboundQueueDto, QueuesDto};
+
+const STATUSES: [&str; 4] = ["pending", "processing", "done", "dead"];
+
+fn to_dto(stats: InboundQueueStats) -> InboundQueueDto {
+    let mut by_status = stats.by_status;
+    for status in STATUSES {
+        by_status.entry(status.to_string()).or_insert(0);
+    }
+    InboundQueueDto {
+        by_status,
+        oldest_pending_age_secs: stats.oldest_pending_age_secs,
+        lag_secs: stats.lag_secs,
+        processing_by_worker: stats.processing_by_worker,
+    }
+}
+
+/// Sample the outbound buffer and update its gauges.
+pub(crate) fn sample_outbound(state: &crate::AppState) -> OutboundQueueDto {
+    let depth = state.queue.depth();
+    let capacity = state.queue.capacity();
+    metrics::set_queue_outbound_gauges(depth, capacity);
+    OutboundQueueDto { depth, capacity }
+}
+
+/// Query inbound_events and update its gauges; `Ok(None)` without a database.
+pub(crate) async fn sample_inbound(
+    state: &crate::AppState,
+) -> anyhow::Result<Option<InboundQueueDto>> {
+    let Some(pool) = state.db() else {
+        return Ok(None);
+    };
+    let stats = queue_stats(&pool).await?;
+    metrics::set_queue_inbound_gauges(&stats);
+    Ok(Some(to_dto(stats)))
+}
+
+/// GET /admin/queues
+pub(crate) async fn show(State(state): State<crate::AppState>) -> Response {
+    let outbound = sample_outbound(&state);
+    let inbound = match sample_inbound(&state).await {
+        Ok(inbound) => inbound,
+        Err(e) => {
+            tracing::warn!(target="server", event="queues_db_error", error=%e, "queue stats query failed");
+            return errors::internal_error("Queue stats query failed").into_response();
+        }
+    };
+    (StatusCode::OK, Json(QueuesDto { inbound, outbound })).into_response()
+}
@@ -0,0 +1,186 @@
+//! Readiness (`/readyz`): can this instance do useful work right now?
+//!
+//! The configured health path stays a liveness probe. Readiness checks the database, the
+//! migration version, the inbound worker heartbeat and the provider breakers, and answers 503
+//! with the same per-component report when any of them is `down`.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::middleware::circuit_breaker::BreakerState;
+use crate::store_db::schema::{applied_migration_version, ping, REQUIRED_MIGRATION_VERSION};
+use crate::types::{ComponentCheckDto, ReadinessDto};
+
+pub const READINESS_PATH: &str = "/readyz";
+
+const UP: &str = "up";
+const DEGRADED: &str = "degraded";
+const DOWN: &str = "down";
+const DISABLED: &str = "disabled";
+
+fn check(
+    status: &str,
+    message: Option<String>,
+    details: Option<serde_json::Value>,
+) -> ComponentCheckDto {
+    ComponentCheckDto {
+        status: status.to_string(),
+        message,
+        details,
+    }
+}
+
+/// `database` and `migrations` checks; both are bounded by `readiness_timeout_ms`.
+async fn check_database(state: &crate::AppState) -> (ComponentCheckDto, ComponentCheckDto) {
+    let Some(pool) = state.db() else {
+        return match &state.db_error {
+            Some(e) => (
+                check(DOWN, Some(format!("database pool unavailable: {e}")), None),
+                check(DOWN, Some("database unavailable".into()), None),
+            ),
+            None => (
+                check(DISABLED, Some("no DATABASE_URL configured".into()), None),
+                check(DISABLED, None, None),
+            ),
+        };
+    };
+    let limit = Duration::from_millis(state.api().readiness_timeout_ms.max(1));
+    let timed_out = || format!("timed out after {}ms", limit.as_millis());
+
+    let started = Instant::now();
+    let database = match tokio::time::timeout(limit, ping(&pool)).await {
+        Ok(Ok(())) => check(
+            UP,
+            None,
+            Some(json!({ "latency_ms": started.elapsed().as_millis() as u64 })),
+        ),
+        Ok(Err(e)) => check(DOWN, Some(e.to_string()), None),
+        Err(_) => check(DOWN, Some(timed_out()), None),
+    };
+    if database.status == DOWN {
+        return (
+            database,
+            check(DOWN, Some("database unavailable".into()), None),
+        );
+    }
+
+    let migrations = match tokio::time::timeout(limit, applied_migration_version(&pool)).await {
+        Ok(Ok(applied)) => {
+            let details = json!({ "applied": applied, "required": REQUIRED_MIGRATION_VERSION });
+            match applied {
+                Some(v) if v >= REQUIRED_MIGRATION_VERSION => check(UP, None, Some(details)),
+                _ => check(
+                    DOWN,
+                    Some("schema is behind this build; run db-migrate apply".into()),
+                    Some(details),
+                ),
+            }
+        }
+        Ok(Err(e)) => check(DOWN, Some(e.to_string()), None),
+        Err(_) => check(DOWN, Some(timed_out()), None),
+    };
+    (database, migrations)
+}
+
+fn check_inbound_worker(state: &crate::AppState) -> ComponentCheckDto {
+    if state.db.is_none() {
+        return check(DISABLED, Some("runs only with a database".into()), None);
+    }
+    let stale_after = Duration::from_secs(state.api().readiness_worker_stale_secs.max(1));
+    match state.inbound_heartbeat.age() {
+        None => check(DOWN, Some("no heartbeat yet".into()), None),
+        Some(age) => {
+            let details = json!({ "last_heartbeat_ms_ago": age.as_millis() as u64 });
+            if age > stale_after {
+                check(
+                    DOWN,
+                    Some(format!("no heartbeat for {}s", age.as_secs())),
+                    Some(details),
+                )
+            } else {
+                check(UP, None, Some(details))
+            }
+        }
+    }
+}
+
+/// `degraded` while some provider breakers are open, `down` once all of them are.
+fn check_providers(state: &crate::AppState) -> ComponentCheckDto {
+    let breakers: BTreeMap<&str, BreakerState> = state
+        .provider_breakers
+        .iter()
+        .map(|(name, b)| (name, b.peek()))
+        .collect();
+    if breakers.is_empty() {
+        return check(DISABLED, Some("no providers registered".into()), None);
+    }
+    let open = breakers
+        .values()
+        .filter(|s| **s == BreakerState::Open)
+        .count();
+    let details = json!({
+        "breakers": breakers
+            .iter()
+            .map(|(name, s)| (name.to_string(), s.as_str()))
+            .collect::<BTreeMap<_, _>>()
+    });
+    if open == breakers.len() {
+        check(
+            DOWN,
+            Some("all provider breakers are open".into()),
+            Some(details),
+        )
+    } else if open > 0 {
+        check(
+            DEGRADED,
+            Some(format!(
+                "{open} of {} provider breakers open",
+                breakers.len()
+            )),
+            Some(details),
+        )
+    } else {
+        check(UP, None, Some(details))
+    }
+}
+
+/// GET /readyz
+pub(crate) async fn ready(State(state): State<crate::AppState>) -> Response {
+    let (database, migrations) = check_database(&state).await;
+    let checks = BTreeMap::from([
+        ("database".to_string(), database),
+        ("migrations".to_string(), migrations),
+        ("inbound_worker".to_string(), check_inbound_worker(&state)),
+        ("providers".to_string(), check_providers(&state)),
+    ]);
+    let ready = checks.values().all(|c| c.status != DOWN);
+    let (code, status) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+    if !ready {
+        let failing: Vec<&str> = checks
+            .iter()
+            .filter(|(_, c)| c.status == DOWN)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        tracing::warn!(target="server", event="readiness_fail", failing=?failing, "instance not ready");
+    }
+    (
+        code,
+        Json(ReadinessDto {
+            status: status.to_string(),
+            checks,
+        }),
+    )
+        .into_response()
+}
@@ -6,17 +6,22 @@ use axum::{
 };
 use serde_json::json;
 
+use crate::correlation::CorrelationId;
 use crate::errors;
 use crate::queue::inbound_events::InboundEvent;
 use crate::store_db::inbound_events::insert_inbound_event;
-use crate::types::{WebhookEmailRequest, WebhookSmsRequest};
+use crate::types::{Validate, WebhookEmailRequest, WebhookSmsRequest};
 
 pub(crate) async fn post_sms(
     State(state): State<crate::AppState>,
     headers: HeaderMap,
     Json(body): Json<WebhookSmsRequest>,
 ) -> Response {
-    if let Some(key) = headers.get("idempotency-key").and_then(|v| v.to_str().ok()) {
+    if let Err(e) = body.validate(&state.api(), state.keys()) {
+        return errors::invalid_request(e).into_response();
+    }
+    let idempotency_key = headers.get("idempotency-key").and_then(|v| v.to_str().ok());
+    if let Some(key) = idempotency_key {
         if !state.idempotency.seen_or_insert(key) {
             return (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response();
         }
@@ -27,7 +32,7 @@ pub(crate) async fn post_sms(
     }
 
     if let Some(ref atts) = body.attachments {
-        if atts.len() > state.api.max_attachments {
+        if atts.len() > state.api().max_attachments {
             return errors::bad_request("too many attachments").into_response();
         }
     }
@@ -36,7 +41,7 @@ pub(crate) async fn post_sms(
     if let Some(pool) = state.db() {
         let channel = body.r#type.to_ascii_lowercase();
         let payload = serde_json::to_value(&body).unwrap_or_else(|_| json!({}));
-        let _ = insert_inbound_event(
+        if let Err(e) = insert_inbound_event(
             &pool,
             &channel,
             &body.from,
@@ -44,7 +49,14 @@ pub(crate) async fn post_sms(
             Some(&body.messaging_provider_id),
             payload,
         )
-        .await;
+        .await
+        {
+            tracing::warn!(target="server", event="inbound_event_persist_fail", error=%e, "failed to persist inbound event");
+            return state.reject_unqueued(
+                idempotency_key,
+                "Cannot accept webhook: inbound queue unavailable",
+            );
+        }
     } else {
         let event = InboundEvent {
             event_name: "webhooks.sms".to_string(),
@@ -52,8 +64,12 @@ pub(crate) async fn post_sms(
             occurred_at: body.timestamp.clone(),
             idempotency_key: None,
             source: "webhook".to_string(),
+            trace_context: crate::telemetry::current_carrier(),
+            correlation_id: CorrelationId::current(),
         };
-        let _ = state.queue.enqueue(event).await;
+        if let Err(e) = state.enqueue(event).await {
+            return state.reject_unqueued(idempotency_key, format!("Cannot accept webhook: {e}"));
+        }
     }
 
     (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response()
@@ -64,20 +80,21 @@ pub(crate) async fn post_email(
     headers: HeaderMap,
     Json(body): Json<WebhookEmailRequest>,
 ) -> Response {
-    if let Some(key) = headers.get("idempotency-key").and_then(|v| v.to_str().ok()) {
+    let idempotency_key = headers.get("idempotency-key").and_then(|v| v.to_str().ok());
+    if let Some(key) = idempotency_key {
         if !state.idempotency.seen_or_insert(key) {
             return (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response();
         }
     }
     if let Some(ref atts) = body.attachments {
-        if atts.len() > state.api.max_attachments {
+        if atts.len() > state.api().max_attachments {
             return errors::bad_request("too many attachments").into_response();
         }
     }
 
     if let Some(pool) = state.db() {
         let payload = serde_json::to_value(&body).unwrap_or_else(|_| json!({}));
-        let _ = insert_inbound_event(
+        if let Err(e) = insert_inbound_event(
             &pool,
             "email",
             &body.from,
@@ -85,7 +102,14 @@ pub(crate) async fn post_email(
             Some(&body.xillio_id),
             payload,
         )
-        .await;
+        .await
+        {
+            tracing::warn!(target="server", event="inbound_event_persist_fail", error=%e, "failed to persist inbound event");
+            return state.reject_unqueued(
+                idempotency_key,
+                "Cannot accept webhook: inbound queue unavailable",
+            );
+        }
     } else {
         let event = InboundEvent {
             event_name: "webhooks.email".to_string(),
@@ -93,8 +117,12 @@ pub(crate) async fn post_email(
             occurred_at: body.timestamp.clone(),
             idempotency_key: None,
             source: "webhook".to_string(),
+            trace_context: crate::telemetry::current_carrier(),
+            correlation_id: CorrelationId::current(),
         };
-        let _ = state.queue.enqueue(event).await;
+        if let Err(e) = state.enqueue(event).await {
+            return state.reject_unqueued(idempotency_key, format!("Cannot accept webhook: {e}"));
+        }
     }
 
     (StatusCode::ACCEPTED, Json(json!({ "status": "accepted" }))).into_response()
@@ -0,0 +1,445 @@
+//! Embeddable server construction.
+//!
+//! `ServerBuilder` assembles state, router, and background workers from explicit inputs so the
+//! crate can be hosted inside tests or a larger service. `run_server` and
+//! `run_server_with_shutdown` are thin wrappers over `ServerBuilder::from_env`.
+//!
+//! ```no_run
+//! # async fn demo(cfg: std::sync::Arc<messaging_core::Config>, pool: sqlx::PgPool) -> Result<(), String> {
+//! use messaging_server::ServerBuilder;
+//! let handle = ServerBuilder::new(cfg)
+//!     .pool(pool)
+//!     .routes(axum::Router::new().route("/extra", axum::routing::get(|| async { "ok" })))
+//!     .build()
+//!     .await?
+//!     .start();
+//! let report = handle.shutdown().await;
+//! # let _ = report; Ok(()) }
+//! ```
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::middleware as axmw;
+use axum::Router;
+use messaging_core::Config;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::config::{ApiConfig, SharedApiConfig};
+use crate::middleware::circuit_breaker::CircuitBreaker;
+use crate::middleware::rate_limit::RateLimiter;
+use crate::providers::registry::{ChannelKind, Provider, ProviderRegistry};
+use crate::queue::inbound_events::{InboundEvent, InboundQueue};
+use crate::shutdown::ShutdownReport;
+use crate::state::breakers::{HttpBreakers, ProviderBreakers, PROVIDER_FALLBACK_BREAKER};
+use crate::state::heartbeat::Heartbeat;
+use crate::state::idempotency::IdempotencyStore;
+use crate::AppState;
+
+type RouterMap = Box<dyn FnOnce(Router) -> Router + Send>;
+
+/// Builder for an embeddable messaging server.
+///
+/// Nothing is read from the environment unless `from_env` is used.
+pub struct ServerBuilder {
+    config: Arc<Config>,
+    api: ApiConfig,
+    pool: Option<PgPool>,
+    providers: ProviderRegistry,
+    routes: Vec<Router>,
+    router_maps: Vec<RouterMap>,
+    seed_bootstrap: bool,
+    db_error: Option<String>,
+    config_file: Option<PathBuf>,
+    reload_on_sighup: bool,
+}
+
+impl ServerBuilder {
+    /// Built-in defaults: `ApiConfig::default()`, mock providers, no database.
+    pub fn new(config: Arc<Config>) -> Self {
+        Self {
+            config,
+            api: ApiConfig::default(),
+            pool: None,
+            providers: default_mock_registry(),
+            routes: Vec::new(),
+            router_maps: Vec::new(),
+            seed_bootstrap: false,
+            db_error: None,
+            config_file: None,
+            reload_on_sighup: false,
+        }
+    }
+
+    /// Defaults resolved from the process environment (what `run_server` uses):
+    /// `ApiConfig::load()`, a pool from `DATABASE_URL` when reachable, `SEED_DB=1`, and
+    /// config reload on SIGHUP.
+    pub async fn from_env(config: Arc<Config>) -> Self {
+        let mut builder = Self::new(config)
+            .api_config(ApiConfig::load())
+            .reload_on_sighup(true)
+            .seed_bootstrap(std::env::var("SEED_DB").ok().as_deref() == Some("1"));
+        if let Ok(url) = std::env::var("DATABASE_URL") {
+            match PgPoolOptions::new().max_connections(5).connect(&url).await {
+                Ok(pool) => builder = builder.pool(pool),
+                Err(e) => {
+                    tracing::warn!(target="server", event="db_pool_error", error=%e, "failed to create DB pool; continuing without DB");
+                    // Serving without the configured DB is degraded: `/readyz` reports it
+                    builder.db_error = Some(e.to_string());
+                }
+            }
+        }
+        builder
+    }
+
+    pub fn api(&self) -> &ApiConfig {
+        &self.api
+    }
+
+    pub fn api_config(mut self, api: ApiConfig) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// File re-read by config reloads (instead of `API_CONFIG_FILE` / the default path).
+    /// `API_*` environment overrides still apply on top.
+    pub fn config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// Reload `ApiConfig` on SIGHUP (`POST /admin/config/reload` is always available).
+    pub fn reload_on_sighup(mut self, enabled: bool) -> Self {
+        self.reload_on_sighup = enabled;
+        self
+    }
+
+    /// Use an existing pool; enables the DB-backed stores and the inbound worker.
+    pub fn pool(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Route `channel` to `provider`, replacing the default mock for that channel.
+    /// A circuit breaker is created per distinct `Provider::name()`.
+    pub fn provider(mut self, channel: ChannelKind, provider: Arc<dyn Provider>) -> Self {
+        self.providers.insert(channel, provider);
+        self
+    }
+
+    /// Merge extra routes into the API router; they share its middleware stack.
+    pub fn routes(mut self, routes: Router) -> Self {
+        self.routes.push(routes);
+        self
+    }
+
+    /// Transform the fully assembled router (outermost), e.g. `|r| r.layer(my_layer)`.
+    pub fn map_router<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Router) -> Router + Send + 'static,
+    {
+        self.router_maps.push(Box::new(f));
+        self
+    }
+
+    /// Seed demo data (`seed_bootstrap`) on start when a pool is configured.
+    pub fn seed_bootstrap(mut self, enabled: bool) -> Self {
+        self.seed_bootstrap = enabled;
+        self
+    }
+
+    /// Assemble state and router and bind the listener. Workers start with `Server::start`.
+    /// An `api_config` that fails `ApiConfig::validate` is rejected with every problem found,
+    /// as a reload with the same values would be.
+    pub async fn build(self) -> Result<Server, String> {
+        let api = self.api;
+        api.validate()
+            .map_err(|errors| format!("invalid api config: {}", errors.join("; ")))?;
+        crate::metrics::init_latency_histograms(&api);
+        let (queue, rx) = InboundQueue::new(api.queue_capacity.max(1));
+        let providers = crate::providers::recording::wrap_registry(self.providers, &api)?;
+        let provider_breakers = breakers_for(&providers, &api);
+        let keys = api.key_normalization();
+        let state = AppState {
+            rate: RateLimiter::new(
+                api.rate_limit_per_ip_per_min,
+                api.rate_limit_per_sender_per_min,
+            ),
+            http_breakers: HttpBreakers::from_config(&api),
+            config_file: self.config_file.map(Arc::new),
+            queue,
+            idempotency: IdempotencyStore::new(2 * 60 * 60), // 2 hours
+            api: SharedApiConfig::new(api),
+            db: self.pool,
+            provider_registry: providers,
+            provider_breakers,
+            snippet_length: self.config.conversation_snippet_length,
+            keys,
+            inbound_heartbeat: Heartbeat::default(),
+            db_error: self.db_error,
+        };
+
+        // Ensure base identities exist (customer id=1, provider id=1) to satisfy FKs for worker inserts
+        if let Some(pool) = &state.db {
+            crate::store_db::seed::seed_identities(pool).await;
+        }
+
+        let mut router = crate::build_router(&self.config.health_path, state.clone(), self.routes)
+            // Security headers (Feature 010 T010)
+            .layer(axmw::from_fn(crate::middleware::headers::security_headers))
+            // Inject Arc<Config> into request extensions for middleware access (outermost)
+            .layer(axmw::from_fn_with_state(
+                self.config.clone(),
+                crate::add_core_config_extension,
+            ));
+        for f in self.router_maps {
+            router = f(router);
+        }
+
+        let bind_addr: SocketAddr = ([0, 0, 0, 0], self.config.port).into();
+        let listener = TcpListener::bind(bind_addr)
+            .await
+            .map_err(|e| format!("failed to bind: {e}"))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("failed to read local addr: {e}"))?;
+
+        Ok(Server {
+            config: self.config,
+            state,
+            rx,
+            router,
+            listener,
+            local_addr,
+            seed_bootstrap: self.seed_bootstrap,
+            reload_on_sighup: self.reload_on_sighup,
+        })
+    }
+}
+
+/// A bound, not-yet-serving server produced by `ServerBuilder::build`.
+pub struct Server {
+    config: Arc<Config>,
+    state: AppState,
+    rx: mpsc::Receiver<InboundEvent>,
+    router: Router,
+    listener: TcpListener,
+    local_addr: SocketAddr,
+    seed_bootstrap: bool,
+    reload_on_sighup: bool,
+}
+
+impl Server {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Spawn workers and begin serving. Dropping the returned handle also triggers shutdown.
+    pub fn start(self) -> ServerHandle {
+        let (tx, rx) = oneshot::channel::<()>();
+        let addr = self.local_addr;
+        let task = tokio::spawn(self.serve(async move {
+            let _ = rx.await;
+        }));
+        ServerHandle {
+            addr,
+            shutdown_tx: tx,
+            task,
+        }
+    }
+
+    /// Serve until `shutdown` resolves, then drain background workers.
+    pub(crate) async fn serve<F>(self, shutdown: F) -> ShutdownReport
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let Server {
+            config,
+            state,
+            rx,
+            router,
+            listener,
+            local_addr,
+            seed_bootstrap,
+            reload_on_sighup,
+        } = self;
+        let workers = spawn_workers(rx, &state, seed_bootstrap, reload_on_sighup);
+        tracing::info!(target: "server", event = "startup", %local_addr, health_path = %config.health_path, "listening");
+        // Audit log seeds (US3 T033)
+        let api = state.api();
+        if let Some(s) = api.provider_sms_seed.or(api.provider_seed) {
+            tracing::info!(target="server", event="provider_seed", provider="sms-mms", seed=%s, "provider seed initialized");
+        }
+        if let Some(s) = api.provider_email_seed.or(api.provider_seed) {
+            tracing::info!(target="server", event="provider_seed", provider="email", seed=%s, "provider seed initialized");
+        }
+
+        // Stop accepting first (in-flight requests finish), then drain workers and report
+        let server =
+            axum::serve(listener, router.into_make_service()).with_graceful_shutdown(shutdown);
+        if let Err(e) = server.await {
+            eprintln!("server error: {e}");
+        }
+        let report = workers.shutdown().await;
+        tracing::info!(
+            target: "server",
+            event = "shutdown",
+            outbound_drained = report.outbound_drained,
+            outbound_abandoned = report.outbound_abandoned,
+            inbound_released = report.inbound_released,
+            inbound_abandoned = report.inbound_abandoned,
+            timed_out = report.timed_out,
+            "server shutdown complete"
+        );
+        report
+    }
+}
+
+fn spawn_workers(
+    rx: mpsc::Receiver<InboundEvent>,
+    state: &AppState,
+    seed_bootstrap: bool,
+    reload_on_sighup: bool,
+) -> BackgroundWorkers {
+    let (trigger, signal) = crate::shutdown::channel();
+    let api = state.api();
+    let drain_timeout = std::time::Duration::from_secs(api.shutdown_drain_timeout_secs);
+    // Outbound worker drains the queue once the HTTP server has stopped
+    let outbound = tokio::spawn(crate::queue::outbound::run(
+        rx,
+        state.clone(),
+        signal.clone(),
+        drain_timeout,
+    ));
+
+    // Queue gauges refresh in the background until shutdown
+    let interval = std::time::Duration::from_secs(api.queue_metrics_interval_secs.max(1));
+    tokio::spawn(crate::worker::queue_metrics::run(
+        state.clone(),
+        interval,
+        signal.clone(),
+    ));
+
+    if reload_on_sighup {
+        crate::reload::spawn_sighup_watcher(state.clone(), signal.clone());
+    }
+
+    // Inbound DB worker only runs when a pool is available
+    let inbound = match state.db() {
+        Some(pool) => {
+            if seed_bootstrap {
+                // Optional: seed demo data to make DB-backed lists non-empty for local runs
+                tokio::spawn({
+                    let pool = pool.clone();
+                    async move { crate::store_db::seed::seed_bootstrap(&pool).await }
+                });
+            }
+            let cfg = state.api.clone(); // shared: reloads reach the worker
+            let heartbeat = state.inbound_heartbeat.clone();
+            let keys = state.keys.clone();
+            Some(tokio::spawn(async move {
+                tracing::info!(
+                    target = "server",
+                    event = "worker_start",
+                    worker = "inbound",
+                    "starting inbound DB worker"
+                );
+                let w = crate::worker::inbound::InboundWorker::new(pool, cfg)
+                    .with_heartbeat(heartbeat)
+                    .with_keys(keys);
+                w.run(signal).await
+            }))
+        }
+        None => {
+            tracing::info!(
+                target = "server",
+                event = "worker_skip",
+                worker = "inbound",
+                "no DB pool; inbound worker disabled"
+            );
+            None
+        }
+    };
+    BackgroundWorkers {
+        trigger,
+        outbound,
+        inbound,
+    }
+}
+
+/// Running server: exposes the bound address and an explicit, reporting shutdown.
+pub struct ServerHandle {
+    addr: SocketAddr,
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<ShutdownReport>,
+}
+
+impl ServerHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stop accepting connections, drain workers, and return what was abandoned.
+    pub async fn shutdown(self) -> ShutdownReport {
+        let _ = self.shutdown_tx.send(());
+        self.task.await.unwrap_or_default()
+    }
+}
+
+/// Background tasks that must be drained before the server completes.
+struct BackgroundWorkers {
+    trigger: crate::shutdown::ShutdownTrigger,
+    outbound: JoinHandle<crate::queue::outbound::OutboundDrain>,
+    inbound: Option<JoinHandle<crate::worker::inbound::InboundDrain>>,
+}
+
+impl BackgroundWorkers {
+    async fn shutdown(self) -> ShutdownReport {
+        tracing::info!(target: "server", event = "shutdown_drain", "draining background workers");
+        self.trigger.fire();
+        let outbound = self.outbound.await.unwrap_or_default();
+        let inbound = match self.inbound {
+            Some(task) => task.await.unwrap_or_default(),
+            None => Default::default(),
+        };
+        ShutdownReport {
+            outbound_drained: outbound.drained,
+            outbound_abandoned: outbound.abandoned,
+            inbound_released: inbound.released,
+            inbound_abandoned: inbound.abandoned,
+            timed_out: outbound.timed_out || inbound.timed_out,
+        }
+    }
+}
+
+/// Default channel routing: SMS/MMS share one mock provider, email has its own (US1 wiring).
+fn default_mock_registry() -> ProviderRegistry {
+    let mut reg = ProviderRegistry::new();
+    let sms = Arc::new(crate::providers::sms_mms::SmsMmsMockProvider::new()) as Arc<dyn Provider>;
+    let email = Arc::new(crate::providers::email::EmailMockProvider::new()) as Arc<dyn Provider>;
+    reg.insert(ChannelKind::Sms, sms.clone());
+    reg.insert(ChannelKind::Mms, sms);
+    reg.insert(ChannelKind::Email, email);
+    reg
+}
+
+/// One breaker per distinct provider name; names align with metrics labels.
+fn breakers_for(registry: &ProviderRegistry, api: &ApiConfig) -> ProviderBreakers {
+    let mut map = HashMap::new();
+    for (_, provider) in registry.iter() {
+        map.entry(provider.name().to_string())
+            .or_insert_with(|| CircuitBreaker::with_policy(api.breaker_policy(provider.name())));
+    }
+    let breakers = ProviderBreakers::new(map);
+    breakers
+        .fallback()
+        .set_policy(api.breaker_policy(PROVIDER_FALLBACK_BREAKER));
+    breakers
+}
@@ -1,14 +1,43 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use messaging_core::conversations::key::KeyNormalization;
+use messaging_core::conversations::normalize_email::{self, EmailRule, EmailRules};
+use messaging_core::conversations::normalize_phone::{self, PhoneRegion};
+
+use crate::middleware::circuit_breaker::BreakerPolicy;
+use crate::providers::latency::LatencyDistribution;
+use crate::providers::scenario::Scenario;
 
 /// API-specific configuration overlays (rates, sizes, breaker thresholds)
+/// Fields missing from the TOML file fall back to `ApiConfig::default()`; unknown (e.g.
+/// misspelled) keys are an error rather than a silently ignored setting.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct ApiConfig {
     /// Maximum JSON request body size in bytes (e.g., 256 KB)
     pub max_body_bytes: usize,
     /// Max attachments per message
     pub max_attachments: usize,
+    /// Per-channel content limit: SMS segments per body (see `messaging_core::sms`; 0 = no limit)
+    pub max_sms_segments: u32,
+    /// Per-channel content limit: MMS body plus attachment URLs, in bytes (0 = no limit)
+    pub max_mms_payload_bytes: usize,
+    /// Per-channel content limit: email body, in bytes (0 = no limit)
+    pub max_email_body_bytes: usize,
+    /// SMS/MMS: ISO region (e.g. `US`, `GB`) of phone numbers written without a country code;
+    /// see `messaging_core::conversations::normalize_phone`
+    pub phone_default_region: String,
+    /// Email: local-part rules by domain (`*` for all other domains), merged over the built-in
+    /// rules; see `messaging_core::conversations::normalize_email`
+    pub email_domain_rules: BTreeMap<String, EmailRule>,
+    /// SMS: replace smart quotes, dashes and ellipses with GSM-7 equivalents when that keeps
+    /// the body out of UCS-2 (see `messaging_core::sms`)
+    pub sms_transliterate: bool,
     /// Per-IP requests per minute (rolling/windowed depending on implementation)
     pub rate_limit_per_ip_per_min: u32,
     /// Per-sender requests per minute
@@ -17,6 +46,25 @@ pub struct ApiConfig {
     pub breaker_error_threshold: u32,
     /// Circuit breaker: open state duration in seconds before half-open
     pub breaker_open_secs: u64,
+    /// Circuit breaker: rolling window in seconds for failure-rate mode (0 = consecutive errors)
+    pub breaker_window_secs: u64,
+    /// Circuit breaker: failure percentage within the window that opens the breaker (0-100)
+    pub breaker_failure_rate_pct: u32,
+    /// Circuit breaker: calls needed within the window before the failure rate counts
+    pub breaker_min_requests: u32,
+    /// Circuit breaker: trial calls let through while half-open
+    pub breaker_half_open_max_probes: u32,
+    /// Circuit breaker: calls at least this slow (ms) count as failures (0 = disabled)
+    pub breaker_slow_call_ms: u64,
+    /// Circuit breaker: cap in seconds for the open duration, which doubles on each re-trip
+    pub breaker_max_open_secs: u64,
+    /// Circuit breaker: per-breaker overrides keyed by provider name, `http` (all route groups)
+    /// or `http.<group>`
+    pub breaker_overrides: BTreeMap<String, BreakerOverrides>,
+    /// HTTP breakers: response statuses that count as failures, as classes (`5xx`) or codes (`429`)
+    pub http_breaker_failure_statuses: Vec<String>,
+    /// HTTP breakers: statuses that never count as failures (client-induced 5xx such as 501)
+    pub http_breaker_ignored_statuses: Vec<u16>,
     /// Mock provider: percentage of timeouts (0-100)
     pub provider_timeout_pct: u32,
     /// Mock provider: percentage of 5xx errors (0-100)
@@ -26,23 +74,40 @@ pub struct ApiConfig {
     /// Mock provider: deterministic RNG seed (optional)
     pub provider_seed: Option<u64>,
     // --- Feature 008 per-provider override placeholders (Phase 1) ---
-    // These will be populated in Phase 2 (T008) via env overrides
-    #[serde(skip)]
+    // These will be populated in Phase 2 (T008) via env overrides; serialized so they show up
+    // in `/admin/config` and reload diffs
+    #[serde(skip_deserializing)]
     pub provider_sms_timeout_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_sms_error_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_sms_ratelimit_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_sms_seed: Option<u64>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_email_timeout_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_email_error_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_email_ratelimit_pct: Option<u32>,
-    #[serde(skip)]
+    #[serde(skip_deserializing)]
     pub provider_email_seed: Option<u64>,
+    /// Mock provider: scripted behaviour per provider (`sms-mms`, `email`), see
+    /// [`crate::providers::scenario`]
+    pub provider_scenarios: BTreeMap<String, Scenario>,
+    /// Mock provider: simulated dispatch latency (see [`crate::providers::latency`])
+    pub provider_latency: LatencyDistribution,
+    /// Mock provider: per-provider latency distributions, replacing `provider_latency`
+    pub provider_latency_overrides: BTreeMap<String, LatencyDistribution>,
+    /// Dispatch timeout in milliseconds: slower dispatches count as timeouts, and a mock timeout
+    /// takes this long (0 = no timeout; mock timeouts return at once)
+    pub provider_dispatch_timeout_ms: u64,
+    /// Append every provider dispatch to this JSONL file (see [`crate::providers::recording`])
+    pub provider_record_path: Option<String>,
+    /// Replace every provider with a replay of this JSONL recording
+    pub provider_replay_path: Option<String>,
+    /// Outbound queue: maximum provider dispatches in flight at once
+    pub outbound_concurrency: u32,
     /// Worker: number of inbound events claimed per cycle
     pub worker_batch_size: u32,
     /// Worker: seconds before a claim is considered stale and can be reaped
@@ -51,6 +116,30 @@ pub struct ApiConfig {
     pub worker_max_retries: u32,
     /// Worker: base backoff in milliseconds for exponential retry
     pub worker_backoff_base_ms: u64,
+    /// Worker: maximum events processed in parallel within a claimed batch
+    pub worker_concurrency: u32,
+    /// Worker: idle poll interval in milliseconds (fallback when no NOTIFY arrives)
+    pub worker_poll_interval_ms: u64,
+    /// Shutdown: seconds workers get to drain queued/claimed work before abandoning it
+    pub shutdown_drain_timeout_secs: u64,
+    /// Queue gauges: seconds between background refreshes of the `queue_*` metrics
+    pub queue_metrics_interval_secs: u64,
+    /// Backpressure: in-memory outbound queue capacity (events)
+    pub queue_capacity: usize,
+    /// Backpressure: milliseconds a request may wait for outbound queue capacity before a 503
+    pub queue_enqueue_wait_ms: u64,
+    /// Backpressure: `Retry-After` seconds sent with queue-saturation 503s
+    pub queue_retry_after_secs: u64,
+    /// Latency histograms: bucket upper bounds (seconds) for HTTP requests per route/status
+    pub http_latency_buckets: Vec<f64>,
+    /// Latency histograms: bucket upper bounds (seconds) for store_db queries per family
+    pub db_latency_buckets: Vec<f64>,
+    /// Latency histograms: bucket upper bounds (seconds) for provider dispatch per provider/outcome
+    pub dispatch_latency_buckets: Vec<f64>,
+    /// Readiness: milliseconds each `/readyz` database check may take before counting as down
+    pub readiness_timeout_ms: u64,
+    /// Readiness: seconds without an inbound worker heartbeat before it counts as stalled
+    pub readiness_worker_stale_secs: u64,
     /// Feature 009: Enable legacy in-memory store fallback when database unavailable or empty
     pub enable_inmemory_fallback: bool,
 }
@@ -60,10 +149,25 @@ impl Default for ApiConfig {
         Self {
             max_body_bytes: 256 * 1024,
             max_attachments: 8,
+            max_sms_segments: 10,
+            max_mms_payload_bytes: 16 * 1024,
+            max_email_body_bytes: 100 * 1024,
+            phone_default_region: "US".to_string(),
+            email_domain_rules: BTreeMap::new(),
+            sms_transliterate: false,
             rate_limit_per_ip_per_min: 120,
             rate_limit_per_sender_per_min: 60,
             breaker_error_threshold: 20,
             breaker_open_secs: 30,
+            breaker_window_secs: 0,
+            breaker_failure_rate_pct: 50,
+            breaker_min_requests: 20,
+            breaker_half_open_max_probes: 1,
+            breaker_slow_call_ms: 0,
+            breaker_max_open_secs: 300,
+            breaker_overrides: BTreeMap::new(),
+            http_breaker_failure_statuses: vec!["5xx".to_string()],
+            http_breaker_ignored_statuses: vec![501, 505],
             provider_timeout_pct: 0,
             provider_error_pct: 0,
             provider_ratelimit_pct: 0,
@@ -76,16 +180,326 @@ impl Default for ApiConfig {
             provider_email_error_pct: None,
             provider_email_ratelimit_pct: None,
             provider_email_seed: None,
+            provider_scenarios: BTreeMap::new(),
+            provider_latency: LatencyDistribution::default(),
+            provider_latency_overrides: BTreeMap::new(),
+            provider_dispatch_timeout_ms: 2000,
+            provider_record_path: None,
+            provider_replay_path: None,
+            outbound_concurrency: 8,
             worker_batch_size: 10,
             worker_claim_timeout_secs: 60,
             worker_max_retries: 5,
             worker_backoff_base_ms: 500,
+            worker_concurrency: 4,
+            worker_poll_interval_ms: 500,
+            shutdown_drain_timeout_secs: 10,
+            queue_metrics_interval_secs: 15,
+            queue_capacity: 1024,
+            queue_enqueue_wait_ms: 100,
+            queue_retry_after_secs: 1,
+            http_latency_buckets: vec![
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ],
+            db_latency_buckets: vec![0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0],
+            dispatch_latency_buckets: vec![
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+            ],
+            readiness_timeout_ms: 1000,
+            readiness_worker_stale_secs: 30,
             enable_inmemory_fallback: true, // Feature 009: Enabled by default for backward compatibility
         }
     }
 }
 
+/// Per-breaker policy overrides (`[breaker_overrides.<name>]`); unset keys use the
+/// `breaker_*` defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BreakerOverrides {
+    pub error_threshold: Option<u32>,
+    pub open_secs: Option<u64>,
+    pub window_secs: Option<u64>,
+    pub failure_rate_pct: Option<u32>,
+    pub min_requests: Option<u32>,
+    pub half_open_max_probes: Option<u32>,
+    pub slow_call_ms: Option<u64>,
+    pub max_open_secs: Option<u64>,
+}
+
+impl BreakerOverrides {
+    fn apply(&self, p: &mut BreakerPolicy) {
+        p.failure_threshold = self.error_threshold.unwrap_or(p.failure_threshold);
+        p.window_secs = self.window_secs.unwrap_or(p.window_secs);
+        p.failure_rate_pct = self.failure_rate_pct.unwrap_or(p.failure_rate_pct);
+        p.min_requests = self.min_requests.unwrap_or(p.min_requests);
+        p.half_open_max_probes = self.half_open_max_probes.unwrap_or(p.half_open_max_probes);
+        p.slow_call_ms = self.slow_call_ms.unwrap_or(p.slow_call_ms);
+        p.open_secs = self.open_secs.unwrap_or(p.open_secs);
+        p.max_open_secs = self.max_open_secs.unwrap_or(p.max_open_secs);
+    }
+}
+
 impl ApiConfig {
+    /// Breaker policy for a provider name, or `http.<group>` for an HTTP route-group breaker.
+    /// Route-group breakers (`http.<group>`) take the `http` overrides first, then their own.
+    pub fn breaker_policy(&self, name: &str) -> BreakerPolicy {
+        let mut policy = BreakerPolicy {
+            failure_threshold: self.breaker_error_threshold,
+            window_secs: self.breaker_window_secs,
+            failure_rate_pct: self.breaker_failure_rate_pct,
+            min_requests: self.breaker_min_requests,
+            half_open_max_probes: self.breaker_half_open_max_probes,
+            slow_call_ms: self.breaker_slow_call_ms,
+            open_secs: self.breaker_open_secs,
+            max_open_secs: self.breaker_max_open_secs,
+        };
+        if let Some((parent, _)) = name.split_once('.') {
+            if let Some(o) = self.breaker_overrides.get(parent) {
+                o.apply(&mut policy);
+            }
+        }
+        if let Some(o) = self.breaker_overrides.get(name) {
+            o.apply(&mut policy);
+        }
+        policy
+    }
+
+    /// Like [`ApiConfig::load`], but a config file that cannot be read or parsed is an error
+    /// instead of a fallback to defaults (used by live reload). `path` defaults to
+    /// `API_CONFIG_FILE`, then `crates/server/config/default.toml` (which may be absent).
+    pub fn try_load(path: Option<&Path>) -> Result<Self, String> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var("API_CONFIG_FILE").ok().map(Into::into));
+        let file_cfg = match explicit {
+            Some(p) => {
+                let contents = fs::read_to_string(&p)
+                    .map_err(|e| format!("cannot read {}: {e}", p.display()))?;
+                toml::from_str::<ApiConfig>(&contents)
+                    .map_err(|e| format!("cannot parse {}: {e}", p.display()))?
+            }
+            None => Self::from_file("crates/server/config/default.toml"),
+        };
+        Ok(Self::apply_env_overrides(file_cfg))
+    }
+
+    /// Semantic checks a reload must pass; every problem found is reported.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let mut check = |ok: bool, msg: String| {
+            if !ok {
+                errors.push(msg);
+            }
+        };
+        check(self.max_body_bytes > 0, "max_body_bytes must be > 0".into());
+        check(
+            normalize_phone::region(&self.phone_default_region).is_some(),
+            format!(
+                "phone_default_region: unknown region {:?}",
+                self.phone_default_region
+            ),
+        );
+        for domain in self.email_domain_rules.keys() {
+            check(
+                domain == "*" || normalize_email::is_valid_domain(domain),
+                format!("email_domain_rules: invalid domain {domain:?}"),
+            );
+        }
+        check(
+            self.rate_limit_per_ip_per_min > 0 && self.rate_limit_per_sender_per_min > 0,
+            "rate limits must be > 0".into(),
+        );
+        for provider in crate::providers::common::MOCK_PROVIDERS {
+            let (timeout, error, ratelimit) = self.provider_pcts(provider);
+            check(
+                timeout + error + ratelimit <= 100,
+                format!("{provider}: timeout + error + ratelimit percentages exceed 100"),
+            );
+        }
+        for (provider, scenario) in &self.provider_scenarios {
+            check(
+                crate::providers::common::MOCK_PROVIDERS.contains(&provider.as_str()),
+                format!("provider_scenarios: unknown provider {provider:?}"),
+            );
+            for e in scenario.validate() {
+                check(false, format!("provider_scenarios.{provider}: {e}"));
+            }
+        }
+        for e in self.provider_latency.validate() {
+            check(false, format!("provider_latency: {e}"));
+        }
+        for (provider, latency) in &self.provider_latency_overrides {
+            check(
+                crate::providers::common::MOCK_PROVIDERS.contains(&provider.as_str()),
+                format!("provider_latency_overrides: unknown provider {provider:?}"),
+            );
+            for e in latency.validate() {
+                check(false, format!("provider_latency_overrides.{provider}: {e}"));
+            }
+        }
+        check(
+            self.breaker_failure_rate_pct <= 100,
+            "breaker_failure_rate_pct must be 0-100".into(),
+        );
+        for (name, o) in &self.breaker_overrides {
+            check(
+                o.failure_rate_pct.is_none_or(|p| p <= 100),
+                format!("breaker_overrides.{name}.failure_rate_pct must be 0-100"),
+            );
+        }
+        for spec in &self.http_breaker_failure_statuses {
+            check(
+                crate::state::breakers::is_valid_status_spec(spec),
+                format!("http_breaker_failure_statuses: invalid entry {spec:?}"),
+            );
+        }
+        check(
+            self.worker_batch_size > 0,
+            "worker_batch_size must be > 0".into(),
+        );
+        check(
+            self.worker_concurrency > 0,
+            "worker_concurrency must be > 0".into(),
+        );
+        check(
+            self.outbound_concurrency > 0,
+            "outbound_concurrency must be > 0".into(),
+        );
+        check(self.queue_capacity > 0, "queue_capacity must be > 0".into());
+        for (key, buckets) in [
+            ("http_latency_buckets", &self.http_latency_buckets),
+            ("db_latency_buckets", &self.db_latency_buckets),
+            ("dispatch_latency_buckets", &self.dispatch_latency_buckets),
+        ] {
+            check(
+                !buckets.is_empty() && buckets.windows(2).all(|w| w[0] < w[1]),
+                format!("{key} must be non-empty and strictly increasing"),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// This config with the per-provider mock overrides it leaves unset taken from `current`.
+    /// Config files cannot set them (only `API_PROVIDER_{SMS,EMAIL}_*` and
+    /// `PUT /api/provider/mock/config` can), so a file reload must not drop what was PUT.
+    pub fn keep_runtime_overrides(mut self, current: &ApiConfig) -> Self {
+        macro_rules! keep {
+            ($($field:ident),*) => {
+                $(self.$field = self.$field.or(current.$field);)*
+            };
+        }
+        keep!(
+            provider_sms_timeout_pct,
+            provider_sms_error_pct,
+            provider_sms_ratelimit_pct,
+            provider_sms_seed,
+            provider_email_timeout_pct,
+            provider_email_error_pct,
+            provider_email_ratelimit_pct,
+            provider_email_seed
+        );
+        self
+    }
+
+    /// Effective (timeout, error, ratelimit) percentages for a provider.
+    pub fn provider_pcts(&self, provider: &str) -> (u32, u32, u32) {
+        match provider {
+            "sms-mms" => (
+                self.provider_sms_timeout_pct
+                    .unwrap_or(self.provider_timeout_pct),
+                self.provider_sms_error_pct
+                    .unwrap_or(self.provider_error_pct),
+                self.provider_sms_ratelimit_pct
+                    .unwrap_or(self.provider_ratelimit_pct),
+            ),
+            "email" => (
+                self.provider_email_timeout_pct
+                    .unwrap_or(self.provider_timeout_pct),
+                self.provider_email_error_pct
+                    .unwrap_or(self.provider_error_pct),
+                self.provider_email_ratelimit_pct
+                    .unwrap_or(self.provider_ratelimit_pct),
+            ),
+            _ => (
+                self.provider_timeout_pct,
+                self.provider_error_pct,
+                self.provider_ratelimit_pct,
+            ),
+        }
+    }
+
+    /// Effective RNG seed for a provider (its override, else `provider_seed`).
+    pub fn provider_seed_for(&self, provider: &str) -> Option<u64> {
+        match provider {
+            "sms-mms" => self.provider_sms_seed.or(self.provider_seed),
+            "email" => self.provider_email_seed.or(self.provider_seed),
+            _ => self.provider_seed,
+        }
+    }
+
+    /// Latency distribution for a provider (its override, else `provider_latency`).
+    pub fn provider_latency_for(&self, provider: &str) -> LatencyDistribution {
+        self.provider_latency_overrides
+            .get(provider)
+            .copied()
+            .unwrap_or(self.provider_latency)
+    }
+
+    /// `phone_default_region`, or US when it is not a known region.
+    pub fn phone_region(&self) -> &'static PhoneRegion {
+        normalize_phone::region(&self.phone_default_region)
+            .unwrap_or_else(normalize_phone::default_region)
+    }
+
+    /// The settings conversation keys are derived with. A server takes them once at startup.
+    pub fn key_normalization(&self) -> KeyNormalization {
+        KeyNormalization {
+            phone_region: self.phone_region(),
+            email_rules: Arc::new(self.email_rules()),
+        }
+    }
+
+    /// The built-in email rules with `email_domain_rules` on top.
+    pub fn email_rules(&self) -> EmailRules {
+        normalize_email::merged_rules(&self.email_domain_rules)
+    }
+
+    /// `provider_dispatch_timeout_ms`, or `None` when disabled.
+    pub fn provider_dispatch_timeout(&self) -> Option<Duration> {
+        (self.provider_dispatch_timeout_ms > 0)
+            .then(|| Duration::from_millis(self.provider_dispatch_timeout_ms))
+    }
+
+    /// Top-level keys whose values differ, in key order, as `(key, old, new)`.
+    pub fn diff(&self, new: &ApiConfig) -> Vec<(String, serde_json::Value, serde_json::Value)> {
+        let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+            (serde_json::to_value(self), serde_json::to_value(new))
+        else {
+            return Vec::new();
+        };
+        let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .filter_map(|k| {
+                let (a, b) = (old.get(k), new.get(k));
+                (a != b).then(|| {
+                    let null = serde_json::Value::Null;
+                    (
+                        k.clone(),
+                        a.unwrap_or(&null).clone(),
+                        b.unwrap_or(&null).clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+
     /// Load configuration from default.toml file and environment overrides (API_* vars).
     pub fn load() -> Self {
         // Allow specifying a custom config file via env
@@ -126,6 +540,9 @@ impl ApiConfig {
         }
         override_u!(max_body_bytes, "API_MAX_BODY_BYTES", usize);
         override_u!(max_attachments, "API_MAX_ATTACHMENTS", u32);
+        override_u!(max_sms_segments, "API_MAX_SMS_SEGMENTS", u32);
+        override_u!(max_mms_payload_bytes, "API_MAX_MMS_PAYLOAD_BYTES", usize);
+        override_u!(max_email_body_bytes, "API_MAX_EMAIL_BODY_BYTES", usize);
         override_u!(
             rate_limit_per_ip_per_min,
             "API_RATE_LIMIT_PER_IP_PER_MIN",
@@ -138,9 +555,29 @@ impl ApiConfig {
         );
         override_u!(breaker_error_threshold, "API_BREAKER_ERROR_THRESHOLD", u32);
         override_u!(breaker_open_secs, "API_BREAKER_OPEN_SECS", u64);
+        override_u!(breaker_window_secs, "API_BREAKER_WINDOW_SECS", u64);
+        override_u!(
+            breaker_failure_rate_pct,
+            "API_BREAKER_FAILURE_RATE_PCT",
+            u32
+        );
+        override_u!(breaker_min_requests, "API_BREAKER_MIN_REQUESTS", u32);
+        override_u!(
+            breaker_half_open_max_probes,
+            "API_BREAKER_HALF_OPEN_MAX_PROBES",
+            u32
+        );
+        override_u!(breaker_slow_call_ms, "API_BREAKER_SLOW_CALL_MS", u64);
+        override_u!(breaker_max_open_secs, "API_BREAKER_MAX_OPEN_SECS", u64);
         override_u!(provider_timeout_pct, "API_PROVIDER_TIMEOUT_PCT", u32);
         override_u!(provider_error_pct, "API_PROVIDER_ERROR_PCT", u32);
         override_u!(provider_ratelimit_pct, "API_PROVIDER_RATELIMIT_PCT", u32);
+        override_u!(
+            provider_dispatch_timeout_ms,
+            "API_PROVIDER_DISPATCH_TIMEOUT_MS",
+            u64
+        );
+        override_u!(outbound_concurrency, "API_OUTBOUND_CONCURRENCY", u32);
         override_u!(worker_batch_size, "API_WORKER_BATCH_SIZE", u32);
         override_u!(
             worker_claim_timeout_secs,
@@ -149,6 +586,65 @@ impl ApiConfig {
         );
         override_u!(worker_max_retries, "API_WORKER_MAX_RETRIES", u32);
         override_u!(worker_backoff_base_ms, "API_WORKER_BACKOFF_BASE_MS", u64);
+        override_u!(worker_concurrency, "API_WORKER_CONCURRENCY", u32);
+        override_u!(worker_poll_interval_ms, "API_WORKER_POLL_INTERVAL_MS", u64);
+        override_u!(
+            shutdown_drain_timeout_secs,
+            "API_SHUTDOWN_DRAIN_TIMEOUT_SECS",
+            u64
+        );
+        override_u!(
+            queue_metrics_interval_secs,
+            "API_QUEUE_METRICS_INTERVAL_SECS",
+            u64
+        );
+        override_u!(queue_capacity, "API_QUEUE_CAPACITY", usize);
+        override_u!(queue_enqueue_wait_ms, "API_QUEUE_ENQUEUE_WAIT_MS", u64);
+        override_u!(queue_retry_after_secs, "API_QUEUE_RETRY_AFTER_SECS", u64);
+        override_u!(readiness_timeout_ms, "API_READINESS_TIMEOUT_MS", u64);
+        override_u!(
+            readiness_worker_stale_secs,
+            "API_READINESS_WORKER_STALE_SECS",
+            u64
+        );
+        // Comma-separated bucket bounds in seconds, e.g. "0.01,0.05,0.1,0.5,1"
+        macro_rules! override_buckets {
+            ($field:ident, $env:literal) => {
+                if let Ok(val) = std::env::var($env) {
+                    let parsed: Result<Vec<f64>, _> =
+                        val.split(',').map(|b| b.trim().parse::<f64>()).collect();
+                    match parsed {
+                        Ok(b) if !b.is_empty() => cfg.$field = b,
+                        _ => tracing::warn!(target="server", key=$env, value=%val, "Invalid bucket list env override"),
+                    }
+                }
+            };
+        }
+        override_buckets!(http_latency_buckets, "API_HTTP_LATENCY_BUCKETS");
+        override_buckets!(db_latency_buckets, "API_DB_LATENCY_BUCKETS");
+        override_buckets!(dispatch_latency_buckets, "API_DISPATCH_LATENCY_BUCKETS");
+        // Comma-separated status lists for the HTTP breakers
+        if let Ok(val) = std::env::var("API_HTTP_BREAKER_FAILURE_STATUSES") {
+            cfg.http_breaker_failure_statuses = val
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Ok(val) = std::env::var("API_HTTP_BREAKER_IGNORED_STATUSES") {
+            let parsed: Result<Vec<u16>, _> = val
+                .split(',')
+                .map(|s| s.trim())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<u16>())
+                .collect();
+            match parsed {
+                Ok(codes) => cfg.http_breaker_ignored_statuses = codes,
+                Err(_) => {
+                    tracing::warn!(target="server", key="API_HTTP_BREAKER_IGNORED_STATUSES", value=%val, "Invalid status list env override")
+                }
+            }
+        }
         if let Ok(seed) = std::env::var("API_PROVIDER_SEED") {
             match seed.parse::<u64>() {
                 Ok(n) => cfg.provider_seed = Some(n),
@@ -189,6 +685,18 @@ impl ApiConfig {
             "API_PROVIDER_EMAIL_RATELIMIT_PCT"
         );
         override_opt_u64!(provider_email_seed, "API_PROVIDER_EMAIL_SEED");
+        if let Ok(path) = std::env::var("API_PROVIDER_RECORD_PATH") {
+            cfg.provider_record_path = Some(path).filter(|p| !p.is_empty());
+        }
+        if let Ok(path) = std::env::var("API_PROVIDER_REPLAY_PATH") {
+            cfg.provider_replay_path = Some(path).filter(|p| !p.is_empty());
+        }
+        if let Ok(region) = std::env::var("API_PHONE_DEFAULT_REGION") {
+            cfg.phone_default_region = region;
+        }
+        if let Ok(val) = std::env::var("API_SMS_TRANSLITERATE") {
+            cfg.sms_transliterate = val.to_lowercase() == "true" || val == "1";
+        }
         // Feature 009: Toggle for in-memory fallback
         if let Ok(val) = std::env::var("API_ENABLE_INMEMORY_FALLBACK") {
             cfg.enable_inmemory_fallback = val.to_lowercase() == "true" || val == "1";
@@ -196,3 +704,57 @@ impl ApiConfig {
         cfg
     }
 }
+
+/// Keys that are read once at startup: a reload records the new value, but it only takes
+/// effect after a restart.
+pub const RESTART_REQUIRED_KEYS: &[&str] = &[
+    "max_body_bytes",
+    "queue_capacity",
+    "outbound_concurrency",
+    "queue_metrics_interval_secs",
+    "shutdown_drain_timeout_secs",
+    "http_latency_buckets",
+    "db_latency_buckets",
+    "dispatch_latency_buckets",
+    "provider_record_path",
+    "provider_replay_path",
+    "phone_default_region",
+    "email_domain_rules",
+];
+
+/// The live `ApiConfig`, shared by request handlers and workers. `store` swaps it atomically:
+/// readers holding an earlier snapshot keep it until they `load` again.
+#[derive(Debug, Clone, Default)]
+pub struct SharedApiConfig {
+    inner: Arc<RwLock<Arc<ApiConfig>>>,
+}
+
+impl SharedApiConfig {
+    pub fn new(cfg: ApiConfig) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(cfg))),
+        }
+    }
+
+    pub fn load(&self) -> Arc<ApiConfig> {
+        self.inner.read().unwrap().clone()
+    }
+
+    /// Swap in the config `f` derives from the current one and return the config it replaced.
+    /// Concurrent updates are serialized, so none is lost; on `Err` the current config stays.
+    pub fn update<T, E>(
+        &self,
+        f: impl FnOnce(&ApiConfig) -> Result<(ApiConfig, T), E>,
+    ) -> Result<(Arc<ApiConfig>, T), E> {
+        let mut guard = self.inner.write().unwrap();
+        let (cfg, out) = f(&guard)?;
+        let previous = std::mem::replace(&mut *guard, Arc::new(cfg));
+        Ok((previous, out))
+    }
+}
+
+impl From<ApiConfig> for SharedApiConfig {
+    fn from(cfg: ApiConfig) -> Self {
+        Self::new(cfg)
+    }
+}
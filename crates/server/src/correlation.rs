@@ -0,0 +1,56 @@
+//! Request correlation IDs.
+//!
+//! `middleware::logging` honors or generates an `x-request-id`, stores it as a
+//! [`CorrelationId`] request extension and runs the rest of the request inside [`CorrelationId::scope`].
+//! From there the ID rides with queued work (`InboundEvent`, `inbound_events.correlation_id`)
+//! into the workers and is stored on `messages`, so one ID follows a send from HTTP to provider.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use uuid::Uuid;
+
+/// Longest caller-supplied `x-request-id` we keep; longer values are replaced.
+pub const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: CorrelationId;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CorrelationId(String);
+
+impl CorrelationId {
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// Accept a caller-supplied ID: non-empty, at most [`MAX_LEN`] visible ASCII characters.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let ok = !value.is_empty()
+            && value.len() <= MAX_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        ok.then(|| Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The ID of the request being handled on this task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|id| id.clone()).ok()
+    }
+
+    /// Run `fut` with `self` as the [`current`](Self::current) correlation ID.
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CURRENT.scope(self, fut).await
+    }
+}
+
+impl std::fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
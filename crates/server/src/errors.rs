@@ -1,12 +1,22 @@
-use axum::{http::StatusCode, Json};
+use axum::{
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::Serialize;
 
+use crate::correlation::CorrelationId;
+use crate::types::ValidationError;
+
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub code: &'static str,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
+    /// The request's `x-request-id`, so a client error report can be matched to server logs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<CorrelationId>,
 }
 
 impl ErrorResponse {
@@ -15,6 +25,7 @@ impl ErrorResponse {
             code,
             message: message.into(),
             details: None,
+            correlation_id: CorrelationId::current(),
         }
     }
 }
@@ -26,6 +37,13 @@ pub fn bad_request(message: impl Into<String>) -> (StatusCode, Json<ErrorRespons
     )
 }
 
+/// 400 for a request body that failed validation, with its `details` when it has any.
+pub fn invalid_request(err: ValidationError) -> (StatusCode, Json<ErrorResponse>) {
+    let mut body = ErrorResponse::new("bad_request", err.message);
+    body.details = err.details;
+    (StatusCode::BAD_REQUEST, Json(body))
+}
+
 pub fn unsupported_media_type() -> (StatusCode, Json<ErrorResponse>) {
     (
         StatusCode::UNSUPPORTED_MEDIA_TYPE,
@@ -59,3 +77,37 @@ pub fn service_unavailable(message: impl Into<String>) -> (StatusCode, Json<Erro
         Json(ErrorResponse::new("service_unavailable", message)),
     )
 }
+
+/// 503 with `Retry-After` when work cannot be queued (outbound queue full or stopped,
+/// inbound_events insert failed). Nothing was accepted, so clients should retry.
+pub fn queue_unavailable(message: impl Into<String>, retry_after_secs: u64) -> Response {
+    let mut resp = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        Json(ErrorResponse::new("queue_unavailable", message)),
+    )
+        .into_response();
+    resp.headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    resp
+}
+
+/// 422 for a config change that failed to load or validate; `details.errors` lists why.
+pub fn invalid_config(errors: Vec<String>) -> (StatusCode, Json<ErrorResponse>) {
+    let mut body = ErrorResponse::new("invalid_config", "Config rejected; current config kept");
+    body.details = Some(serde_json::json!({ "errors": errors }));
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(body))
+}
+
+pub fn not_found(message: impl Into<String>) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse::new("not_found", message)),
+    )
+}
+
+pub fn internal_error(message: impl Into<String>) -> (StatusCode, Json<ErrorResponse>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse::new("internal_error", message)),
+    )
+}
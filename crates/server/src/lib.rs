@@ -1,24 +1,27 @@
 use axum::body::Body;
 use axum::extract::State;
-use axum::http::{Request, StatusCode};
+use axum::http::header::{ACCEPT, CONTENT_TYPE};
+use axum::http::{HeaderMap, Request};
 use axum::middleware as axmw;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use axum::{routing::get, Json, Router};
 use messaging_core::Config;
 use serde::Serialize;
-use sqlx::postgres::PgPoolOptions;
-// (deduped) PgPoolOptions imported above
 use std::future::Future;
 use std::{net::SocketAddr, sync::Arc};
-use tokio::net::TcpListener;
 
 // Expose internal modules for middleware and types so they can be wired in later phases
+pub mod builder;
 pub mod config;
+pub mod correlation;
 pub mod errors;
 pub mod logging;
 pub mod metrics;
+pub mod reload;
+pub mod shutdown;
 pub mod snippet;
+pub mod telemetry;
 pub mod types;
 pub mod middleware {
     pub mod accept;
@@ -36,6 +39,7 @@ pub mod queue {
 }
 pub mod state {
     pub mod breakers;
+    pub mod heartbeat;
     pub mod idempotency;
 }
 
@@ -45,7 +49,10 @@ pub mod providers {
     // Feature 008 provider modules scaffolds
     pub mod common;
     pub mod email;
+    pub mod latency;
+    pub mod recording;
     pub mod registry;
+    pub mod scenario;
     pub mod sms_mms; // shared helpers (Feature 008)
 }
 pub mod store {
@@ -58,22 +65,32 @@ pub mod store_db {
     pub mod inbound_events;
     pub mod messages;
     pub mod normalize;
+    pub mod schema;
     pub mod seed;
 }
 pub mod worker {
     pub mod inbound;
+    pub mod queue_metrics;
 }
 
-use crate::config::ApiConfig;
-use crate::middleware::circuit_breaker::{BreakerState, CircuitBreaker};
+use crate::config::{ApiConfig, SharedApiConfig};
+use crate::middleware::circuit_breaker::BreakerState;
 use crate::middleware::rate_limit::RateLimiter;
-use crate::queue::inbound_events::InboundQueue;
+use crate::queue::inbound_events::{EnqueueError, InboundEvent, InboundQueue, QueueSlot};
+use crate::state::breakers::HttpVerdict;
 use crate::state::idempotency::IdempotencyStore;
 
+pub use crate::builder::{Server, ServerBuilder, ServerHandle};
+
 pub mod api {
+    pub mod breakers;
+    pub mod config;
     pub mod conversations;
+    pub mod dead_letters;
     pub mod messages;
     pub mod provider_mock;
+    pub mod queues;
+    pub mod readiness;
     pub mod webhooks;
 }
 
@@ -84,9 +101,13 @@ struct Health {
 
 #[derive(Clone)]
 pub(crate) struct AppState {
-    api: ApiConfig,
+    // Live config: handlers take a snapshot per request via `api()`
+    api: SharedApiConfig,
+    // Re-read on reload; `None` means `API_CONFIG_FILE` or the default path
+    config_file: Option<Arc<std::path::PathBuf>>,
     rate: RateLimiter,
-    breaker: CircuitBreaker,
+    // HTTP middleware breakers, one per route group
+    http_breakers: crate::state::breakers::HttpBreakers,
     queue: InboundQueue,
     idempotency: IdempotencyStore,
     db: Option<sqlx::PgPool>,
@@ -95,6 +116,12 @@ pub(crate) struct AppState {
     // Feature 008: per-provider circuit breakers
     provider_breakers: crate::state::breakers::ProviderBreakers,
     snippet_length: usize,
+    // Read from the config at startup only: a change would split conversations under new keys
+    keys: messaging_core::conversations::key::KeyNormalization,
+    // Readiness: bumped by the inbound worker's loop
+    inbound_heartbeat: crate::state::heartbeat::Heartbeat,
+    // Readiness: DATABASE_URL was set but the pool could not be created at startup
+    db_error: Option<String>,
 }
 
 impl AppState {
@@ -106,14 +133,54 @@ impl AppState {
         self.snippet_length
     }
 
+    /// What this server derives conversation keys with.
+    pub(crate) fn keys(&self) -> &messaging_core::conversations::key::KeyNormalization {
+        &self.keys
+    }
+
+    /// Current `ApiConfig` snapshot.
+    pub(crate) fn api(&self) -> Arc<ApiConfig> {
+        self.api.load()
+    }
+
     pub(crate) fn inmemory_fallback_enabled(&self) -> bool {
-        self.api.enable_inmemory_fallback
+        self.api().enable_inmemory_fallback
+    }
+
+    /// Reserve an outbound queue slot, waiting at most `queue_enqueue_wait_ms` for capacity.
+    pub(crate) async fn reserve_queue_slot(&self) -> Result<QueueSlot<'_>, EnqueueError> {
+        let wait = std::time::Duration::from_millis(self.api().queue_enqueue_wait_ms);
+        let res = self.queue.reserve(wait).await;
+        if let Err(e) = &res {
+            crate::metrics::record_queue_rejected();
+            tracing::warn!(target="server", event="queue_rejected", reason=%e, depth=self.queue.depth(), "outbound queue saturated");
+        }
+        res
+    }
+
+    /// Queue an event within the wait budget (for handlers with no side effects to order).
+    pub(crate) async fn enqueue(&self, event: InboundEvent) -> Result<(), EnqueueError> {
+        self.reserve_queue_slot().await?.send(event);
+        Ok(())
+    }
+
+    /// 503 + `Retry-After` for a request whose work could not be queued. The idempotency key
+    /// is released so the client's retry is not mistaken for a duplicate.
+    pub(crate) fn reject_unqueued(
+        &self,
+        idempotency_key: Option<&str>,
+        message: impl Into<String>,
+    ) -> Response {
+        if let Some(key) = idempotency_key {
+            self.idempotency.forget(key);
+        }
+        crate::errors::queue_unavailable(message, self.api().queue_retry_after_secs)
     }
 }
 
-fn build_router(health_path: &str, state: AppState) -> Router {
+fn build_router(health_path: &str, state: AppState, extra_routes: Vec<Router>) -> Router {
     let path = health_path.to_string();
-    Router::new()
+    let mut router = Router::new()
         .route(&path, get(health_handler))
         .route("/metrics", get(metrics_handler))
         // API routes expected by bin/test.sh (no version prefix)
@@ -149,6 +216,45 @@ fn build_router(health_path: &str, state: AppState) -> Router {
             "/api/provider/mock/config",
             get(api::provider_mock::get_config).put(api::provider_mock::put_config),
         )
+        // Admin: dead-letter inspection and replay
+        .route("/admin/dead-letters", get(api::dead_letters::list))
+        .route(
+            "/admin/dead-letters/requeue",
+            axum::routing::post(api::dead_letters::requeue_many),
+        )
+        .route(
+            "/admin/dead-letters/purge",
+            axum::routing::post(api::dead_letters::purge_many),
+        )
+        .route(
+            "/admin/dead-letters/{id}",
+            get(api::dead_letters::show).delete(api::dead_letters::purge_one),
+        )
+        .route(
+            "/admin/dead-letters/{id}/requeue",
+            axum::routing::post(api::dead_letters::requeue_one),
+        )
+        // Admin: queue depth / lag inspection
+        .route("/admin/queues", get(api::queues::show))
+        // Admin: circuit breaker inspection and manual control
+        .route(api::breakers::ADMIN_PATH, get(api::breakers::list))
+        .route(
+            "/admin/breakers/{name}",
+            get(api::breakers::show).post(api::breakers::act),
+        )
+        // Admin: live config inspection and reload
+        .route(api::config::CONFIG_PATH, get(api::config::show))
+        .route(
+            api::config::RELOAD_PATH,
+            axum::routing::post(api::config::reload),
+        )
+        // Readiness: dependency checks (the health path above is liveness only)
+        .route(api::readiness::READINESS_PATH, get(api::readiness::ready));
+    // Embedder routes (ServerBuilder::routes) share the API middleware stack below
+    for extra in extra_routes {
+        router = router.merge(extra.with_state::<AppState>(()));
+    }
+    router
         // Global middleware for this phase; specific routes will be added in later phases
         .layer(axmw::from_fn(
             crate::middleware::accept::enforce_json_accept,
@@ -163,7 +269,7 @@ fn build_router(health_path: &str, state: AppState) -> Router {
         ))
         .layer(axmw::from_fn_with_state(state.clone(), rate_limit_ip_layer))
         .layer(crate::middleware::limits::body_limit(
-            state.api.max_body_bytes,
+            state.api().max_body_bytes,
         ))
         // Outermost: request logging
         .layer(axmw::from_fn(crate::middleware::logging::log_requests))
@@ -175,293 +281,52 @@ async fn health_handler() -> Json<Health> {
     Json(Health { status: "ok" })
 }
 
-async fn metrics_handler() -> Json<crate::metrics::MetricsSnapshot> {
-    Json(crate::metrics::snapshot())
+/// GET /metrics: Prometheus text for scrapers (`Accept: text/plain` / OpenMetrics), JSON otherwise.
+async fn metrics_handler(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    // Outbound depth is cheap to read, so keep it exact; inbound gauges come from the sampler
+    api::queues::sample_outbound(&state);
+    let accept = headers.get(ACCEPT).and_then(|v| v.to_str().ok());
+    if crate::metrics::wants_text(accept) {
+        (
+            [(CONTENT_TYPE, crate::metrics::TEXT_CONTENT_TYPE)],
+            crate::metrics::render_text(),
+        )
+            .into_response()
+    } else {
+        Json(crate::metrics::snapshot()).into_response()
+    }
 }
 
+/// Start the server with environment-driven defaults (see `ServerBuilder::from_env`).
+/// The returned task serves until aborted.
 pub async fn run_server(
     config: Arc<Config>,
 ) -> Result<(tokio::task::JoinHandle<()>, SocketAddr), String> {
-    // Build shared state
-    let (queue, rx) = InboundQueue::new(1024);
-    let api_cfg = ApiConfig::load();
-    // Initialize deterministic provider RNG seeds (US3 T030/T033)
-    crate::providers::common::init_rng_seeds(&api_cfg);
-    let api_cfg_for_worker = api_cfg.clone();
-    let brk_thresh = api_cfg.breaker_error_threshold;
-    let brk_open_secs = api_cfg.breaker_open_secs;
-    // Optionally create DB pool if DATABASE_URL is set
-    let db_pool: Option<sqlx::PgPool> = match std::env::var("DATABASE_URL") {
-        Ok(url) => match PgPoolOptions::new().max_connections(5).connect(&url).await {
-            Ok(pool) => Some(pool),
-            Err(e) => {
-                tracing::warn!(target="server", event="db_pool_error", error=%e, "failed to create DB pool; continuing without DB");
-                None
-            }
-        },
-        Err(_) => None,
-    };
-    // Build provider registry and implementations (US1 wiring)
-    let provider_registry = {
-        use crate::providers::registry::{ChannelKind, Provider, ProviderRegistry};
-        use std::sync::Arc;
-        let mut reg = ProviderRegistry::new();
-        let sms =
-            Arc::new(crate::providers::sms_mms::SmsMmsMockProvider::new()) as Arc<dyn Provider>;
-        let email =
-            Arc::new(crate::providers::email::EmailMockProvider::new()) as Arc<dyn Provider>;
-        reg.insert(ChannelKind::Sms, sms.clone());
-        reg.insert(ChannelKind::Mms, sms);
-        reg.insert(ChannelKind::Email, email);
-        reg
-    };
-
-    let state = AppState {
-        rate: RateLimiter::new(
-            api_cfg.rate_limit_per_ip_per_min,
-            api_cfg.rate_limit_per_sender_per_min,
-        ),
-        breaker: CircuitBreaker::new(api_cfg.breaker_error_threshold, api_cfg.breaker_open_secs),
-        queue,
-        idempotency: IdempotencyStore::new(2 * 60 * 60), // 2 hours
-        api: api_cfg.clone(),
-        db: db_pool.clone(),
-        provider_registry,
-        provider_breakers: {
-            use crate::middleware::circuit_breaker::CircuitBreaker;
-            let mut map = std::collections::HashMap::new();
-            // Pre-create breakers for known providers; names align with metrics labels
-            map.insert(
-                crate::metrics::PROVIDER_LABEL_SMS_MMS.to_string(),
-                CircuitBreaker::new(brk_thresh, brk_open_secs),
-            );
-            map.insert(
-                crate::metrics::PROVIDER_LABEL_EMAIL.to_string(),
-                CircuitBreaker::new(brk_thresh, brk_open_secs),
-            );
-            crate::state::breakers::ProviderBreakers::new(map)
-        },
-        snippet_length: config.conversation_snippet_length,
-    };
-    // Spawn outbound worker (mock provider)
-    let worker_state = state.clone();
-    tokio::spawn(async move {
-        crate::queue::outbound::run(rx, worker_state).await;
-    });
-
-    // Spawn inbound DB worker if pool is available
-    if let Some(pool) = db_pool.clone() {
-        // Ensure base identities exist (customer id=1, provider id=1) to satisfy FKs for worker inserts
-        crate::store_db::seed::seed_identities(&pool).await;
-        // Optional: seed demo data to make DB-backed lists non-empty for local runs
-        if std::env::var("SEED_DB").ok().as_deref() == Some("1") {
-            tokio::spawn({
-                let pool = pool.clone();
-                async move { crate::store_db::seed::seed_bootstrap(&pool).await }
-            });
-        }
-        let cfg_clone = api_cfg_for_worker.clone();
-        tokio::spawn(async move {
-            tracing::info!(
-                target = "server",
-                event = "worker_start",
-                worker = "inbound",
-                "starting inbound DB worker"
-            );
-            let w = crate::worker::inbound::InboundWorker::new(pool, cfg_clone);
-            w.run().await;
-        });
-    } else {
-        tracing::info!(
-            target = "server",
-            event = "worker_skip",
-            worker = "inbound",
-            "no DB pool; inbound worker disabled"
-        );
-    }
-
-    // TODO (Feature 007): create PgPool and spawn inbound DB worker
-
-    let router = build_router(&config.health_path, state)
-        // Security headers (Feature 010 T010)
-        .layer(axmw::from_fn(crate::middleware::headers::security_headers))
-        // Inject Arc<Config> into request extensions for middleware access (outermost)
-        .layer(axmw::from_fn_with_state(
-            config.clone(),
-            add_core_config_extension,
-        ));
-
-    // Bind to 127.0.0.1 for tests; for production US1 scope, bind on 0.0.0.0
-    let bind_addr: SocketAddr = ([0, 0, 0, 0], config.port).into();
-    let listener = TcpListener::bind(bind_addr)
-        .await
-        .map_err(|e| format!("failed to bind: {e}"))?;
-    let local_addr = listener
-        .local_addr()
-        .map_err(|e| format!("failed to read local addr: {e}"))?;
-
-    tracing::info!(target: "server", event = "startup", %local_addr, health_path = %config.health_path, "listening");
-    // Audit log seeds (US3 T033) using cloned cfg (api_cfg was moved into state)
-    if let Some(s) = api_cfg_for_worker
-        .provider_sms_seed
-        .or(api_cfg_for_worker.provider_seed)
-    {
-        tracing::info!(target="server", event="provider_seed", provider="sms-mms", seed=%s, "provider seed initialized");
-    }
-    if let Some(s) = api_cfg_for_worker
-        .provider_email_seed
-        .or(api_cfg_for_worker.provider_seed)
-    {
-        tracing::info!(target="server", event="provider_seed", provider="email", seed=%s, "provider seed initialized");
-    }
-
-    let server = axum::serve(listener, router.into_make_service());
+    let server = ServerBuilder::from_env(config).await.build().await?;
+    let local_addr = server.local_addr();
     let handle = tokio::spawn(async move {
-        if let Err(e) = server.await {
-            eprintln!("server error: {e}");
-        }
+        server.serve(std::future::pending()).await;
     });
     Ok((handle, local_addr))
 }
 
+/// Like `run_server`, but stops when `shutdown` resolves and yields a `ShutdownReport`.
 pub async fn run_server_with_shutdown<F>(
     config: Arc<Config>,
     shutdown: F,
-) -> Result<(tokio::task::JoinHandle<()>, SocketAddr), String>
+) -> Result<
+    (
+        tokio::task::JoinHandle<crate::shutdown::ShutdownReport>,
+        SocketAddr,
+    ),
+    String,
+>
 where
     F: Future<Output = ()> + Send + 'static,
 {
-    // Build shared state
-    let (queue, rx) = InboundQueue::new(1024);
-    let api_cfg = ApiConfig::load();
-    crate::providers::common::init_rng_seeds(&api_cfg);
-    let brk_thresh = api_cfg.breaker_error_threshold;
-    let brk_open_secs = api_cfg.breaker_open_secs;
-    // Optionally create DB pool if DATABASE_URL is set
-    let db_pool: Option<sqlx::PgPool> = match std::env::var("DATABASE_URL") {
-        Ok(url) => match PgPoolOptions::new().max_connections(5).connect(&url).await {
-            Ok(pool) => Some(pool),
-            Err(e) => {
-                tracing::warn!(target="server", event="db_pool_error", error=%e, "failed to create DB pool; continuing without DB");
-                None
-            }
-        },
-        Err(_) => None,
-    };
-    // Build provider registry for graceful-shutdown path
-    let provider_registry = {
-        use crate::providers::registry::{ChannelKind, Provider, ProviderRegistry};
-        use std::sync::Arc;
-        let mut reg = ProviderRegistry::new();
-        let sms =
-            Arc::new(crate::providers::sms_mms::SmsMmsMockProvider::new()) as Arc<dyn Provider>;
-        let email =
-            Arc::new(crate::providers::email::EmailMockProvider::new()) as Arc<dyn Provider>;
-        reg.insert(ChannelKind::Sms, sms.clone());
-        reg.insert(ChannelKind::Mms, sms);
-        reg.insert(ChannelKind::Email, email);
-        reg
-    };
-
-    let state = AppState {
-        rate: RateLimiter::new(
-            api_cfg.rate_limit_per_ip_per_min,
-            api_cfg.rate_limit_per_sender_per_min,
-        ),
-        breaker: CircuitBreaker::new(api_cfg.breaker_error_threshold, api_cfg.breaker_open_secs),
-        queue,
-        idempotency: IdempotencyStore::new(2 * 60 * 60),
-        api: api_cfg.clone(),
-        db: db_pool.clone(),
-        provider_registry,
-        provider_breakers: {
-            use crate::middleware::circuit_breaker::CircuitBreaker;
-            let mut map = std::collections::HashMap::new();
-            map.insert(
-                crate::metrics::PROVIDER_LABEL_SMS_MMS.to_string(),
-                CircuitBreaker::new(brk_thresh, brk_open_secs),
-            );
-            map.insert(
-                crate::metrics::PROVIDER_LABEL_EMAIL.to_string(),
-                CircuitBreaker::new(brk_thresh, brk_open_secs),
-            );
-            crate::state::breakers::ProviderBreakers::new(map)
-        },
-        snippet_length: config.conversation_snippet_length,
-    };
-    // Spawn outbound worker with shutdown signal? For now, fire-and-forget; shutdown will drop rx
-    let worker_state = state.clone();
-    tokio::spawn(async move {
-        crate::queue::outbound::run(rx, worker_state).await;
-    });
-
-    // Spawn inbound DB worker if pool is available
-    if let Some(pool) = db_pool.clone() {
-        // Ensure base identities exist (customer id=1, provider id=1) to satisfy FKs for worker inserts
-        crate::store_db::seed::seed_identities(&pool).await;
-        // Optional: seed demo data for graceful startup with DB present
-        if std::env::var("SEED_DB").ok().as_deref() == Some("1") {
-            tokio::spawn({
-                let pool = pool.clone();
-                async move { crate::store_db::seed::seed_bootstrap(&pool).await }
-            });
-        }
-        let cfg_clone = state.api.clone();
-        tokio::spawn(async move {
-            tracing::info!(
-                target = "server",
-                event = "worker_start",
-                worker = "inbound",
-                "starting inbound DB worker"
-            );
-            let w = crate::worker::inbound::InboundWorker::new(pool, cfg_clone);
-            w.run().await;
-        });
-    } else {
-        tracing::info!(
-            target = "server",
-            event = "worker_skip",
-            worker = "inbound",
-            "no DB pool; inbound worker disabled"
-        );
-    }
-
-    let router = build_router(&config.health_path, state)
-        .layer(axmw::from_fn(crate::middleware::headers::security_headers))
-        .layer(axmw::from_fn_with_state(
-            config.clone(),
-            add_core_config_extension,
-        ));
-
-    let bind_addr: SocketAddr = ([0, 0, 0, 0], config.port).into();
-    let listener = TcpListener::bind(bind_addr)
-        .await
-        .map_err(|e| format!("failed to bind: {e}"))?;
-    let local_addr = listener
-        .local_addr()
-        .map_err(|e| format!("failed to read local addr: {e}"))?;
-
-    tracing::info!(target: "server", event = "startup", %local_addr, health_path = %config.health_path, "listening");
-    if let Some(s) = api_cfg.provider_sms_seed.or(api_cfg.provider_seed) {
-        tracing::info!(target="server", event="provider_seed", provider="sms-mms", seed=%s, "provider seed initialized");
-    }
-    if let Some(s) = api_cfg.provider_email_seed.or(api_cfg.provider_seed) {
-        tracing::info!(target="server", event="provider_seed", provider="email", seed=%s, "provider seed initialized");
-    }
-
-    let server = axum::serve(listener, router.into_make_service()).with_graceful_shutdown(shutdown);
-
-    let handle = tokio::spawn(async move {
-        match server.await {
-            Ok(()) => {
-                tracing::info!(target: "server", event = "shutdown", "server shutdown complete");
-            }
-            Err(e) => {
-                eprintln!("server error: {e}");
-            }
-        }
-    });
-    Ok((handle, local_addr))
+    let server = ServerBuilder::from_env(config).await.build().await?;
+    let local_addr = server.local_addr();
+    Ok((tokio::spawn(server.serve(shutdown)), local_addr))
 }
 
 // ---------- Middleware glue (Phase 2) ----------
@@ -516,25 +381,38 @@ async fn circuit_breaker_layer(
     req: Request<Body>,
     next: Next,
 ) -> Response {
-    if state.breaker.before_request() == BreakerState::Open {
+    // Readiness reports its own 503s, and operators must be able to close a forced-open
+    // breaker: neither may trip or be blocked by it
+    let path = req.uri().path();
+    if path == api::readiness::READINESS_PATH || path.starts_with(api::breakers::ADMIN_PATH) {
+        return next.run(req).await;
+    }
+    let (group, breaker) = state.http_breakers.for_path(path);
+    if breaker.before_request() == BreakerState::Open {
         crate::metrics::record_breaker_open();
+        tracing::debug!(
+            target = "server",
+            event = "http_short_circuit",
+            group,
+            "route group breaker open"
+        );
         let (status, body) = crate::errors::service_unavailable("Temporarily unavailable");
         let mut resp = (status, body).into_response();
-        let secs = state.breaker.recovery_timeout.as_secs().to_string();
+        let secs = breaker.retry_after().as_secs().to_string();
         if let Ok(v) = axum::http::HeaderValue::from_str(&secs) {
             resp.headers_mut()
                 .insert(axum::http::header::RETRY_AFTER, v);
         }
         return resp;
     }
+    let started = std::time::Instant::now();
     let response = next.run(req).await;
-    // Update breaker based on response status
-    let status = response.status();
-    if status.is_server_error() {
-        state.breaker.record_failure();
-    } else if status.is_success() || status == StatusCode::ACCEPTED || status == StatusCode::CREATED
-    {
-        state.breaker.record_success();
+    // Update breaker based on the configured status classification; slow successes count as
+    // failures
+    match state.http_breakers.classify(response.status().as_u16()) {
+        HttpVerdict::Failure => breaker.record_call(false, started.elapsed()),
+        HttpVerdict::Success => breaker.record_call(true, started.elapsed()),
+        HttpVerdict::Neutral => breaker.release(),
     }
     response
 }
@@ -545,7 +423,7 @@ async fn extract_idempotency_layer(req: Request<Body>, next: Next) -> Response {
 }
 
 // Insert Arc<messaging_core::Config> into request extensions so middleware can read it
-async fn add_core_config_extension(
+pub(crate) async fn add_core_config_extension(
     State(cfg): State<Arc<Config>>,
     mut req: Request<Body>,
     next: Next,
@@ -1,26 +1,26 @@
 use messaging_core::config::{ConfigSources, Source};
-use messaging_core::{logging, Config};
+use messaging_core::Config;
+use messaging_server::telemetry::{self, TelemetryConfig};
+use messaging_server::ServerBuilder;
 use std::sync::Arc;
 use tokio::time::{timeout, Duration};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (cfg, sources) = Config::load_with_sources().map_err(|e| format!("config error: {e}"))?;
-    // Initialize logging based on resolved level
-    logging::init_logging(&cfg.log_level).map_err(|e| format!("logging init error: {e}"))?;
+    // Initialize logging based on resolved level; spans are exported when OTEL_EXPORTER_OTLP_* is set
+    let telemetry = telemetry::init(&cfg.log_level, &TelemetryConfig::from_env())
+        .map_err(|e| format!("logging init error: {e}"))?;
 
     // Log resolved config and detected sources
     log_config(&cfg, &sources);
 
     let cfg = Arc::new(cfg);
-    // Prepare shutdown trigger for server (oneshot)
-    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
-    let shutdown = async move {
-        let _ = shutdown_rx.await;
-    };
-
-    let (handle, _addr) = match messaging_server::run_server_with_shutdown(cfg, shutdown).await {
-        Ok(ok) => ok,
+    let builder = ServerBuilder::from_env(cfg).await;
+    // Bounded graceful shutdown: workers drain within the configured window, plus slack
+    let shutdown_budget = Duration::from_secs(builder.api().shutdown_drain_timeout_secs + 5);
+    let handle = match builder.build().await {
+        Ok(server) => server.start(),
         Err(e) => {
             eprintln!("server startup failed: {e}");
             std::process::exit(1);
@@ -30,20 +30,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Wait for OS signals
     shutdown_signal().await;
     tracing::info!(target: "server", event = "shutdown_signal", "shutdown signal received");
-    let _ = shutdown_tx.send(());
 
-    // Bounded graceful shutdown timeout
-    match timeout(Duration::from_secs(5), handle).await {
-        Ok(_) => {
-            tracing::info!(target: "server", event = "shutdown_done", "graceful shutdown complete")
+    match timeout(shutdown_budget, handle.shutdown()).await {
+        Ok(report) => {
+            tracing::info!(target: "server", event = "shutdown_done", outbound_abandoned = report.outbound_abandoned, inbound_released = report.inbound_released, timed_out = report.timed_out, "graceful shutdown complete")
         }
         Err(_) => {
-            tracing::warn!(target: "server", event = "shutdown_timeout", "graceful shutdown timed out; aborting");
-            // Abort the task to force shutdown
-            // Note: handle is a JoinHandle; abort here if still running
-            // We can't move handle after await, so we abort via AbortHandle by cloning? Use abort directly:
+            tracing::warn!(target: "server", event = "shutdown_timeout", "graceful shutdown timed out; exiting");
         }
     }
+    // Flush pending spans before exit
+    drop(telemetry);
     Ok(())
 }
 
@@ -1,38 +1,352 @@
+//! Process-wide metrics registry.
+//!
+//! Everything is registered in one Prometheus [`Registry`] (prefix `messaging_`). `GET /metrics`
+//! serves it as Prometheus text when asked (`Accept: text/plain` or OpenMetrics) and otherwise
+//! as the legacy JSON [`MetricsSnapshot`], which is derived from the same registry.
+//! Provider metrics are labeled by `Provider::name()`, so new providers need no changes here.
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{SystemTime, UNIX_EPOCH};
-
-// Simple in-process counters for early observability. For production replace with Prometheus exporter.
-static TOTAL_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
-static TOTAL_BREAKER_OPEN: AtomicU64 = AtomicU64::new(0);
-static DISPATCH_ATTEMPTS: AtomicU64 = AtomicU64::new(0);
-static DISPATCH_SUCCESS: AtomicU64 = AtomicU64::new(0);
-static DISPATCH_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
-static DISPATCH_ERROR: AtomicU64 = AtomicU64::new(0);
-// Per-provider counters (Feature 008 US1)
-static SMS_MMS_ATTEMPTS: AtomicU64 = AtomicU64::new(0);
-static SMS_MMS_SUCCESS: AtomicU64 = AtomicU64::new(0);
-static SMS_MMS_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
-static SMS_MMS_ERROR: AtomicU64 = AtomicU64::new(0);
-static EMAIL_ATTEMPTS: AtomicU64 = AtomicU64::new(0);
-static EMAIL_SUCCESS: AtomicU64 = AtomicU64::new(0);
-static EMAIL_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
-static EMAIL_ERROR: AtomicU64 = AtomicU64::new(0);
-static BREAKER_TRANSITIONS: AtomicU64 = AtomicU64::new(0); // global breaker transitions (legacy)
-                                                           // Per-provider breaker transition counters (Feature 008 US2)
-static SMS_MMS_BREAKER_TRANSITIONS: AtomicU64 = AtomicU64::new(0);
-static EMAIL_BREAKER_TRANSITIONS: AtomicU64 = AtomicU64::new(0);
-static WORKER_CLAIMED: AtomicU64 = AtomicU64::new(0);
-static WORKER_PROCESSED: AtomicU64 = AtomicU64::new(0);
-static WORKER_ERROR: AtomicU64 = AtomicU64::new(0);
-static WORKER_DEAD_LETTER: AtomicU64 = AtomicU64::new(0);
-static WORKER_LATENCY_TOTAL_US: AtomicU64 = AtomicU64::new(0);
-static WORKER_LATENCY_MAX_US: AtomicU64 = AtomicU64::new(0);
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-// --- Feature 008 placeholders (Phase 1) ---
-// Provider label constants used for per-provider metrics in later phases
+use crate::config::ApiConfig;
+
+// Provider labels of the built-in mock providers (kept for the legacy JSON fields)
 pub const PROVIDER_LABEL_SMS_MMS: &str = "sms-mms";
 pub const PROVIDER_LABEL_EMAIL: &str = "email";
 
+/// Content type of the Prometheus text exposition.
+pub const TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const OUTCOME_SUCCESS: &str = "success";
+const OUTCOME_RATE_LIMITED: &str = "rate_limited";
+const OUTCOME_ERROR: &str = "error";
+const INBOUND_STATUSES: [&str; 4] = ["pending", "processing", "done", "dead"];
+const SMS_ENCODINGS: [&str; 2] = ["gsm7", "ucs2"];
+
+struct Metrics {
+    registry: Registry,
+    rate_limited: IntCounter,
+    breaker_open: IntCounter,
+    breaker_transitions: IntCounter,
+    dispatch_attempts: IntCounter,
+    dispatch_results: IntCounterVec,
+    provider_attempts: IntCounterVec,
+    provider_results: IntCounterVec,
+    provider_breaker_transitions: IntCounterVec,
+    /// Provider labels seen so far, for the JSON `providers` map
+    providers: Mutex<BTreeSet<String>>,
+    invalid_routing: IntCounter,
+    worker_claimed: IntCounter,
+    worker_processed: IntCounter,
+    worker_errors: IntCounter,
+    worker_dead_letters: IntCounter,
+    worker_processing_seconds: Histogram,
+    conversations_created: IntCounter,
+    conversations_reused: IntCounter,
+    conversations_failures: IntCounter,
+    queue_inbound_events: IntGaugeVec,
+    queue_inbound_oldest_pending_age_seconds: Gauge,
+    queue_inbound_lag_seconds: Gauge,
+    queue_outbound_depth: IntGauge,
+    queue_outbound_capacity: IntGauge,
+    queue_rejected: IntCounter,
+    sms_messages: IntCounterVec,
+    sms_segments: IntCounterVec,
+}
+
+// Histograms have no max; the JSON snapshot still reports one
+static WORKER_LATENCY_MAX_US: AtomicU64 = AtomicU64::new(0);
+
+fn counter(registry: &Registry, name: &str, help: &str) -> IntCounter {
+    let c = IntCounter::new(name, help).expect("valid counter");
+    registry.register(Box::new(c.clone())).expect("register");
+    c
+}
+
+fn counter_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    let c = IntCounterVec::new(Opts::new(name, help), labels).expect("valid counter vec");
+    registry.register(Box::new(c.clone())).expect("register");
+    c
+}
+
+fn gauge(registry: &Registry, name: &str, help: &str) -> Gauge {
+    let g = Gauge::new(name, help).expect("valid gauge");
+    registry.register(Box::new(g.clone())).expect("register");
+    g
+}
+
+fn int_gauge(registry: &Registry, name: &str, help: &str) -> IntGauge {
+    let g = IntGauge::new(name, help).expect("valid gauge");
+    registry.register(Box::new(g.clone())).expect("register");
+    g
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(|| {
+    let r = Registry::new_custom(Some("messaging".to_string()), None).expect("registry");
+    let queue_inbound_events = IntGaugeVec::new(
+        Opts::new(
+            "queue_inbound_events",
+            "inbound_events rows by status (sampled)",
+        ),
+        &["status"],
+    )
+    .expect("valid gauge vec");
+    r.register(Box::new(queue_inbound_events.clone()))
+        .expect("register");
+    let worker_processing_seconds = Histogram::with_opts(HistogramOpts::new(
+        "worker_processing_seconds",
+        "Time to process one inbound event",
+    ))
+    .expect("valid histogram");
+    r.register(Box::new(worker_processing_seconds.clone()))
+        .expect("register");
+    Metrics {
+        rate_limited: counter(
+            &r,
+            "http_rate_limited_total",
+            "Requests rejected by the per-IP rate limiter",
+        ),
+        breaker_open: counter(
+            &r,
+            "breaker_open_total",
+            "Requests or dispatches short-circuited by an open breaker",
+        ),
+        breaker_transitions: counter(
+            &r,
+            "breaker_transitions_total",
+            "Circuit breaker state transitions (all breakers)",
+        ),
+        dispatch_attempts: counter(&r, "dispatch_attempts_total", "Outbound dispatch attempts"),
+        dispatch_results: counter_vec(
+            &r,
+            "dispatch_results_total",
+            "Outbound dispatch results by outcome",
+            &["outcome"],
+        ),
+        provider_attempts: counter_vec(
+            &r,
+            "provider_dispatch_attempts_total",
+            "Dispatch attempts per provider",
+            &["provider"],
+        ),
+        provider_results: counter_vec(
+            &r,
+            "provider_dispatch_results_total",
+            "Dispatch results per provider and outcome",
+            &["provider", "outcome"],
+        ),
+        provider_breaker_transitions: counter_vec(
+            &r,
+            "provider_breaker_transitions_total",
+            "Circuit breaker state transitions per provider",
+            &["provider"],
+        ),
+        providers: Mutex::new(BTreeSet::new()),
+        invalid_routing: counter(
+            &r,
+            "invalid_routing_total",
+            "Outbound events with no provider for their channel",
+        ),
+        worker_claimed: counter(
+            &r,
+            "worker_claimed_total",
+            "inbound_events claimed by the worker",
+        ),
+        worker_processed: counter(
+            &r,
+            "worker_processed_total",
+            "inbound_events processed successfully",
+        ),
+        worker_errors: counter(
+            &r,
+            "worker_errors_total",
+            "inbound_events processing failures scheduled for retry",
+        ),
+        worker_dead_letters: counter(
+            &r,
+            "worker_dead_letters_total",
+            "inbound_events moved to dead after exhausting retries",
+        ),
+        worker_processing_seconds,
+        conversations_created: counter(&r, "conversations_created_total", "Conversations created"),
+        conversations_reused: counter(
+            &r,
+            "conversations_reused_total",
+            "Existing conversations reused",
+        ),
+        conversations_failures: counter(
+            &r,
+            "conversations_failures_total",
+            "Conversation upsert failures",
+        ),
+        queue_inbound_events,
+        queue_inbound_oldest_pending_age_seconds: gauge(
+            &r,
+            "queue_inbound_oldest_pending_age_seconds",
+            "Age of the oldest pending inbound event (sampled)",
+        ),
+        queue_inbound_lag_seconds: gauge(
+            &r,
+            "queue_inbound_lag_seconds",
+            "Wait of the oldest ready pending inbound event (sampled)",
+        ),
+        queue_outbound_depth: int_gauge(
+            &r,
+            "queue_outbound_depth",
+            "Events buffered in the outbound queue",
+        ),
+        queue_outbound_capacity: int_gauge(
+            &r,
+            "queue_outbound_capacity",
+            "Outbound queue capacity",
+        ),
+        queue_rejected: counter(
+            &r,
+            "queue_rejected_total",
+            "Requests answered 503 because work could not be queued",
+        ),
+        sms_messages: counter_vec(
+            &r,
+            "sms_messages_total",
+            "Accepted SMS bodies by encoding",
+            &["encoding"],
+        ),
+        sms_segments: counter_vec(
+            &r,
+            "sms_segments_total",
+            "SMS segments of accepted bodies by encoding",
+            &["encoding"],
+        ),
+        registry: r,
+    }
+});
+
+/// Latency histograms, registered once with the buckets from `ApiConfig` (see
+/// [`init_latency_histograms`]).
+struct LatencyHistograms {
+    http: HistogramVec,
+    db: HistogramVec,
+    dispatch: HistogramVec,
+}
+
+static LATENCY: OnceLock<LatencyHistograms> = OnceLock::new();
+
+/// Prometheus requires strictly increasing, finite bounds; fall back to the defaults otherwise.
+fn sanitize_buckets(name: &str, configured: &[f64], default: &[f64]) -> Vec<f64> {
+    let mut buckets: Vec<f64> = configured
+        .iter()
+        .copied()
+        .filter(|b| b.is_finite() && *b > 0.0)
+        .collect();
+    buckets.sort_by(|a, b| a.total_cmp(b));
+    buckets.dedup();
+    if buckets.is_empty() {
+        tracing::warn!(target="server", histogram=%name, "no valid latency buckets configured; using defaults");
+        return default.to_vec();
+    }
+    buckets
+}
+
+fn histogram_vec(name: &str, help: &str, labels: &[&str], buckets: Vec<f64>) -> HistogramVec {
+    let h = HistogramVec::new(HistogramOpts::new(name, help).buckets(buckets), labels)
+        .expect("valid histogram vec");
+    METRICS
+        .registry
+        .register(Box::new(h.clone()))
+        .expect("register");
+    h
+}
+
+impl LatencyHistograms {
+    fn register(api: &ApiConfig) -> Self {
+        let defaults = ApiConfig::default();
+        Self {
+            http: histogram_vec(
+                "http_request_duration_seconds",
+                "HTTP request latency by method, route template and status",
+                &["method", "route", "status"],
+                sanitize_buckets(
+                    "http",
+                    &api.http_latency_buckets,
+                    &defaults.http_latency_buckets,
+                ),
+            ),
+            db: histogram_vec(
+                "db_query_duration_seconds",
+                "store_db query latency by query family",
+                &["query"],
+                sanitize_buckets("db", &api.db_latency_buckets, &defaults.db_latency_buckets),
+            ),
+            dispatch: histogram_vec(
+                "provider_dispatch_duration_seconds",
+                "Provider dispatch latency by provider and outcome",
+                &["provider", "outcome"],
+                sanitize_buckets(
+                    "dispatch",
+                    &api.dispatch_latency_buckets,
+                    &defaults.dispatch_latency_buckets,
+                ),
+            ),
+        }
+    }
+}
+
+fn latency() -> &'static LatencyHistograms {
+    LATENCY.get_or_init(|| LatencyHistograms::register(&ApiConfig::default()))
+}
+
+/// Register the latency histograms with the configured buckets. The registry is process-wide,
+/// so the first server built in a process decides the buckets; later calls are no-ops.
+pub fn init_latency_histograms(api: &ApiConfig) {
+    LATENCY.get_or_init(|| LatencyHistograms::register(api));
+}
+
+/// `route` should be the matched route template (e.g. `/api/conversations/{id}`), not the raw
+/// path, to keep label cardinality bounded.
+pub fn observe_http_request(method: &str, route: &str, status: u16, elapsed: Duration) {
+    latency()
+        .http
+        .with_label_values(&[method, route, &status.to_string()])
+        .observe(elapsed.as_secs_f64());
+}
+
+pub fn observe_provider_dispatch(provider: &str, outcome: &str, elapsed: Duration) {
+    latency()
+        .dispatch
+        .with_label_values(&[provider, outcome])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// Times a store_db query family; the observation is recorded when the timer drops, so
+/// early returns via `?` are measured too.
+pub struct DbTimer {
+    query: &'static str,
+    started: Instant,
+}
+
+impl Drop for DbTimer {
+    fn drop(&mut self) {
+        latency()
+            .db
+            .with_label_values(&[self.query])
+            .observe(self.started.elapsed().as_secs_f64());
+    }
+}
+
+pub fn db_timer(query: &'static str) -> DbTimer {
+    DbTimer {
+        query,
+        started: Instant::now(),
+    }
+}
+
 #[derive(serde::Serialize)]
 pub struct MetricsSnapshot {
     pub ts_unix_ms: u128,
@@ -64,168 +378,313 @@ pub struct MetricsSnapshot {
     pub conversations_created: u64,
     pub conversations_reused: u64,
     pub conversations_failures: u64,
+    // Queue gauges: inbound_events by status, age/lag of pending work, outbound buffer
+    pub queue_inbound_pending: u64,
+    pub queue_inbound_processing: u64,
+    pub queue_inbound_done: u64,
+    pub queue_inbound_dead: u64,
+    pub queue_inbound_oldest_pending_age_ms: u64,
+    pub queue_inbound_lag_ms: u64,
+    pub queue_outbound_depth: u64,
+    pub queue_outbound_capacity: u64,
+    /// Requests answered 503 because the outbound queue was full or closed
+    pub queue_rejected: u64,
+    /// Accepted SMS bodies and the segments they take (all encodings)
+    pub sms_messages: u64,
+    pub sms_segments: u64,
+    /// Per-provider counters for every provider that has dispatched
+    pub providers: BTreeMap<String, ProviderSnapshot>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ProviderSnapshot {
+    pub attempts: u64,
+    pub success: u64,
+    pub rate_limited: u64,
+    pub error: u64,
+    pub breaker_transitions: u64,
+}
+
+fn note_provider(label: &str) {
+    let mut seen = METRICS.providers.lock().unwrap();
+    if !seen.contains(label) {
+        seen.insert(label.to_string());
+    }
 }
 
 pub fn record_rate_limited() {
-    TOTAL_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+    METRICS.rate_limited.inc();
 }
 
 pub fn record_breaker_open() {
-    TOTAL_BREAKER_OPEN.fetch_add(1, Ordering::Relaxed);
+    METRICS.breaker_open.inc();
 }
 
 pub fn record_breaker_transition() {
-    BREAKER_TRANSITIONS.fetch_add(1, Ordering::Relaxed);
+    METRICS.breaker_transitions.inc();
 }
 
 pub fn record_provider_breaker_transition(label: &str) {
-    match label {
-        PROVIDER_LABEL_SMS_MMS => {
-            SMS_MMS_BREAKER_TRANSITIONS.fetch_add(1, Ordering::Relaxed);
-        }
-        PROVIDER_LABEL_EMAIL => {
-            EMAIL_BREAKER_TRANSITIONS.fetch_add(1, Ordering::Relaxed);
-        }
-        _ => {}
-    }
+    note_provider(label);
+    METRICS
+        .provider_breaker_transitions
+        .with_label_values(&[label])
+        .inc();
 }
 
 pub fn record_dispatch_attempt() {
-    DISPATCH_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+    METRICS.dispatch_attempts.inc();
 }
 
 pub fn record_dispatch_success() {
-    DISPATCH_SUCCESS.fetch_add(1, Ordering::Relaxed);
+    METRICS
+        .dispatch_results
+        .with_label_values(&[OUTCOME_SUCCESS])
+        .inc();
 }
 
 pub fn record_dispatch_rate_limited() {
-    DISPATCH_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+    METRICS
+        .dispatch_results
+        .with_label_values(&[OUTCOME_RATE_LIMITED])
+        .inc();
 }
 
 pub fn record_dispatch_error() {
-    DISPATCH_ERROR.fetch_add(1, Ordering::Relaxed);
+    METRICS
+        .dispatch_results
+        .with_label_values(&[OUTCOME_ERROR])
+        .inc();
 }
 
 pub fn record_provider_attempt(label: &str) {
-    match label {
-        PROVIDER_LABEL_SMS_MMS => {
-            SMS_MMS_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
-        }
-        PROVIDER_LABEL_EMAIL => {
-            EMAIL_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
-        }
-        _ => {}
-    }
+    note_provider(label);
+    METRICS.provider_attempts.with_label_values(&[label]).inc();
+}
+
+fn record_provider_result(label: &str, outcome: &str) {
+    note_provider(label);
+    METRICS
+        .provider_results
+        .with_label_values(&[label, outcome])
+        .inc();
 }
 
 pub fn record_provider_success(label: &str) {
-    match label {
-        PROVIDER_LABEL_SMS_MMS => {
-            SMS_MMS_SUCCESS.fetch_add(1, Ordering::Relaxed);
-        }
-        PROVIDER_LABEL_EMAIL => {
-            EMAIL_SUCCESS.fetch_add(1, Ordering::Relaxed);
-        }
-        _ => {}
-    }
+    record_provider_result(label, OUTCOME_SUCCESS);
 }
 
 pub fn record_provider_rate_limited(label: &str) {
-    match label {
-        PROVIDER_LABEL_SMS_MMS => {
-            SMS_MMS_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
-        }
-        PROVIDER_LABEL_EMAIL => {
-            EMAIL_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
-        }
-        _ => {}
-    }
+    record_provider_result(label, OUTCOME_RATE_LIMITED);
 }
 
 pub fn record_provider_error(label: &str) {
-    match label {
-        PROVIDER_LABEL_SMS_MMS => {
-            SMS_MMS_ERROR.fetch_add(1, Ordering::Relaxed);
+    record_provider_result(label, OUTCOME_ERROR);
+}
+
+pub fn record_worker_claimed(n: u64) {
+    METRICS.worker_claimed.inc_by(n);
+}
+
+pub fn record_worker_processed(latency_us: u64) {
+    METRICS.worker_processed.inc();
+    METRICS
+        .worker_processing_seconds
+        .observe(latency_us as f64 / 1_000_000.0);
+    WORKER_LATENCY_MAX_US.fetch_max(latency_us, Ordering::Relaxed);
+}
+
+pub fn record_worker_error() {
+    METRICS.worker_errors.inc();
+}
+
+pub fn record_worker_dead_letter() {
+    METRICS.worker_dead_letters.inc();
+}
+
+pub fn record_invalid_routing() {
+    METRICS.invalid_routing.inc();
+}
+
+pub fn record_queue_rejected() {
+    METRICS.queue_rejected.inc();
+}
+
+/// An accepted SMS body: `encoding` is `gsm7` or `ucs2` (`messaging_core::sms`).
+pub fn record_sms_segments(encoding: &str, segments: u64) {
+    METRICS.sms_messages.with_label_values(&[encoding]).inc();
+    METRICS
+        .sms_segments
+        .with_label_values(&[encoding])
+        .inc_by(segments);
+}
+
+pub fn set_queue_inbound_gauges(stats: &crate::store_db::inbound_events::InboundQueueStats) {
+    for status in INBOUND_STATUSES {
+        METRICS
+            .queue_inbound_events
+            .with_label_values(&[status])
+            .set(stats.count(status));
+    }
+    METRICS
+        .queue_inbound_oldest_pending_age_seconds
+        .set(stats.oldest_pending_age_secs.unwrap_or(0.0).max(0.0));
+    METRICS
+        .queue_inbound_lag_seconds
+        .set(stats.lag_secs.unwrap_or(0.0).max(0.0));
+}
+
+pub fn set_queue_outbound_gauges(depth: usize, capacity: usize) {
+    METRICS.queue_outbound_depth.set(depth as i64);
+    METRICS.queue_outbound_capacity.set(capacity as i64);
+}
+
+/// Counters owned by messaging-core are plain atomics; mirror them into the registry.
+fn sync_external() {
+    let conv = messaging_core::conversations::metrics::metrics();
+    for (src, dst) in [
+        (&conv.created, &METRICS.conversations_created),
+        (&conv.reused, &METRICS.conversations_reused),
+        (&conv.failures, &METRICS.conversations_failures),
+    ] {
+        let delta = src.load(Ordering::Relaxed).saturating_sub(dst.get());
+        if delta > 0 {
+            dst.inc_by(delta);
         }
-        PROVIDER_LABEL_EMAIL => {
-            EMAIL_ERROR.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// True when the `Accept` header asks for the Prometheus/OpenMetrics text format
+/// (and not JSON); anything else keeps the legacy JSON snapshot.
+pub fn wants_text(accept: Option<&str>) -> bool {
+    let Some(accept) = accept else {
+        return false;
+    };
+    let accept = accept.to_ascii_lowercase();
+    let mut text = false;
+    for part in accept.split(',').map(|p| p.trim()) {
+        if part.starts_with("application/json") {
+            return false;
         }
-        _ => {}
+        text |= part.starts_with("text/plain") || part.starts_with("application/openmetrics-text");
+    }
+    text
+}
+
+/// Prometheus text exposition (format 0.0.4) of the whole registry.
+pub fn render_text() -> String {
+    sync_external();
+    latency(); // register the histograms even before their first observation
+    let mut buf = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&METRICS.registry.gather(), &mut buf) {
+        tracing::warn!(target="server", event="metrics_encode_error", error=%e, "metrics encoding failed");
+    }
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+fn provider_snapshot(label: &str) -> ProviderSnapshot {
+    let m = &*METRICS;
+    ProviderSnapshot {
+        attempts: m.provider_attempts.with_label_values(&[label]).get(),
+        success: m
+            .provider_results
+            .with_label_values(&[label, OUTCOME_SUCCESS])
+            .get(),
+        rate_limited: m
+            .provider_results
+            .with_label_values(&[label, OUTCOME_RATE_LIMITED])
+            .get(),
+        error: m
+            .provider_results
+            .with_label_values(&[label, OUTCOME_ERROR])
+            .get(),
+        breaker_transitions: m
+            .provider_breaker_transitions
+            .with_label_values(&[label])
+            .get(),
     }
 }
 
 pub fn snapshot() -> MetricsSnapshot {
+    sync_external();
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis();
-    // Feature 009: Load conversation metrics from messaging-core
-    let conv_metrics = messaging_core::conversations::metrics::metrics();
+    let m = &*METRICS;
+    let sms = provider_snapshot(PROVIDER_LABEL_SMS_MMS);
+    let email = provider_snapshot(PROVIDER_LABEL_EMAIL);
+    let providers: BTreeMap<String, ProviderSnapshot> = m
+        .providers
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|label| (label.clone(), provider_snapshot(label)))
+        .collect();
+    let inbound = |status: &str| {
+        m.queue_inbound_events
+            .with_label_values(&[status])
+            .get()
+            .max(0) as u64
+    };
+    let by_encoding = |c: &IntCounterVec| -> u64 {
+        SMS_ENCODINGS
+            .iter()
+            .map(|e| c.with_label_values(&[e]).get())
+            .sum()
+    };
+    let secs_to_ms = |g: &Gauge| (g.get().max(0.0) * 1000.0) as u64;
     MetricsSnapshot {
         ts_unix_ms: now,
-        rate_limited: TOTAL_RATE_LIMITED.load(Ordering::Relaxed),
-        breaker_open: TOTAL_BREAKER_OPEN.load(Ordering::Relaxed),
-        dispatch_attempts: DISPATCH_ATTEMPTS.load(Ordering::Relaxed),
-        dispatch_success: DISPATCH_SUCCESS.load(Ordering::Relaxed),
-        dispatch_rate_limited: DISPATCH_RATE_LIMITED.load(Ordering::Relaxed),
-        dispatch_error: DISPATCH_ERROR.load(Ordering::Relaxed),
-        provider_sms_mms_attempts: SMS_MMS_ATTEMPTS.load(Ordering::Relaxed),
-        provider_sms_mms_success: SMS_MMS_SUCCESS.load(Ordering::Relaxed),
-        provider_sms_mms_rate_limited: SMS_MMS_RATE_LIMITED.load(Ordering::Relaxed),
-        provider_sms_mms_error: SMS_MMS_ERROR.load(Ordering::Relaxed),
-        provider_email_attempts: EMAIL_ATTEMPTS.load(Ordering::Relaxed),
-        provider_email_success: EMAIL_SUCCESS.load(Ordering::Relaxed),
-        provider_email_rate_limited: EMAIL_RATE_LIMITED.load(Ordering::Relaxed),
-        provider_email_error: EMAIL_ERROR.load(Ordering::Relaxed),
-        breaker_transitions: BREAKER_TRANSITIONS.load(Ordering::Relaxed),
-        provider_sms_mms_breaker_transitions: SMS_MMS_BREAKER_TRANSITIONS.load(Ordering::Relaxed),
-        provider_email_breaker_transitions: EMAIL_BREAKER_TRANSITIONS.load(Ordering::Relaxed),
-        worker_claimed: WORKER_CLAIMED.load(Ordering::Relaxed),
-        worker_processed: WORKER_PROCESSED.load(Ordering::Relaxed),
-        worker_error: WORKER_ERROR.load(Ordering::Relaxed),
-        worker_dead_letter: WORKER_DEAD_LETTER.load(Ordering::Relaxed),
+        rate_limited: m.rate_limited.get(),
+        breaker_open: m.breaker_open.get(),
+        dispatch_attempts: m.dispatch_attempts.get(),
+        dispatch_success: m
+            .dispatch_results
+            .with_label_values(&[OUTCOME_SUCCESS])
+            .get(),
+        dispatch_rate_limited: m
+            .dispatch_results
+            .with_label_values(&[OUTCOME_RATE_LIMITED])
+            .get(),
+        dispatch_error: m.dispatch_results.with_label_values(&[OUTCOME_ERROR]).get(),
+        provider_sms_mms_attempts: sms.attempts,
+        provider_sms_mms_success: sms.success,
+        provider_sms_mms_rate_limited: sms.rate_limited,
+        provider_sms_mms_error: sms.error,
+        provider_email_attempts: email.attempts,
+        provider_email_success: email.success,
+        provider_email_rate_limited: email.rate_limited,
+        provider_email_error: email.error,
+        breaker_transitions: m.breaker_transitions.get(),
+        provider_sms_mms_breaker_transitions: sms.breaker_transitions,
+        provider_email_breaker_transitions: email.breaker_transitions,
+        worker_claimed: m.worker_claimed.get(),
+        worker_processed: m.worker_processed.get(),
+        worker_error: m.worker_errors.get(),
+        worker_dead_letter: m.worker_dead_letters.get(),
         worker_latency_avg_us: {
-            let total = WORKER_LATENCY_TOTAL_US.load(Ordering::Relaxed);
-            let processed = WORKER_PROCESSED.load(Ordering::Relaxed).max(1); // avoid div by zero
-            total / processed
+            let count = m.worker_processing_seconds.get_sample_count().max(1); // avoid div by zero
+            (m.worker_processing_seconds.get_sample_sum() * 1_000_000.0 / count as f64) as u64
         },
         worker_latency_max_us: WORKER_LATENCY_MAX_US.load(Ordering::Relaxed),
-        invalid_routing: INVALID_ROUTING.load(Ordering::Relaxed),
-        conversations_created: conv_metrics.created.load(Ordering::Relaxed),
-        conversations_reused: conv_metrics.reused.load(Ordering::Relaxed),
-        conversations_failures: conv_metrics.failures.load(Ordering::Relaxed),
-    }
-}
-
-pub fn record_worker_claimed(n: u64) {
-    WORKER_CLAIMED.fetch_add(n, Ordering::Relaxed);
-}
-
-pub fn record_worker_processed(latency_us: u64) {
-    WORKER_PROCESSED.fetch_add(1, Ordering::Relaxed);
-    WORKER_LATENCY_TOTAL_US.fetch_add(latency_us, Ordering::Relaxed);
-    // update max
-    let mut current = WORKER_LATENCY_MAX_US.load(Ordering::Relaxed);
-    while latency_us > current
-        && WORKER_LATENCY_MAX_US
-            .compare_exchange(current, latency_us, Ordering::Relaxed, Ordering::Relaxed)
-            .is_err()
-    {
-        current = WORKER_LATENCY_MAX_US.load(Ordering::Relaxed);
+        invalid_routing: m.invalid_routing.get(),
+        conversations_created: m.conversations_created.get(),
+        conversations_reused: m.conversations_reused.get(),
+        conversations_failures: m.conversations_failures.get(),
+        queue_inbound_pending: inbound("pending"),
+        queue_inbound_processing: inbound("processing"),
+        queue_inbound_done: inbound("done"),
+        queue_inbound_dead: inbound("dead"),
+        queue_inbound_oldest_pending_age_ms: secs_to_ms(
+            &m.queue_inbound_oldest_pending_age_seconds,
+        ),
+        queue_inbound_lag_ms: secs_to_ms(&m.queue_inbound_lag_seconds),
+        queue_outbound_depth: m.queue_outbound_depth.get().max(0) as u64,
+        queue_outbound_capacity: m.queue_outbound_capacity.get().max(0) as u64,
+        queue_rejected: m.queue_rejected.get(),
+        sms_messages: by_encoding(&m.sms_messages),
+        sms_segments: by_encoding(&m.sms_segments),
+        providers,
     }
 }
-
-pub fn record_worker_error() {
-    WORKER_ERROR.fetch_add(1, Ordering::Relaxed);
-}
-
-pub fn record_worker_dead_letter() {
-    WORKER_DEAD_LETTER.fetch_add(1, Ordering::Relaxed);
-}
-
-// --- Feature 008 additional metrics (Phase 2 placeholder impl) ---
-static INVALID_ROUTING: AtomicU64 = AtomicU64::new(0);
-
-pub fn record_invalid_routing() {
-    INVALID_ROUTING.fetch_add(1, Ordering::Relaxed);
-}
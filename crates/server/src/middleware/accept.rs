@@ -18,9 +18,14 @@ fn accepts_json(value: &HeaderValue) -> bool {
     }
 }
 
+/// Endpoints that negotiate their own representation (`/metrics` also serves Prometheus text).
+const NEGOTIATED_PATHS: &[&str] = &["/metrics"];
+
 /// Enforce JSON responses for GET-like endpoints when Accept is specified.
 pub async fn enforce_json_accept(req: Request<Body>, next: Next) -> Response {
-    if matches!(req.method().as_str(), "GET" | "HEAD") {
+    if matches!(req.method().as_str(), "GET" | "HEAD")
+        && !NEGOTIATED_PATHS.contains(&req.uri().path())
+    {
         if let Some(v) = req.headers().get(ACCEPT) {
             if !accepts_json(v) {
                 let (status, body) = errors::not_acceptable();
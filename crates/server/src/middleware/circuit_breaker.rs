@@ -1,3 +1,15 @@
+//! Circuit breaker shared by the HTTP middleware (one per route group) and each provider.
+//!
+//! [`BreakerPolicy::window_secs`] picks the trip mode:
+//! - `0`: open after `failure_threshold` consecutive failures;
+//! - otherwise: open once the last `window_secs` hold at least `min_requests` calls and
+//!   `failure_rate_pct` percent or more of them failed.
+//!
+//! Calls slower than `slow_call_ms` count as failures in both modes. When the open period ends,
+//! at most `half_open_max_probes` calls are let through: a successful probe closes the breaker,
+//! a failed one re-opens it for twice as long as before (capped at `max_open_secs`).
+
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -8,66 +20,354 @@ pub enum BreakerState {
     HalfOpen,
 }
 
+impl BreakerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Trip and recovery rules for one breaker (see the module docs).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BreakerPolicy {
+    /// Consecutive failures that open the breaker (when `window_secs == 0`)
+    pub failure_threshold: u32,
+    /// Rolling window length; `0` selects consecutive-failure mode
+    pub window_secs: u64,
+    /// Failure rate (0-100) within the window that opens the breaker
+    pub failure_rate_pct: u32,
+    /// Calls required in the window before the failure rate is evaluated
+    pub min_requests: u32,
+    /// Calls let through while half-open
+    pub half_open_max_probes: u32,
+    /// Calls at least this slow count as failures; `0` disables slow-call detection
+    pub slow_call_ms: u64,
+    /// Open duration after the first trip; doubles with each consecutive re-trip
+    pub open_secs: u64,
+    /// Upper bound for the growing open duration
+    pub max_open_secs: u64,
+}
+
+impl BreakerPolicy {
+    /// Consecutive-failure policy with a fixed open period and a single half-open probe.
+    pub fn consecutive(failure_threshold: u32, open_secs: u64) -> Self {
+        Self {
+            failure_threshold,
+            window_secs: 0,
+            failure_rate_pct: 50,
+            min_requests: 1,
+            half_open_max_probes: 1,
+            slow_call_ms: 0,
+            open_secs,
+            max_open_secs: open_secs,
+        }
+    }
+
+    /// Open duration for the `trips`-th consecutive trip (1-based).
+    pub fn open_duration(&self, trips: u32) -> Duration {
+        let factor = 1u64 << trips.saturating_sub(1).min(32);
+        let secs = self
+            .open_secs
+            .saturating_mul(factor)
+            .min(self.max_open_secs.max(self.open_secs));
+        Duration::from_secs(secs)
+    }
+
+    fn is_slow(&self, elapsed: Duration) -> bool {
+        self.slow_call_ms > 0 && elapsed >= Duration::from_millis(self.slow_call_ms)
+    }
+}
+
 #[derive(Clone)]
 pub struct CircuitBreaker {
-    pub failure_threshold: u32,
-    pub recovery_timeout: Duration,
     inner: Arc<Mutex<Inner>>,
 }
 
 #[derive(Debug)]
 struct Inner {
-    failures: u32,
+    policy: BreakerPolicy,
     state: BreakerState,
+    /// When `state` last changed
+    since: Instant,
+    /// Pinned by an operator: outcomes and the open period no longer move the state
+    forced: bool,
+    /// Consecutive-failure mode counter
+    consecutive: u32,
+    /// Rolling-window mode: (finished at, failed)
+    window: VecDeque<(Instant, bool)>,
     opened_at: Option<Instant>,
+    open_for: Duration,
+    /// Trips since the breaker last closed normally; drives open-duration growth
+    trips: u32,
+    probes_in_flight: u32,
+}
+
+impl Inner {
+    fn set_state(&mut self, state: BreakerState) {
+        if self.state != state {
+            self.state = state;
+            self.since = Instant::now();
+        }
+    }
+
+    fn prune(&mut self, window: Duration) {
+        let now = Instant::now();
+        while let Some((at, _)) = self.window.front() {
+            if now.duration_since(*at) <= window {
+                break;
+            }
+            self.window.pop_front();
+        }
+    }
+
+    fn clear_counts(&mut self) {
+        self.consecutive = 0;
+        self.window.clear();
+        self.probes_in_flight = 0;
+    }
+}
+
+/// Point-in-time view of a breaker for the admin API.
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerSnapshot {
+    /// As the next request would see it (see [`CircuitBreaker::peek`])
+    pub state: BreakerState,
+    /// Consecutive failures, or failures within the window in rolling-window mode
+    pub failures: u32,
+    /// Calls within the window (rolling-window mode; `0` otherwise)
+    pub window_calls: u32,
+    pub forced: bool,
+    pub in_state_for: Duration,
+    pub trips: u32,
+    /// Length of the current (or last) open period
+    pub open_for: Duration,
 }
 
 impl CircuitBreaker {
+    /// Consecutive-failure breaker; see [`BreakerPolicy::consecutive`].
     pub fn new(failure_threshold: u32, recovery_timeout_secs: u64) -> Self {
-        Self {
+        Self::with_policy(BreakerPolicy::consecutive(
             failure_threshold,
-            recovery_timeout: Duration::from_secs(recovery_timeout_secs),
+            recovery_timeout_secs,
+        ))
+    }
+
+    pub fn with_policy(policy: BreakerPolicy) -> Self {
+        Self {
             inner: Arc::new(Mutex::new(Inner {
-                failures: 0,
+                policy,
                 state: BreakerState::Closed,
+                since: Instant::now(),
+                forced: false,
+                consecutive: 0,
+                window: VecDeque::new(),
                 opened_at: None,
+                open_for: Duration::from_secs(policy.open_secs),
+                trips: 0,
+                probes_in_flight: 0,
             })),
         }
     }
 
+    pub fn policy(&self) -> BreakerPolicy {
+        self.inner.lock().unwrap().policy
+    }
+
+    /// Swap the policy (config reload). State and recorded calls are kept; a new open
+    /// duration applies from the next trip.
+    pub fn set_policy(&self, policy: BreakerPolicy) {
+        self.inner.lock().unwrap().policy = policy;
+    }
+
     pub fn state(&self) -> BreakerState {
         let inner = self.inner.lock().unwrap();
         inner.state
     }
 
+    /// State the next request would see, without transitioning: an `Open` breaker whose
+    /// open period has elapsed reports `HalfOpen` (unless it was forced open).
+    pub fn peek(&self) -> BreakerState {
+        let inner = self.inner.lock().unwrap();
+        Self::peek_inner(&inner)
+    }
+
+    fn peek_inner(inner: &Inner) -> BreakerState {
+        match (inner.state, inner.opened_at) {
+            (BreakerState::Open, Some(opened))
+                if !inner.forced && opened.elapsed() >= inner.open_for =>
+            {
+                BreakerState::HalfOpen
+            }
+            (state, _) => state,
+        }
+    }
+
+    pub fn snapshot(&self) -> BreakerSnapshot {
+        let mut inner = self.inner.lock().unwrap();
+        let (failures, window_calls) = if inner.policy.window_secs == 0 {
+            (inner.consecutive, 0)
+        } else {
+            let window = Duration::from_secs(inner.policy.window_secs);
+            inner.prune(window);
+            let failed = inner.window.iter().filter(|(_, f)| *f).count();
+            (failed as u32, inner.window.len() as u32)
+        };
+        BreakerSnapshot {
+            state: Self::peek_inner(&inner),
+            failures,
+            window_calls,
+            forced: inner.forced,
+            in_state_for: inner.since.elapsed(),
+            trips: inner.trips,
+            open_for: inner.open_for,
+        }
+    }
+
+    /// Time until an open breaker lets probes through (at least one second, for `Retry-After`).
+    pub fn retry_after(&self) -> Duration {
+        let inner = self.inner.lock().unwrap();
+        let remaining = match (inner.state, inner.opened_at) {
+            (BreakerState::Open, Some(opened)) if !inner.forced => {
+                inner.open_for.saturating_sub(opened.elapsed())
+            }
+            _ => inner.open_for,
+        };
+        remaining.max(Duration::from_secs(1))
+    }
+
+    /// Admit or reject a call. `Open` means reject; this includes a half-open breaker whose
+    /// probe slots are all taken. An admitted call must end in `record_*` or `release`.
     pub fn before_request(&self) -> BreakerState {
         let mut inner = self.inner.lock().unwrap();
-        match inner.state {
-            BreakerState::Open => {
-                if let Some(opened) = inner.opened_at {
-                    if opened.elapsed() >= self.recovery_timeout {
-                        inner.state = BreakerState::HalfOpen;
-                    }
+        if inner.forced {
+            return inner.state;
+        }
+        if inner.state == BreakerState::Open {
+            match inner.opened_at {
+                Some(opened) if opened.elapsed() >= inner.open_for => {
+                    inner.set_state(BreakerState::HalfOpen);
+                    inner.probes_in_flight = 0;
                 }
-                inner.state
+                _ => return BreakerState::Open,
+            }
+        }
+        if inner.state == BreakerState::HalfOpen {
+            if inner.probes_in_flight >= inner.policy.half_open_max_probes.max(1) {
+                return BreakerState::Open;
             }
-            _ => inner.state,
+            inner.probes_in_flight += 1;
         }
+        inner.state
     }
 
     pub fn record_success(&self) {
-        let mut inner = self.inner.lock().unwrap();
-        inner.failures = 0;
-        inner.state = BreakerState::Closed;
-        inner.opened_at = None;
+        self.record(false);
     }
 
     pub fn record_failure(&self) {
+        self.record(true);
+    }
+
+    /// Record a finished call; successes slower than `slow_call_ms` count as failures.
+    pub fn record_call(&self, success: bool, elapsed: Duration) {
+        let slow = self.inner.lock().unwrap().policy.is_slow(elapsed);
+        self.record(!success || slow);
+    }
+
+    /// An admitted call ended without a verdict (e.g. rate limited): free its probe slot.
+    pub fn release(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == BreakerState::HalfOpen {
+            inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+        }
+    }
+
+    fn record(&self, failed: bool) {
         let mut inner = self.inner.lock().unwrap();
-        inner.failures += 1;
-        if inner.failures >= self.failure_threshold {
-            inner.state = BreakerState::Open;
-            inner.opened_at = Some(Instant::now());
+        if inner.forced {
+            return;
         }
+        match inner.state {
+            BreakerState::HalfOpen => {
+                inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+                if failed {
+                    Self::trip(&mut inner);
+                } else {
+                    inner.clear_counts();
+                    inner.trips = 0;
+                    inner.opened_at = None;
+                    inner.set_state(BreakerState::Closed);
+                }
+            }
+            BreakerState::Closed => {
+                if Self::should_trip(&mut inner, failed) {
+                    Self::trip(&mut inner);
+                }
+            }
+            // Late results from calls admitted before the trip
+            BreakerState::Open => {}
+        }
+    }
+
+    fn should_trip(inner: &mut Inner, failed: bool) -> bool {
+        let p = inner.policy;
+        if p.window_secs == 0 {
+            inner.consecutive = if failed { inner.consecutive + 1 } else { 0 };
+            return inner.consecutive >= p.failure_threshold.max(1);
+        }
+        inner.window.push_back((Instant::now(), failed));
+        inner.prune(Duration::from_secs(p.window_secs));
+        let calls = inner.window.len() as u64;
+        let failures = inner.window.iter().filter(|(_, f)| *f).count() as u64;
+        failed
+            && calls >= p.min_requests.max(1) as u64
+            && failures * 100 >= p.failure_rate_pct as u64 * calls
+    }
+
+    fn trip(inner: &mut Inner) {
+        inner.trips = inner.trips.saturating_add(1);
+        inner.open_for = inner.policy.open_duration(inner.trips);
+        inner.opened_at = Some(Instant::now());
+        inner.clear_counts();
+        inner.set_state(BreakerState::Open);
+    }
+
+    /// Hold the breaker open until `force_close` or `reset` (planned provider maintenance).
+    /// Returns the state it had before.
+    pub fn force_open(&self) -> BreakerState {
+        let mut inner = self.inner.lock().unwrap();
+        let before = Self::peek_inner(&inner);
+        inner.set_state(BreakerState::Open);
+        inner.opened_at = Some(Instant::now());
+        inner.probes_in_flight = 0;
+        inner.forced = true;
+        before
+    }
+
+    /// Hold the breaker closed, ignoring failures, until `reset`.
+    pub fn force_close(&self) -> BreakerState {
+        let mut inner = self.inner.lock().unwrap();
+        let before = Self::peek_inner(&inner);
+        inner.set_state(BreakerState::Closed);
+        inner.opened_at = None;
+        inner.clear_counts();
+        inner.forced = true;
+        before
+    }
+
+    /// Back to automatic operation: closed, no recorded failures or trips.
+    pub fn reset(&self) -> BreakerState {
+        let mut inner = self.inner.lock().unwrap();
+        let before = Self::peek_inner(&inner);
+        inner.set_state(BreakerState::Closed);
+        inner.opened_at = None;
+        inner.clear_counts();
+        inner.trips = 0;
+        inner.open_for = Duration::from_secs(inner.policy.open_secs);
+        inner.forced = false;
+        before
     }
 }
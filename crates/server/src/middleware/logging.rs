@@ -1,6 +1,8 @@
-use axum::{body::Body, http::Request, middleware::Next, response::Response};
+use axum::{body::Body, extract::MatchedPath, http::Request, middleware::Next, response::Response};
 use std::time::Instant;
-use uuid::Uuid;
+use tracing::Instrument;
+
+use crate::correlation::CorrelationId;
 
 /// Public header constant for correlation IDs
 pub const HDR_REQUEST_ID: &str = "x-request-id";
@@ -32,6 +34,12 @@ fn client_ip(req: &Request<Body>) -> String {
 pub async fn log_requests(mut req: Request<Body>, next: Next) -> Response {
     let method = req.method().clone();
     let path = req.uri().path().to_string();
+    // Route template for the latency histogram; raw paths would explode label cardinality
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
     let ip = client_ip(&req);
     let start = Instant::now();
 
@@ -40,9 +48,8 @@ pub async fn log_requests(mut req: Request<Body>, next: Next) -> Response {
         .headers()
         .get(HDR_REQUEST_ID)
         .and_then(|v| v.to_str().ok())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
+        .and_then(CorrelationId::parse)
+        .unwrap_or_else(CorrelationId::generate);
 
     // Basic redaction sample: capture header names but not values for sensitive keys
     const SENSITIVE: [&str; 3] = ["authorization", "x-api-key", "cookie"];
@@ -59,10 +66,23 @@ pub async fn log_requests(mut req: Request<Body>, next: Next) -> Response {
     // Attach correlation id to request extensions for downstream use
     req.extensions_mut().insert(correlation_id.clone());
 
-    let mut resp = next.run(req).await;
+    // Request span; continues the caller's trace when a W3C `traceparent` header is present
+    let span = tracing::info_span!(
+        "http_request",
+        http.method = %method,
+        http.route = %route,
+        correlation_id = %correlation_id
+    );
+    crate::telemetry::set_parent_from_headers(&span, req.headers());
+
+    // Handlers, error bodies and enqueued events read the ID via `CorrelationId::current()`
+    let mut resp = correlation_id
+        .clone()
+        .scope(next.run(req).instrument(span))
+        .await;
 
     // Propagate correlation id back to caller
-    if let Ok(hv) = axum::http::HeaderValue::from_str(&correlation_id) {
+    if let Ok(hv) = axum::http::HeaderValue::from_str(correlation_id.as_str()) {
         resp.headers_mut().insert(
             axum::http::header::HeaderName::from_static(HDR_REQUEST_ID),
             hv,
@@ -70,7 +90,9 @@ pub async fn log_requests(mut req: Request<Body>, next: Next) -> Response {
     }
 
     let status = resp.status().as_u16();
-    let took_us = start.elapsed().as_micros();
+    let elapsed = start.elapsed();
+    let took_us = elapsed.as_micros();
+    crate::metrics::observe_http_request(method.as_str(), &route, status, elapsed);
 
     tracing::info!(
         target = "server",
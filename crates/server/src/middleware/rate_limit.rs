@@ -1,11 +1,12 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 #[derive(Clone)]
 pub struct RateLimiter {
-    per_ip_limit_per_min: u32,
-    per_sender_limit_per_min: u32,
+    per_ip_limit_per_min: Arc<AtomicU32>,
+    per_sender_limit_per_min: Arc<AtomicU32>,
     inner: Arc<Mutex<Inner>>,
 }
 
@@ -22,8 +23,8 @@ struct Inner {
 impl RateLimiter {
     pub fn new(per_ip: u32, per_sender: u32) -> Self {
         Self {
-            per_ip_limit_per_min: per_ip,
-            per_sender_limit_per_min: per_sender,
+            per_ip_limit_per_min: Arc::new(AtomicU32::new(per_ip)),
+            per_sender_limit_per_min: Arc::new(AtomicU32::new(per_sender)),
             inner: Arc::new(Mutex::new(Inner {
                 ip: HashMap::new(),
                 sender: HashMap::new(),
@@ -31,6 +32,13 @@ impl RateLimiter {
         }
     }
 
+    /// Change the limits (config reload); counts in the current window are kept.
+    pub fn set_limits(&self, per_ip: u32, per_sender: u32) {
+        self.per_ip_limit_per_min.store(per_ip, Ordering::Relaxed);
+        self.per_sender_limit_per_min
+            .store(per_sender, Ordering::Relaxed);
+    }
+
     fn check_and_inc(counter: &mut HashMap<String, Counter>, key: &str, limit: u32) -> bool {
         let now = Instant::now();
         let entry = counter.entry(key.to_string()).or_insert(Counter {
@@ -51,11 +59,13 @@ impl RateLimiter {
 
     pub fn allow_ip(&self, ip: &str) -> bool {
         let mut inner = self.inner.lock().unwrap();
-        Self::check_and_inc(&mut inner.ip, ip, self.per_ip_limit_per_min)
+        let limit = self.per_ip_limit_per_min.load(Ordering::Relaxed);
+        Self::check_and_inc(&mut inner.ip, ip, limit)
     }
 
     pub fn allow_sender(&self, sender: &str) -> bool {
         let mut inner = self.inner.lock().unwrap();
-        Self::check_and_inc(&mut inner.sender, sender, self.per_sender_limit_per_min)
+        let limit = self.per_sender_limit_per_min.load(Ordering::Relaxed);
+        Self::check_and_inc(&mut inner.sender, sender, limit)
     }
 }
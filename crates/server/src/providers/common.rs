@@ -1,7 +1,16 @@
 //! Shared provider helpers (Feature 008)
 
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use crate::config::ApiConfig;
 use crate::providers::mock::Outcome;
+use crate::providers::registry::DispatchResult;
+use crate::providers::scenario::ScenarioRunner;
+
+/// Logical providers with their own mock percentages and seed (`provider_sms_*`,
+/// `provider_email_*`).
+pub const MOCK_PROVIDERS: &[&str] = &["sms-mms", "email"];
 
 fn clamp(p: i32) -> u32 {
     if p < 0 {
@@ -13,42 +22,14 @@ fn clamp(p: i32) -> u32 {
     }
 }
 
-/// Choose an outcome for a given logical provider based on per-provider overrides,
-/// falling back to global API_* values. Returns (Outcome, debug_roll) to support deterministic tests (US3).
-pub fn pick_outcome_for_provider(provider: &str, cfg: &ApiConfig) -> (Outcome, u32) {
-    // Resolve effective pct/seed values based on provider-specific overrides
-    let (timeout_pct, error_pct, ratelimit_pct, seed) = match provider {
-        "sms-mms" => (
-            cfg.provider_sms_timeout_pct
-                .unwrap_or(cfg.provider_timeout_pct),
-            cfg.provider_sms_error_pct.unwrap_or(cfg.provider_error_pct),
-            cfg.provider_sms_ratelimit_pct
-                .unwrap_or(cfg.provider_ratelimit_pct),
-            cfg.provider_sms_seed.or(cfg.provider_seed),
-        ),
-        "email" => (
-            cfg.provider_email_timeout_pct
-                .unwrap_or(cfg.provider_timeout_pct),
-            cfg.provider_email_error_pct
-                .unwrap_or(cfg.provider_error_pct),
-            cfg.provider_email_ratelimit_pct
-                .unwrap_or(cfg.provider_ratelimit_pct),
-            cfg.provider_email_seed.or(cfg.provider_seed),
-        ),
-        _ => (
-            cfg.provider_timeout_pct,
-            cfg.provider_error_pct,
-            cfg.provider_ratelimit_pct,
-            cfg.provider_seed,
-        ),
-    };
-
-    // Compute a deterministic roll (0..99) given seed, similar to providers::mock
+/// Map a roll (0..99) onto the provider's effective percentages (per-provider overrides, falling
+/// back to the global API_* values). Success is whatever remains.
+fn outcome_for_roll(provider: &str, cfg: &ApiConfig, roll: u32) -> Outcome {
+    let (timeout_pct, error_pct, ratelimit_pct) = cfg.provider_pcts(provider);
     let timeout = clamp(timeout_pct as i32);
     let error = clamp(error_pct as i32);
     let ratelimit = clamp(ratelimit_pct as i32);
-    let roll = next_roll(provider, seed);
-    let outcome = if roll < timeout {
+    if roll < timeout {
         Outcome::Timeout
     } else if roll < timeout + error {
         Outcome::Error
@@ -56,123 +37,147 @@ pub fn pick_outcome_for_provider(provider: &str, cfg: &ApiConfig) -> (Outcome, u
         Outcome::RateLimited
     } else {
         Outcome::Success
-    };
-    (outcome, roll)
+    }
 }
 
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::OnceLock;
+/// Seedable outcome and latency streams, owned by a mock provider instance (US3). Until it is
+/// seeded, the first draw seeds it from the provider's configured seed, or from entropy when
+/// there is none; [`ProviderRng::reset`] returns it to that state.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderRng {
+    inner: Arc<Mutex<Option<Streams>>>,
+}
 
-const INIT: u64 = 0x9E3779B97F4A7C15;
+#[derive(Debug)]
+struct Streams {
+    outcome: u64,
+    latency: u64,
+}
 
-fn rng_state_for(provider: &str) -> &'static AtomicU64 {
-    match provider {
-        "sms-mms" => {
-            static SMS: OnceLock<AtomicU64> = OnceLock::new();
-            SMS.get_or_init(|| AtomicU64::new(INIT))
-        }
-        "email" => {
-            static EMAIL: OnceLock<AtomicU64> = OnceLock::new();
-            EMAIL.get_or_init(|| AtomicU64::new(INIT))
-        }
-        _ => {
-            static DEFAULT: OnceLock<AtomicU64> = OnceLock::new();
-            DEFAULT.get_or_init(|| AtomicU64::new(INIT))
+impl Streams {
+    fn from_seed(seed: u64) -> Self {
+        Self {
+            outcome: seed,
+            latency: latency_seed(seed),
         }
     }
 }
 
-/// Deterministic pseudo-random roll scoped per provider. When a seed is provided, we set the
-/// provider's RNG state once if it's still at INIT; subsequent calls advance a LCG.
-fn next_roll(provider: &str, seed: Option<u64>) -> u32 {
-    let state = rng_state_for(provider);
-    if let Some(s) = seed {
-        let _ = state.compare_exchange(INIT, s, Ordering::SeqCst, Ordering::SeqCst);
-    } else {
-        let t = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_nanos() as u64)
-            .unwrap_or(0);
-        state.fetch_xor(t.rotate_left(7), Ordering::Relaxed);
-    }
-    let prev = state.load(Ordering::Relaxed);
-    let next = prev.wrapping_mul(6364136223846793005).wrapping_add(1);
-    state.store(next, Ordering::Relaxed);
-    (next % 100) as u32
+/// Seed of a provider's latency stream, kept apart from its outcome stream so that enabling
+/// latency does not change the outcome sequence.
+fn latency_seed(seed: u64) -> u64 {
+    seed.rotate_left(32) ^ 0x9E3779B97F4A7C15
 }
 
-/// Forcefully seed a provider's RNG state (used at startup and in tests for determinism).
-pub fn seed_provider_rng(provider: &str, seed: u64) {
-    let state = rng_state_for(provider);
-    state.store(seed, Ordering::Relaxed);
+fn entropy() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
 }
 
-/// Initialize RNG seeds from ApiConfig at startup for deterministic sequences (US3 T030/T033).
-pub fn init_rng_seeds(cfg: &ApiConfig) {
-    if let Some(s) = cfg.provider_sms_seed.or(cfg.provider_seed) {
-        seed_provider_rng("sms-mms", s);
+impl ProviderRng {
+    pub fn new() -> Self {
+        Self::default()
     }
-    if let Some(s) = cfg.provider_email_seed.or(cfg.provider_seed) {
-        seed_provider_rng("email", s);
+
+    /// Streams starting from `seed`, whatever the configured seed.
+    pub fn seeded(seed: u64) -> Self {
+        let rng = Self::new();
+        rng.seed(seed);
+        rng
+    }
+
+    /// Restart both streams from `seed`.
+    pub fn seed(&self, seed: u64) {
+        *self.inner.lock().unwrap() = Some(Streams::from_seed(seed));
+    }
+
+    /// Forget the current streams; the next draw seeds them again from the config.
+    pub fn reset(&self) {
+        *self.inner.lock().unwrap() = None;
+    }
+
+    /// Advance one stream by an LCG step, seeding first if needed.
+    fn next(&self, seed: Option<u64>, stream: impl FnOnce(&mut Streams) -> &mut u64) -> u64 {
+        let mut guard = self.inner.lock().unwrap();
+        let streams = guard.get_or_insert_with(|| Streams::from_seed(seed.unwrap_or_else(entropy)));
+        let state = stream(streams);
+        *state = lcg_step(*state);
+        *state
+    }
+
+    /// Choose an outcome for a logical provider. Returns (Outcome, debug_roll) to support
+    /// deterministic tests (US3).
+    pub fn pick_outcome(&self, provider: &str, cfg: &ApiConfig) -> (Outcome, u32) {
+        let roll = (self.next(cfg.provider_seed_for(provider), |s| &mut s.outcome) % 100) as u32;
+        (outcome_for_roll(provider, cfg, roll), roll)
+    }
+
+    /// Sample a dispatch latency from the provider's distribution (see `providers::latency`).
+    pub fn sample_latency(&self, provider: &str, cfg: &ApiConfig) -> Duration {
+        let dist = cfg.provider_latency_for(provider);
+        if !dist.is_random() {
+            return dist.sample(|| 0.0);
+        }
+        let seed = cfg.provider_seed_for(provider);
+        dist.sample(|| {
+            let next = self.next(seed, |s| &mut s.latency);
+            (next >> 11) as f64 / (1u64 << 53) as f64
+        })
     }
 }
 
-/// Pure LCG step used for offline prediction without touching global state.
+/// Dispatch for the mock providers: the active scenario step decides the outcome when it sets
+/// one, else the percentages do. The latency is sampled, plus any scenario latency; a timeout
+/// takes at least the dispatch timeout.
+pub fn mock_dispatch(
+    provider: &str,
+    scenario: &ScenarioRunner,
+    rng: &ProviderRng,
+    cfg: &ApiConfig,
+) -> DispatchResult {
+    let step = scenario.step(provider, cfg.provider_scenarios.get(provider));
+    let outcome = match step.and_then(|s| s.outcome) {
+        Some(outcome) => outcome,
+        None => rng.pick_outcome(provider, cfg).0,
+    };
+    let mut latency =
+        rng.sample_latency(provider, cfg) + step.map_or(Duration::ZERO, |s| s.latency);
+    if let (Outcome::Timeout, Some(timeout)) = (outcome, cfg.provider_dispatch_timeout()) {
+        latency = latency.max(timeout);
+    }
+    DispatchResult {
+        provider_name: provider.to_string(),
+        outcome,
+        latency,
+    }
+}
+
+/// Pure LCG step.
 fn lcg_step(prev: u64) -> u64 {
     prev.wrapping_mul(6364136223846793005).wrapping_add(1)
 }
 
-/// Predict outcome counts for N rolls from a given seed without mutating global RNG.
+/// Predict outcome counts for N rolls from a given seed, using a fresh RNG (the same draws a
+/// provider seeded with `seed` makes).
 pub fn predict_outcomes_from_seed(
     provider: &str,
     cfg: &ApiConfig,
     seed: u64,
     n: usize,
 ) -> (u32, u32, u32, u32) {
-    let (timeout_pct, error_pct, ratelimit_pct, _) = match provider {
-        "sms-mms" => (
-            cfg.provider_sms_timeout_pct
-                .unwrap_or(cfg.provider_timeout_pct),
-            cfg.provider_sms_error_pct.unwrap_or(cfg.provider_error_pct),
-            cfg.provider_sms_ratelimit_pct
-                .unwrap_or(cfg.provider_ratelimit_pct),
-            cfg.provider_sms_seed.or(cfg.provider_seed),
-        ),
-        "email" => (
-            cfg.provider_email_timeout_pct
-                .unwrap_or(cfg.provider_timeout_pct),
-            cfg.provider_email_error_pct
-                .unwrap_or(cfg.provider_error_pct),
-            cfg.provider_email_ratelimit_pct
-                .unwrap_or(cfg.provider_ratelimit_pct),
-            cfg.provider_email_seed.or(cfg.provider_seed),
-        ),
-        _ => (
-            cfg.provider_timeout_pct,
-            cfg.provider_error_pct,
-            cfg.provider_ratelimit_pct,
-            cfg.provider_seed,
-        ),
-    };
-    let timeout = clamp(timeout_pct as i32);
-    let error = clamp(error_pct as i32);
-    let ratelimit = clamp(ratelimit_pct as i32);
-    let mut state = seed;
+    let rng = ProviderRng::seeded(seed);
     let mut succ = 0;
     let mut rate = 0;
     let mut err = 0;
     let mut to = 0;
     for _ in 0..n {
-        state = lcg_step(state);
-        let roll = (state % 100) as u32;
-        if roll < timeout {
-            to += 1;
-        } else if roll < timeout + error {
-            err += 1;
-        } else if roll < timeout + error + ratelimit {
-            rate += 1;
-        } else {
-            succ += 1;
+        match rng.pick_outcome(provider, cfg).0 {
+            Outcome::Success => succ += 1,
+            Outcome::RateLimited => rate += 1,
+            Outcome::Error => err += 1,
+            Outcome::Timeout => to += 1,
         }
     }
     (succ, rate, err, to)
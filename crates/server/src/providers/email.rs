@@ -1,22 +1,21 @@
 //! Mock Email provider implementation (Feature 008 - US1)
 
 use crate::config::ApiConfig;
-use crate::providers::common::pick_outcome_for_provider;
-use crate::providers::mock::Outcome;
+use crate::providers::common::{mock_dispatch, ProviderRng};
 use crate::providers::registry::{DispatchResult, OutboundMessage, Provider};
+use crate::providers::scenario::ScenarioRunner;
 
-#[derive(Debug, Clone)]
-pub struct EmailMockProvider;
-
-impl Default for EmailMockProvider {
-    fn default() -> Self {
-        Self
-    }
+/// Follows its `provider_scenarios` entry while one is active, else the configured percentages
+/// drawn from its own [`ProviderRng`]; see [`mock_dispatch`].
+#[derive(Debug, Clone, Default)]
+pub struct EmailMockProvider {
+    scenario: ScenarioRunner,
+    rng: ProviderRng,
 }
 
 impl EmailMockProvider {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 }
 
@@ -25,10 +24,10 @@ impl Provider for EmailMockProvider {
         "email"
     }
     fn dispatch(&self, _msg: &OutboundMessage, cfg: &ApiConfig) -> DispatchResult {
-        let (outcome, _roll): (Outcome, u32) = pick_outcome_for_provider(self.name(), cfg);
-        DispatchResult {
-            provider_name: self.name().to_string(),
-            outcome,
-        }
+        mock_dispatch(self.name(), &self.scenario, &self.rng, cfg)
+    }
+    fn reset(&self) {
+        self.scenario.reset();
+        self.rng.reset();
     }
 }
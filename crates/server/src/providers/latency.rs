@@ -0,0 +1,91 @@
+//! Simulated dispatch latency for the mock providers.
+//!
+//! Configured globally as `provider_latency` and per provider under
+//! `provider_latency_overrides.<provider>`:
+//!
+//! ```toml
+//! provider_latency = { distribution = "fixed", ms = 20 }
+//! [provider_latency_overrides.email]
+//! distribution = "log_normal"
+//! p50_ms = 80
+//! p99_ms = 1500
+//! ```
+//!
+//! `log_normal` is fitted to the given median and 99th percentile, so roughly one dispatch in a
+//! hundred takes `p99_ms` or longer.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// z-score of the 99th percentile of the standard normal distribution
+const Z_P99: f64 = 2.326_347_874;
+
+/// Longest latency a distribution may be configured with or produce (10 minutes)
+pub const MAX_LATENCY_MS: u64 = 600_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case", deny_unknown_fields)]
+pub enum LatencyDistribution {
+    /// Every dispatch takes `ms`
+    Fixed { ms: u64 },
+    /// Evenly spread between `min_ms` and `max_ms`
+    Uniform { min_ms: u64, max_ms: u64 },
+    /// Long-tailed: median `p50_ms`, 99th percentile `p99_ms`
+    LogNormal { p50_ms: u64, p99_ms: u64 },
+}
+
+impl Default for LatencyDistribution {
+    fn default() -> Self {
+        Self::Fixed { ms: 0 }
+    }
+}
+
+impl LatencyDistribution {
+    /// Whether sampling needs random numbers (a fixed latency does not touch the RNG).
+    pub fn is_random(&self) -> bool {
+        !matches!(self, Self::Fixed { .. })
+    }
+
+    /// Draw one latency; `unit` yields uniform numbers in `[0, 1)`.
+    pub fn sample(&self, mut unit: impl FnMut() -> f64) -> Duration {
+        let ms = match *self {
+            Self::Fixed { ms } => ms as f64,
+            Self::Uniform { min_ms, max_ms } => {
+                min_ms as f64 + unit() * max_ms.saturating_sub(min_ms) as f64
+            }
+            Self::LogNormal { p50_ms, p99_ms } => {
+                let mu = (p50_ms.max(1) as f64).ln();
+                let sigma = ((p99_ms.max(p50_ms).max(1) as f64).ln() - mu) / Z_P99;
+                // Box-Muller; 1 - u keeps the logarithm's argument in (0, 1]
+                let (u1, u2) = (1.0 - unit(), unit());
+                let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+                (mu + sigma * z).exp()
+            }
+        };
+        // A log-normal tail can overflow to infinity; keep every draw representable
+        Duration::from_secs_f64(ms.max(0.0).min(MAX_LATENCY_MS as f64) / 1000.0)
+    }
+
+    /// Problems with the parameters; empty when valid.
+    pub fn validate(&self) -> Vec<String> {
+        let longest = match *self {
+            Self::Fixed { ms } => ms,
+            Self::Uniform { max_ms, .. } => max_ms,
+            Self::LogNormal { p99_ms, .. } => p99_ms,
+        };
+        if longest > MAX_LATENCY_MS {
+            return vec![format!("latencies must be <= {MAX_LATENCY_MS} ms")];
+        }
+        match *self {
+            Self::Fixed { .. } => Vec::new(),
+            Self::Uniform { min_ms, max_ms } if min_ms > max_ms => {
+                vec!["uniform: min_ms must be <= max_ms".to_string()]
+            }
+            Self::LogNormal { p50_ms, p99_ms } if p50_ms == 0 || p99_ms < p50_ms => {
+                vec!["log_normal: needs 0 < p50_ms <= p99_ms".to_string()]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
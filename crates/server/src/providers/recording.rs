@@ -0,0 +1,216 @@
+//! Record-and-replay providers for reproducing traffic.
+//!
+//! [`RecordingProvider`] wraps any provider and appends each dispatch (the `OutboundMessage` and
+//! the `DispatchResult`) to a JSONL file as a [`DispatchRecord`]. [`ReplayProvider`] reads such a
+//! file and returns the recorded results of one provider in order, so the same messages sent
+//! through the outbound queue get identical outcomes, latencies included.
+//!
+//! The server wires both from `provider_replay_path` (replace every provider with its replay)
+//! and `provider_record_path` (record every provider) at startup.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ApiConfig;
+use crate::providers::mock::Outcome;
+use crate::providers::registry::{DispatchResult, OutboundMessage, Provider, ProviderRegistry};
+
+/// One line of a recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DispatchRecord {
+    /// 1-based position within the recording session
+    pub seq: u64,
+    pub recorded_at: chrono::DateTime<chrono::Utc>,
+    pub provider: String,
+    pub message: OutboundMessage,
+    pub outcome: Outcome,
+    pub latency_ms: u64,
+}
+
+/// Appends [`DispatchRecord`]s to a JSONL file; shared by every provider it records.
+pub struct DispatchRecorder {
+    out: Mutex<LineWriter<File>>,
+    seq: AtomicU64,
+}
+
+impl DispatchRecorder {
+    /// Open `path` for appending, creating it if needed.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Arc<Self>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Arc::new(Self {
+            out: Mutex::new(LineWriter::new(file)),
+            seq: AtomicU64::new(0),
+        }))
+    }
+
+    fn record(&self, msg: &OutboundMessage, result: &DispatchResult) {
+        let record = DispatchRecord {
+            seq: self.seq.fetch_add(1, Ordering::SeqCst) + 1,
+            recorded_at: chrono::Utc::now(),
+            provider: result.provider_name.clone(),
+            message: msg.clone(),
+            outcome: result.outcome,
+            latency_ms: result.latency.as_millis() as u64,
+        };
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(target="server", event="dispatch_record_fail", error=%e, "failed to serialize dispatch record");
+                return;
+            }
+        };
+        if let Err(e) = writeln!(self.out.lock().unwrap(), "{line}") {
+            tracing::warn!(target="server", event="dispatch_record_fail", error=%e, seq=record.seq, "failed to write dispatch record");
+        }
+    }
+}
+
+/// Records every dispatch of the wrapped provider.
+pub struct RecordingProvider {
+    inner: Arc<dyn Provider>,
+    recorder: Arc<DispatchRecorder>,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn Provider>, recorder: Arc<DispatchRecorder>) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl Provider for RecordingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+    fn dispatch(&self, msg: &OutboundMessage, cfg: &ApiConfig) -> DispatchResult {
+        let result = self.inner.dispatch(msg, cfg);
+        self.recorder.record(msg, &result);
+        result
+    }
+    fn reset(&self) {
+        self.inner.reset();
+    }
+}
+
+/// Returns one provider's recorded results in order. A message that differs from the recorded
+/// one is logged (`replay_mismatch`) but still gets the recorded result; once the recording is
+/// used up every dispatch fails (`replay_exhausted`).
+pub struct ReplayProvider {
+    name: String,
+    records: Mutex<VecDeque<DispatchRecord>>,
+}
+
+impl ReplayProvider {
+    /// Replay the records of `name`, in the order given.
+    pub fn new(name: impl Into<String>, records: Vec<DispatchRecord>) -> Self {
+        let name = name.into();
+        let records = records.into_iter().filter(|r| r.provider == name).collect();
+        Self {
+            name,
+            records: Mutex::new(records),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>, name: impl Into<String>) -> std::io::Result<Self> {
+        Ok(Self::new(name, read_records(path)?))
+    }
+
+    /// Records not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+}
+
+impl Provider for ReplayProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn dispatch(&self, msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        let Some(record) = self.records.lock().unwrap().pop_front() else {
+            tracing::warn!(target="server", event="replay_exhausted", provider=%self.name, "no recorded dispatches left; failing dispatch");
+            return DispatchResult {
+                provider_name: self.name.clone(),
+                outcome: Outcome::Error,
+                latency: Duration::ZERO,
+            };
+        };
+        let m = &record.message;
+        if (m.channel, &m.to, &m.from, &m.body) != (msg.channel, &msg.to, &msg.from, &msg.body) {
+            tracing::warn!(target="server", event="replay_mismatch", provider=%self.name, seq=record.seq, recorded_to=%m.to, to=%msg.to, "message differs from the recorded one");
+        }
+        DispatchResult {
+            provider_name: self.name.clone(),
+            outcome: record.outcome,
+            latency: Duration::from_millis(record.latency_ms),
+        }
+    }
+}
+
+/// Read a JSONL recording; blank lines are skipped.
+pub fn read_records(path: impl AsRef<Path>) -> std::io::Result<Vec<DispatchRecord>> {
+    let file = File::open(path)?;
+    let mut records = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str(&line).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("line {}: {e}", i + 1),
+            )
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Apply `provider_replay_path` and `provider_record_path` to a registry. Providers serving
+/// several channels stay shared.
+pub(crate) fn wrap_registry(
+    registry: ProviderRegistry,
+    api: &ApiConfig,
+) -> Result<ProviderRegistry, String> {
+    let mut registry = registry;
+    if let Some(path) = &api.provider_replay_path {
+        let records =
+            read_records(path).map_err(|e| format!("failed to read replay file {path}: {e}"))?;
+        registry = map_providers(&registry, |p| {
+            let replay = ReplayProvider::new(p.name(), records.clone());
+            tracing::info!(target="server", event="replay_enabled", provider=%p.name(), records=replay.remaining(), path=%path, "replaying recorded dispatches");
+            Arc::new(replay)
+        });
+    }
+    if let Some(path) = &api.provider_record_path {
+        let recorder = DispatchRecorder::create(path)
+            .map_err(|e| format!("failed to open record file {path}: {e}"))?;
+        tracing::info!(target="server", event="record_enabled", path=%path, "recording provider dispatches");
+        registry = map_providers(&registry, |p| {
+            Arc::new(RecordingProvider::new(p.clone(), recorder.clone()))
+        });
+    }
+    Ok(registry)
+}
+
+fn map_providers(
+    registry: &ProviderRegistry,
+    mut f: impl FnMut(&Arc<dyn Provider>) -> Arc<dyn Provider>,
+) -> ProviderRegistry {
+    let mut by_name: HashMap<String, Arc<dyn Provider>> = HashMap::new();
+    let mut out = ProviderRegistry::new();
+    for (channel, provider) in registry.iter() {
+        let mapped = by_name
+            .entry(provider.name().to_string())
+            .or_insert_with(|| f(provider))
+            .clone();
+        out.insert(channel, mapped);
+    }
+    out
+}
@@ -9,12 +9,14 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::config::ApiConfig;
 use crate::providers::mock::Outcome;
 
 /// Channel type supported for outbound messages.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ChannelKind {
     Sms,
     Mms,
@@ -50,7 +52,7 @@ impl std::str::FromStr for ChannelKind {
 /// - `to`, `from`, `body` may be empty strings (validation handled earlier in API layer in future phases).
 /// - `attachments` currently unused by mock providers but retained for parity with future real providers.
 /// - `idempotency_key` propagates idempotency semantics into provider layer for future dedup.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct OutboundMessage {
     pub channel: ChannelKind,
     pub to: String,
@@ -60,15 +62,25 @@ pub struct OutboundMessage {
     pub idempotency_key: Option<String>,
 }
 
+impl OutboundMessage {
+    /// Encoding and segment count of an SMS body; `None` for MMS and email.
+    pub fn sms_segments(&self) -> Option<messaging_core::sms::Segmentation> {
+        (self.channel == ChannelKind::Sms).then(|| messaging_core::sms::segment(&self.body))
+    }
+}
+
 /// Result of a provider dispatch attempt.
 ///
 /// Contract:
 /// - `provider_name` echoes the logical provider (metrics/log correlation key).
 /// - `outcome` drives metrics & breaker state updates.
+/// - `latency` is simulated provider latency (mock scenarios): the dispatcher waits this long
+///   before recording the outcome. Real providers return `Duration::ZERO`.
 #[derive(Debug, Clone)]
 pub struct DispatchResult {
     pub provider_name: String,
     pub outcome: Outcome,
+    pub latency: Duration,
 }
 
 /// Provider abstraction; implementations will perform mock/real dispatch.
@@ -81,6 +93,8 @@ pub struct DispatchResult {
 pub trait Provider: Send + Sync {
     fn name(&self) -> &str;
     fn dispatch(&self, msg: &OutboundMessage, cfg: &ApiConfig) -> DispatchResult;
+    /// Restart simulated behaviour (mock scenarios); called when the mock config changes.
+    fn reset(&self) {}
 }
 
 /// Provider registry mapping channel → provider instance.
@@ -104,6 +118,10 @@ impl ProviderRegistry {
     pub fn is_empty(&self) -> bool {
         self.providers.is_empty()
     }
+    /// Iterate channel → provider pairs (a provider may serve several channels).
+    pub fn iter(&self) -> impl Iterator<Item = (ChannelKind, &Arc<dyn Provider>)> {
+        self.providers.iter().map(|(k, v)| (*k, v))
+    }
 }
 
 impl Clone for ProviderRegistry {
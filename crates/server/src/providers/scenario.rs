@@ -0,0 +1,166 @@
+//! Scripted mock provider behaviour for chaos testing.
+//!
+//! A [`Scenario`] is an ordered list of phases. Each phase lasts `duration_ms` (wall time from
+//! its start) or `messages` dispatches, whichever comes first, and decides every outcome in it:
+//! a fixed `outcome`, a `sequence` cycled in order, or (with neither) the provider's usual
+//! percentages. `latency_ms` is injected into every dispatch of the phase. After the last phase
+//! the scenario starts over when `repeat` is set; otherwise the provider goes back to its
+//! percentages. A phase with neither limit never ends.
+//!
+//! ```toml
+//! [provider_scenarios.sms-mms]
+//! [[provider_scenarios.sms-mms.phases]]
+//! name = "outage"
+//! duration_ms = 30000
+//! outcome = "timeout"
+//! [[provider_scenarios.sms-mms.phases]]
+//! name = "recovered"
+//! outcome = "success"
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::providers::mock::Outcome;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scenario {
+    pub phases: Vec<ScenarioPhase>,
+    /// Start over after the last phase instead of returning to the percentages
+    pub repeat: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioPhase {
+    /// Label for logs
+    pub name: Option<String>,
+    /// Phase length in milliseconds, from its first dispatch
+    pub duration_ms: Option<u64>,
+    /// Phase length in dispatches
+    pub messages: Option<u32>,
+    /// Outcome of every dispatch in the phase
+    pub outcome: Option<Outcome>,
+    /// Outcomes returned in order, cycling; takes precedence over `outcome`
+    pub sequence: Vec<Outcome>,
+    /// Simulated latency added to each dispatch
+    pub latency_ms: u64,
+}
+
+impl Scenario {
+    /// Problems that would make the scenario stall or loop; empty when valid.
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.repeat && self.phases.is_empty() {
+            errors.push("repeat needs at least one phase".to_string());
+        }
+        let last = self.phases.len().saturating_sub(1);
+        for (i, phase) in self.phases.iter().enumerate() {
+            let bounded =
+                phase.duration_ms.is_some_and(|d| d > 0) || phase.messages.is_some_and(|m| m > 0);
+            // A final phase without limits is fine: it simply never ends
+            let open_ended = phase.duration_ms.is_none()
+                && phase.messages.is_none()
+                && i == last
+                && !self.repeat;
+            if !(bounded || open_ended) {
+                errors.push(format!(
+                    "phase {i}: needs duration_ms or messages > 0 (only a final, non-repeating phase may omit both)"
+                ));
+            }
+        }
+        errors
+    }
+}
+
+/// What a scenario decided for one dispatch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScenarioStep {
+    /// Index of the active phase
+    pub phase: usize,
+    /// `None`: use the provider's percentages
+    pub outcome: Option<Outcome>,
+    pub latency: Duration,
+}
+
+/// Progress through a scenario, owned by a mock provider instance.
+#[derive(Debug, Clone, Default)]
+pub struct ScenarioRunner {
+    inner: Arc<Mutex<Progress>>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    phase: usize,
+    /// Set on the phase's first dispatch
+    started: Option<Instant>,
+    dispatched: u32,
+    /// Past the last phase of a non-repeating scenario
+    finished: bool,
+}
+
+impl ScenarioRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start the scenario over from its first phase.
+    pub fn reset(&self) {
+        *self.inner.lock().unwrap() = Progress::default();
+    }
+
+    /// Advance `scenario` by one dispatch. `None` when there is no scenario or it has finished.
+    pub fn step(&self, provider: &str, scenario: Option<&Scenario>) -> Option<ScenarioStep> {
+        let scenario = scenario.filter(|s| !s.phases.is_empty())?;
+        let mut p = self.inner.lock().unwrap();
+        // Bounded by validation, but never spin on an invalid scenario
+        for _ in 0..=scenario.phases.len() {
+            if p.finished {
+                return None;
+            }
+            if p.phase >= scenario.phases.len() {
+                if !scenario.repeat {
+                    p.finished = true;
+                    tracing::info!(target="server", event="mock_scenario_done", mock=true, provider=%provider, "scenario finished; back to percentages");
+                    return None;
+                }
+                p.phase = 0;
+            }
+            let phase = &scenario.phases[p.phase];
+            let started = match p.started {
+                Some(at) => at,
+                None => {
+                    tracing::info!(target="server", event="mock_scenario_phase", mock=true, provider=%provider, phase=p.phase, name=?phase.name, "scenario phase started");
+                    let now = Instant::now();
+                    p.started = Some(now);
+                    p.dispatched = 0;
+                    now
+                }
+            };
+            let timed_out = phase
+                .duration_ms
+                .is_some_and(|d| started.elapsed() >= Duration::from_millis(d));
+            let counted_out = phase.messages.is_some_and(|m| p.dispatched >= m);
+            if timed_out || counted_out {
+                p.phase += 1;
+                p.started = None;
+                continue;
+            }
+            let outcome = if phase.sequence.is_empty() {
+                phase.outcome
+            } else {
+                Some(phase.sequence[p.dispatched as usize % phase.sequence.len()])
+            };
+            p.dispatched += 1;
+            return Some(ScenarioStep {
+                phase: p.phase,
+                outcome,
+                latency: Duration::from_millis(phase.latency_ms),
+            });
+        }
+        None
+    }
+}
@@ -2,22 +2,21 @@
 //! Combines SMS + MMS under single logical provider.
 
 use crate::config::ApiConfig;
-use crate::providers::common::pick_outcome_for_provider;
-use crate::providers::mock::Outcome;
+use crate::providers::common::{mock_dispatch, ProviderRng};
 use crate::providers::registry::{DispatchResult, OutboundMessage, Provider};
+use crate::providers::scenario::ScenarioRunner;
 
-#[derive(Debug, Clone)]
-pub struct SmsMmsMockProvider;
-
-impl Default for SmsMmsMockProvider {
-    fn default() -> Self {
-        Self
-    }
+/// Follows its `provider_scenarios` entry while one is active, else the configured percentages
+/// drawn from its own [`ProviderRng`]; see [`mock_dispatch`].
+#[derive(Debug, Clone, Default)]
+pub struct SmsMmsMockProvider {
+    scenario: ScenarioRunner,
+    rng: ProviderRng,
 }
 
 impl SmsMmsMockProvider {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
 }
 
@@ -25,11 +24,14 @@ impl Provider for SmsMmsMockProvider {
     fn name(&self) -> &str {
         "sms-mms"
     }
-    fn dispatch(&self, _msg: &OutboundMessage, cfg: &ApiConfig) -> DispatchResult {
-        let (outcome, _roll): (Outcome, u32) = pick_outcome_for_provider(self.name(), cfg);
-        DispatchResult {
-            provider_name: self.name().to_string(),
-            outcome,
+    fn dispatch(&self, msg: &OutboundMessage, cfg: &ApiConfig) -> DispatchResult {
+        if let Some(seg) = msg.sms_segments() {
+            tracing::debug!(target="server", event="mock_sms_segments", mock=true, segments=seg.segments, encoding=%seg.encoding.as_str(), "sms body segmented");
         }
+        mock_dispatch(self.name(), &self.scenario, &self.rng, cfg)
+    }
+    fn reset(&self) {
+        self.scenario.reset();
+        self.rng.reset();
     }
 }
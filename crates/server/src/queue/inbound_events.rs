@@ -1,5 +1,6 @@
 use serde::Serialize;
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+use tokio::sync::mpsc::{channel, error::TrySendError, Permit, Receiver, Sender};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct InboundEvent {
@@ -8,6 +9,42 @@ pub struct InboundEvent {
     pub occurred_at: String,
     pub idempotency_key: Option<String>,
     pub source: String, // "api" or "webhook"
+    /// Producer's trace context so the outbound worker continues the request's trace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_context: Option<crate::telemetry::TraceCarrier>,
+    /// `x-request-id` of the request that produced the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<crate::correlation::CorrelationId>,
+}
+
+/// Why an event could not be queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueError {
+    /// No capacity freed up within the wait budget
+    Full,
+    /// The outbound worker has stopped (shutdown)
+    Closed,
+}
+
+impl std::fmt::Display for EnqueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnqueueError::Full => f.write_str("outbound queue is full"),
+            EnqueueError::Closed => f.write_str("outbound queue is closed"),
+        }
+    }
+}
+
+impl std::error::Error for EnqueueError {}
+
+/// A reserved queue position. Sending through it cannot fail, so handlers reserve before
+/// any side effects and only report success once the event is actually queued.
+pub struct QueueSlot<'a>(Permit<'a, InboundEvent>);
+
+impl QueueSlot<'_> {
+    pub fn send(self, event: InboundEvent) {
+        self.0.send(event);
+    }
 }
 
 #[derive(Clone)]
@@ -21,10 +58,33 @@ impl InboundQueue {
         (Self { tx }, rx)
     }
 
-    pub async fn enqueue(&self, event: InboundEvent) -> Result<(), String> {
-        self.tx
-            .send(event)
-            .await
-            .map_err(|e| format!("queue send failed: {e}"))
+    /// Events buffered and not yet picked up by the outbound worker.
+    pub fn depth(&self) -> usize {
+        self.tx.max_capacity() - self.tx.capacity()
+    }
+
+    /// Buffer size the queue was created with.
+    pub fn capacity(&self) -> usize {
+        self.tx.max_capacity()
+    }
+
+    /// Reserve a slot, waiting at most `wait` for capacity (zero means fail fast when full).
+    pub async fn reserve(&self, wait: Duration) -> Result<QueueSlot<'_>, EnqueueError> {
+        match self.tx.try_reserve() {
+            Ok(permit) => return Ok(QueueSlot(permit)),
+            Err(TrySendError::Closed(())) => return Err(EnqueueError::Closed),
+            Err(TrySendError::Full(())) if wait.is_zero() => return Err(EnqueueError::Full),
+            Err(TrySendError::Full(())) => {}
+        }
+        match tokio::time::timeout(wait, self.tx.reserve()).await {
+            Ok(Ok(permit)) => Ok(QueueSlot(permit)),
+            Ok(Err(_)) => Err(EnqueueError::Closed),
+            Err(_) => Err(EnqueueError::Full),
+        }
+    }
+
+    pub async fn enqueue(&self, event: InboundEvent, wait: Duration) -> Result<(), EnqueueError> {
+        self.reserve(wait).await?.send(event);
+        Ok(())
     }
 }
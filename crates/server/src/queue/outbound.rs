@@ -1,157 +1,326 @@
+use std::sync::Arc;
+
 use tokio::sync::mpsc::Receiver;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::{timeout_at, Duration, Instant};
 
+use crate::correlation::CorrelationId;
 use crate::middleware::circuit_breaker::BreakerState;
 use crate::providers::mock::Outcome;
 use crate::providers::registry::{ChannelKind, OutboundMessage};
 use crate::queue::inbound_events::InboundEvent;
-use tracing::info;
-
-/// Run the outbound worker consuming events and simulating provider dispatch.
-pub(crate) async fn run(mut rx: Receiver<InboundEvent>, state: crate::AppState) {
-    while let Some(evt) = rx.recv().await {
-        // Only process outbound api events; skip others for now
-        let is_outbound = matches!(
-            evt.event_name.as_str(),
-            "api.messages.sms" | "api.messages.email"
+use crate::shutdown::ShutdownSignal;
+use tracing::{info, warn, Instrument};
+
+/// Result of the outbound worker's shutdown drain.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct OutboundDrain {
+    pub drained: u64,
+    pub abandoned: u64,
+    pub timed_out: bool,
+}
+
+/// Run the outbound worker consuming events and simulating provider dispatch, with up to
+/// `outbound_concurrency` dispatches in flight so one slow call does not hold up the queue.
+/// On shutdown the queue is closed; buffered and in-flight events get until `drain_timeout`,
+/// after which the remaining dispatches are aborted.
+pub(crate) async fn run(
+    mut rx: Receiver<InboundEvent>,
+    state: crate::AppState,
+    mut shutdown: ShutdownSignal,
+    drain_timeout: Duration,
+) -> OutboundDrain {
+    let limit = Arc::new(Semaphore::new(
+        state.api().outbound_concurrency.max(1) as usize
+    ));
+    let mut in_flight = JoinSet::new();
+    loop {
+        reap(&mut in_flight);
+        let permit = tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            permit = limit.clone().acquire_owned() => permit.expect("dispatch semaphore is never closed"),
+        };
+        tokio::select! {
+            biased;
+            _ = shutdown.wait() => break,
+            evt = rx.recv() => match evt {
+                Some(evt) => spawn_dispatch(&mut in_flight, permit, evt, &state),
+                None => {
+                    while let Some(res) = in_flight.join_next().await {
+                        log_panic(res);
+                    }
+                    return OutboundDrain::default();
+                }
+            },
+        }
+    }
+
+    // Stop accepting new events; whatever is buffered or in flight gets until the deadline.
+    rx.close();
+    let deadline = Instant::now() + drain_timeout;
+    let mut report = OutboundDrain::default();
+    info!(
+        target = "server",
+        event = "outbound_drain_start",
+        buffered = rx.len(),
+        in_flight = in_flight.len(),
+        timeout_ms = drain_timeout.as_millis() as u64,
+        "draining outbound queue"
+    );
+    let drain = async {
+        while let Some(evt) = rx.recv().await {
+            let permit = limit
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("dispatch semaphore is never closed");
+            spawn_dispatch(&mut in_flight, permit, evt, &state);
+            report.drained += reap(&mut in_flight);
+        }
+        while let Some(res) = in_flight.join_next().await {
+            log_panic(res);
+            report.drained += 1;
+        }
+    };
+    if timeout_at(deadline, drain).await.is_err() {
+        report.timed_out = true;
+        report.abandoned += in_flight.len() as u64;
+        warn!(
+            target = "server",
+            event = "outbound_abandoned",
+            in_flight = in_flight.len(),
+            "drain deadline elapsed mid-dispatch"
         );
-        if !is_outbound {
-            continue;
+        in_flight.abort_all();
+        while let Ok(evt) = rx.try_recv() {
+            report.abandoned += 1;
+            warn!(target="server", event="outbound_abandoned", event_name=%evt.event_name, message_id=%message_id_of(&evt), "drain deadline elapsed; event not dispatched");
         }
+    }
+    info!(
+        target = "server",
+        event = "outbound_drain_done",
+        drained = report.drained,
+        abandoned = report.abandoned,
+        timed_out = report.timed_out,
+        "outbound queue drained"
+    );
+    report
+}
 
-        // Determine channel kind based on event name
-        let channel = match evt.event_name.as_str() {
-            "api.messages.sms" => {
-                // differentiate SMS vs MMS using payload type field if present
-                let kind = evt
-                    .payload
-                    .get("type")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("sms");
-                if kind.eq_ignore_ascii_case("mms") {
-                    ChannelKind::Mms
-                } else {
-                    ChannelKind::Sms
-                }
-            }
-            "api.messages.email" => ChannelKind::Email,
-            _ => continue,
-        };
+fn spawn_dispatch(
+    in_flight: &mut JoinSet<()>,
+    permit: OwnedSemaphorePermit,
+    evt: InboundEvent,
+    state: &crate::AppState,
+) {
+    let state = state.clone();
+    in_flight.spawn(async move {
+        let _permit = permit;
+        dispatch_event(evt, &state).await;
+    });
+}
 
-        // Provider lookup
-        let provider = match state.provider_registry.get(channel) {
-            Some(p) => p.clone(),
-            None => {
-                crate::metrics::record_invalid_routing();
-                info!(target="server", event="provider_missing", channel=?channel, "no provider registered for channel");
-                continue;
-            }
-        };
+/// Collect finished dispatches without waiting; returns how many there were.
+fn reap(in_flight: &mut JoinSet<()>) -> u64 {
+    let mut finished = 0;
+    while let Some(res) = in_flight.try_join_next() {
+        log_panic(res);
+        finished += 1;
+    }
+    finished
+}
 
-        info!(target="server", event="dispatch_attempt", provider=%provider.name(), channel=%channel.as_str(), event_name=%evt.event_name, "processing outbound event");
-        crate::metrics::record_provider_attempt(provider.name());
-
-        // Use per-provider breaker (fallback to global if not found)
-        // Per-provider breaker lookup
-        let provider_breaker = state
-            .provider_breakers
-            .get(provider.name())
-            .unwrap_or(&state.breaker);
-        if provider_breaker.before_request() == BreakerState::Open {
-            crate::metrics::record_breaker_open();
-            info!(
-                target = "server",
-                event = "dispatch_short_circuit",
-                provider = %provider.name(),
-                breaker_state = "open",
-                "provider breaker open; short-circuiting dispatch"
-            );
-            continue;
+fn log_panic(res: Result<(), tokio::task::JoinError>) {
+    if let Err(e) = res {
+        if e.is_panic() {
+            warn!(target="server", event="dispatch_panicked", error=%e, "outbound dispatch panicked");
         }
+    }
+}
 
-        crate::metrics::record_dispatch_attempt();
-        // Build outbound message (subset fields used currently)
-        let outbound = OutboundMessage {
-            channel,
-            to: evt
-                .payload
-                .get("to")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            from: evt
-                .payload
-                .get("from")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            body: evt
+fn message_id_of(evt: &InboundEvent) -> String {
+    evt.payload
+        .get("message_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("-")
+        .to_string()
+}
+
+/// Dispatch one event inside a span that continues the enqueuing request's trace and carries
+/// its correlation ID.
+async fn dispatch_event(evt: InboundEvent, state: &crate::AppState) {
+    let span = tracing::info_span!(
+        "dispatch_event",
+        event_name = %evt.event_name,
+        correlation_id = evt.correlation_id.as_ref().map(CorrelationId::as_str)
+    );
+    if let Some(carrier) = &evt.trace_context {
+        crate::telemetry::set_parent_from_carrier(&span, carrier);
+    }
+    let correlation_id = evt.correlation_id.clone();
+    let work = dispatch(evt, state).instrument(span);
+    match correlation_id {
+        Some(id) => id.scope(work).await,
+        None => work.await,
+    }
+}
+
+/// Route a single queued event to its provider and record the outcome.
+async fn dispatch(evt: InboundEvent, state: &crate::AppState) {
+    // Only process outbound api events; skip others for now
+    let is_outbound = matches!(
+        evt.event_name.as_str(),
+        "api.messages.sms" | "api.messages.email"
+    );
+    if !is_outbound {
+        return;
+    }
+
+    // Determine channel kind based on event name
+    let channel = match evt.event_name.as_str() {
+        "api.messages.sms" => {
+            // differentiate SMS vs MMS using payload type field if present
+            let kind = evt
                 .payload
-                .get("body")
+                .get("type")
                 .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            attachments: evt
-                .payload
-                .get("attachments")
-                .and_then(|v| v.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|a| a.as_str().map(|s| s.to_string()))
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default(),
-            idempotency_key: evt.idempotency_key.clone(),
-        };
+                .unwrap_or("sms");
+            if kind.eq_ignore_ascii_case("mms") {
+                ChannelKind::Mms
+            } else {
+                ChannelKind::Sms
+            }
+        }
+        "api.messages.email" => ChannelKind::Email,
+        _ => return,
+    };
 
-        // Tag provider on stored outbound message if id present
-        if let Some(msg_id) = evt.payload.get("message_id").and_then(|v| v.as_str()) {
-            let _ = crate::store::messages::set_outbound_provider(msg_id, provider.name());
+    // Provider lookup
+    let provider = match state.provider_registry.get(channel) {
+        Some(p) => p.clone(),
+        None => {
+            crate::metrics::record_invalid_routing();
+            info!(target="server", event="provider_missing", channel=?channel, "no provider registered for channel");
+            return;
         }
+    };
 
-        // Execute provider dispatch (mock)
-        let result = provider.dispatch(&outbound, &state.api);
-        let outcome = result.outcome;
-        match outcome {
-            Outcome::Success => {
-                crate::metrics::record_dispatch_success();
-                crate::metrics::record_provider_success(provider.name());
-                // Successful attempt may transition breaker (e.g., half-open -> closed)
-                let before = provider_breaker.state();
-                provider_breaker.record_success();
-                let after = provider_breaker.state();
-                if before != after {
-                    crate::metrics::record_breaker_transition();
-                    crate::metrics::record_provider_breaker_transition(provider.name());
-                    info!(target = "server", event = "breaker_transition", provider=%provider.name(), from=?before, to=?after, "circuit breaker state transitioned");
-                }
-                info!(target = "server", event = "dispatch_outcome", provider=%provider.name(), outcome="success", channel=%channel.as_str(), "provider dispatch succeeded");
-            }
-            Outcome::RateLimited => {
-                crate::metrics::record_dispatch_rate_limited();
-                crate::metrics::record_provider_rate_limited(provider.name());
-                // No breaker change on 429
-                info!(target = "server", event = "dispatch_outcome", provider=%provider.name(), outcome="rate_limited", channel=%channel.as_str(), "provider returned 429 rate limit");
+    info!(target="server", event="dispatch_attempt", provider=%provider.name(), channel=%channel.as_str(), event_name=%evt.event_name, "processing outbound event");
+    crate::metrics::record_provider_attempt(provider.name());
+
+    // Per-provider breaker lookup (fallback to the shared provider fallback breaker)
+    let provider_breaker = state.provider_breakers.get_or_fallback(provider.name());
+    if provider_breaker.before_request() == BreakerState::Open {
+        crate::metrics::record_breaker_open();
+        info!(
+            target = "server",
+            event = "dispatch_short_circuit",
+            provider = %provider.name(),
+            breaker_state = "open",
+            "provider breaker open; short-circuiting dispatch"
+        );
+        return;
+    }
+
+    crate::metrics::record_dispatch_attempt();
+    // Build outbound message (subset fields used currently)
+    let outbound = OutboundMessage {
+        channel,
+        to: evt
+            .payload
+            .get("to")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        from: evt
+            .payload
+            .get("from")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        body: evt
+            .payload
+            .get("body")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        attachments: evt
+            .payload
+            .get("attachments")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|a| a.as_str().map(|s| s.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+        idempotency_key: evt.idempotency_key.clone(),
+    };
+
+    // Tag provider on stored outbound message if id present
+    if let Some(msg_id) = evt.payload.get("message_id").and_then(|v| v.as_str()) {
+        let _ = crate::store::messages::set_outbound_provider(msg_id, provider.name());
+    }
+
+    // Execute provider dispatch (mock)
+    let started = std::time::Instant::now();
+    let api = state.api();
+    let result = provider.dispatch(&outbound, &api);
+    // Simulated latency; past the dispatch timeout the call is cut off and counts as a timeout
+    let (outcome, wait) = match api.provider_dispatch_timeout() {
+        Some(timeout) if result.latency >= timeout => (Outcome::Timeout, timeout),
+        _ => (result.outcome, result.latency),
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+    let elapsed = started.elapsed();
+    let outcome_label = match outcome {
+        Outcome::Success => "success",
+        Outcome::RateLimited => "rate_limited",
+        Outcome::Timeout => "timeout",
+        Outcome::Error => "error",
+    };
+    crate::metrics::observe_provider_dispatch(provider.name(), outcome_label, elapsed);
+    match outcome {
+        Outcome::Success => {
+            crate::metrics::record_dispatch_success();
+            crate::metrics::record_provider_success(provider.name());
+            // Successful attempt may transition breaker (e.g., half-open -> closed)
+            let before = provider_breaker.state();
+            provider_breaker.record_call(true, elapsed);
+            let after = provider_breaker.state();
+            if before != after {
+                crate::metrics::record_breaker_transition();
+                crate::metrics::record_provider_breaker_transition(provider.name());
+                info!(target = "server", event = "breaker_transition", provider=%provider.name(), from=?before, to=?after, "circuit breaker state transitioned");
             }
-            Outcome::Error | Outcome::Timeout => {
-                crate::metrics::record_dispatch_error();
-                crate::metrics::record_provider_error(provider.name());
-                // Record failure against provider-specific breaker (fallback may be global)
-                let before = provider_breaker.state();
-                provider_breaker.record_failure();
-                let after = provider_breaker.state();
-                if before != after {
-                    // Global transition counter retained + per-provider counter
-                    crate::metrics::record_breaker_transition();
-                    crate::metrics::record_provider_breaker_transition(provider.name());
-                    info!(target="server", event="breaker_transition", provider=%provider.name(), from=?before, to=?after, "circuit breaker state transitioned");
-                }
-                let label = if matches!(outcome, Outcome::Timeout) {
-                    "timeout"
-                } else {
-                    "error"
-                };
-                info!(target="server", event="dispatch_outcome", provider=%provider.name(), outcome=%label, channel=%channel.as_str(), "provider dispatch failed");
+            info!(target = "server", event = "dispatch_outcome", provider=%provider.name(), outcome="success", channel=%channel.as_str(), "provider dispatch succeeded");
+        }
+        Outcome::RateLimited => {
+            crate::metrics::record_dispatch_rate_limited();
+            crate::metrics::record_provider_rate_limited(provider.name());
+            // No breaker verdict on 429; free a half-open probe slot
+            provider_breaker.release();
+            info!(target = "server", event = "dispatch_outcome", provider=%provider.name(), outcome="rate_limited", channel=%channel.as_str(), "provider returned 429 rate limit");
+        }
+        Outcome::Error | Outcome::Timeout => {
+            crate::metrics::record_dispatch_error();
+            crate::metrics::record_provider_error(provider.name());
+            // Record failure against provider-specific breaker (or the provider fallback)
+            let before = provider_breaker.state();
+            provider_breaker.record_call(false, elapsed);
+            let after = provider_breaker.state();
+            if before != after {
+                // Global transition counter retained + per-provider counter
+                crate::metrics::record_breaker_transition();
+                crate::metrics::record_provider_breaker_transition(provider.name());
+                info!(target="server", event="breaker_transition", provider=%provider.name(), from=?before, to=?after, "circuit breaker state transitioned");
             }
+            info!(target="server", event="dispatch_outcome", provider=%provider.name(), outcome=%outcome_label, channel=%channel.as_str(), "provider dispatch failed");
         }
     }
 }
@@ -0,0 +1,174 @@
+//! Live `ApiConfig` reload, triggered by SIGHUP or `POST /admin/config/reload`.
+//!
+//! A reload re-reads the config file and `API_*` overrides, validates the result, pushes
+//! rate limits and breaker policies into the running components, and swaps the shared
+//! config in one step, so handlers and workers see either the old or the new config. Every
+//! changed key is logged; keys in [`RESTART_REQUIRED_KEYS`] are stored but only take effect
+//! after a restart.
+
+use crate::config::{ApiConfig, RESTART_REQUIRED_KEYS};
+use crate::providers::common::MOCK_PROVIDERS;
+use crate::shutdown::ShutdownSignal;
+use crate::state::breakers::PROVIDER_FALLBACK_BREAKER;
+use crate::types::{ConfigChangeDto, ConfigReloadDto};
+use crate::AppState;
+
+#[derive(Debug)]
+pub(crate) enum ReloadError {
+    /// The config file could not be read or parsed
+    Load(String),
+    /// The new config failed `ApiConfig::validate`
+    Invalid(Vec<String>),
+}
+
+impl ReloadError {
+    pub(crate) fn errors(&self) -> Vec<String> {
+        match self {
+            ReloadError::Load(e) => vec![e.clone()],
+            ReloadError::Invalid(errors) => errors.clone(),
+        }
+    }
+}
+
+/// Reload from the config file (see `ApiConfig::try_load`) and environment, keeping the
+/// per-provider mock overrides the file cannot express.
+pub(crate) fn reload(state: &AppState, trigger: &str) -> Result<ConfigReloadDto, ReloadError> {
+    let loaded = ApiConfig::try_load(state.config_file.as_deref().map(|p| p.as_path()))
+        .map_err(ReloadError::Load);
+    match loaded {
+        Ok(new) => apply_with(state, trigger, false, |old| new.keep_runtime_overrides(old)),
+        Err(e) => {
+            log_rejected(trigger, &e.errors());
+            Err(e)
+        }
+    }
+}
+
+/// Derive a new config from the current one, validate it and apply it. The mock providers
+/// restart when their settings change, or always with `restart_mocks`.
+pub(crate) fn apply_with(
+    state: &AppState,
+    trigger: &str,
+    restart_mocks: bool,
+    f: impl FnOnce(&ApiConfig) -> ApiConfig,
+) -> Result<ConfigReloadDto, ReloadError> {
+    let result: Result<_, ReloadError> = state.api.update(|old| {
+        let new = f(old);
+        new.validate().map_err(ReloadError::Invalid)?;
+        let changes = old.diff(&new);
+        Ok((new, changes))
+    });
+    let (old, changes) = match result {
+        Ok(applied) => applied,
+        Err(e) => {
+            log_rejected(trigger, &e.errors());
+            return Err(e);
+        }
+    };
+    // Pushed after the write lock is released. The live config, not this update's, goes to
+    // the components, so a concurrent update that swapped in later is never overwritten.
+    let live = state.api();
+    if !changes.is_empty() {
+        push_to_components(state, &live);
+    }
+    if restart_mocks || mock_settings(&old) != mock_settings(&live) {
+        restart_mock_providers(state);
+    }
+    let restart_required: Vec<String> = changes
+        .iter()
+        .filter(|(key, _, _)| RESTART_REQUIRED_KEYS.contains(&key.as_str()))
+        .map(|(key, _, _)| key.clone())
+        .collect();
+    for (key, from, to) in &changes {
+        tracing::info!(target="server", event="config_changed", trigger, key=%key, from=%from, to=%to, "config value changed");
+    }
+    tracing::info!(
+        target = "server",
+        event = "config_reload",
+        trigger,
+        changed = changes.len(),
+        "api config reloaded"
+    );
+    if !restart_required.is_empty() {
+        tracing::warn!(target="server", event="config_restart_required", trigger, keys=?restart_required, "reloaded keys only take effect after a restart");
+    }
+    Ok(ConfigReloadDto {
+        trigger: trigger.to_string(),
+        changes: changes
+            .into_iter()
+            .map(|(key, from, to)| ConfigChangeDto { key, from, to })
+            .collect(),
+        restart_required,
+    })
+}
+
+/// Components that copied config values at startup.
+fn push_to_components(state: &AppState, new: &ApiConfig) {
+    state.rate.set_limits(
+        new.rate_limit_per_ip_per_min,
+        new.rate_limit_per_sender_per_min,
+    );
+    for (name, breaker) in state.provider_breakers.iter() {
+        breaker.set_policy(new.breaker_policy(name));
+    }
+    state
+        .provider_breakers
+        .fallback()
+        .set_policy(new.breaker_policy(PROVIDER_FALLBACK_BREAKER));
+    state.http_breakers.reconfigure(new);
+}
+
+/// What the mock providers run from. Any change restarts the seeded outcome sequences and
+/// scenarios.
+fn mock_settings(c: &ApiConfig) -> impl PartialEq + '_ {
+    let providers: Vec<_> = MOCK_PROVIDERS
+        .iter()
+        .map(|p| {
+            (
+                c.provider_pcts(p),
+                c.provider_seed_for(p),
+                c.provider_latency_for(p),
+            )
+        })
+        .collect();
+    (providers, &c.provider_scenarios)
+}
+
+/// Restart every provider's scenario and RNG; seeded providers start their sequence over with
+/// the next dispatch.
+pub(crate) fn restart_mock_providers(state: &AppState) {
+    for (_, provider) in state.provider_registry.iter() {
+        provider.reset();
+    }
+}
+
+fn log_rejected(trigger: &str, errors: &[String]) {
+    tracing::warn!(target="server", event="config_reload_rejected", trigger, errors=?errors, "api config reload rejected; keeping current config");
+}
+
+/// Reload on every SIGHUP until shutdown. The handler is installed before this returns, so a
+/// SIGHUP sent once the server answers requests cannot hit the default (terminating) action.
+#[cfg(unix)]
+pub(crate) fn spawn_sighup_watcher(state: AppState, mut shutdown: ShutdownSignal) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!(target="server", event="sighup_unavailable", error=%e, "cannot listen for SIGHUP; reload via /admin/config/reload only");
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(()) = hangup.recv() => {
+                    let _ = reload(&state, "sighup");
+                }
+                _ = shutdown.wait() => return,
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub(crate) fn spawn_sighup_watcher(_state: AppState, _shutdown: ShutdownSignal) {}
@@ -0,0 +1,46 @@
+use serde::Serialize;
+use tokio::sync::watch;
+
+/// Summary of a coordinated shutdown: what the workers finished vs. left behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ShutdownReport {
+    /// Outbound queue events processed after the shutdown signal
+    pub outbound_drained: u64,
+    /// Outbound queue events dropped because the drain deadline elapsed
+    pub outbound_abandoned: u64,
+    /// Claimed inbound_events returned to `pending` instead of being processed
+    pub inbound_released: u64,
+    /// Claimed inbound_events aborted mid-processing, left for the stale-claim reaper
+    pub inbound_abandoned: u64,
+    /// True when any worker hit the drain deadline
+    pub timed_out: bool,
+}
+
+/// Fires the shutdown signal for background workers. Dropping it also counts as firing.
+pub struct ShutdownTrigger(watch::Sender<bool>);
+
+impl ShutdownTrigger {
+    pub fn fire(&self) {
+        let _ = self.0.send(true);
+    }
+}
+
+/// Cloneable receiver side handed to each background worker.
+#[derive(Clone)]
+pub struct ShutdownSignal(watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once shutdown has been requested (immediately if it already was).
+    pub async fn wait(&mut self) {
+        let _ = self.0.wait_for(|fired| *fired).await;
+    }
+}
+
+pub fn channel() -> (ShutdownTrigger, ShutdownSignal) {
+    let (tx, rx) = watch::channel(false);
+    (ShutdownTrigger(tx), ShutdownSignal(rx))
+}
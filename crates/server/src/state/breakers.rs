@@ -1,28 +1,215 @@
 //! Per-provider circuit breaker storage (Feature 008 - T010)
 //!
 //! Provides a lightweight mapping from provider name to individual `CircuitBreaker` instances.
-//! These will be used in User Story 2 to isolate failures.
+//! These will be used in User Story 2 to isolate failures. [`HttpBreakers`] does the same for
+//! HTTP route groups.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
 
+use crate::config::ApiConfig;
 use crate::middleware::circuit_breaker::CircuitBreaker;
 
-#[derive(Clone, Default)]
+/// Breaker-policy name of [`ProviderBreakers::fallback`].
+pub const PROVIDER_FALLBACK_BREAKER: &str = "provider_fallback";
+
+#[derive(Clone)]
 pub struct ProviderBreakers {
     inner: Arc<HashMap<String, CircuitBreaker>>, // immutable map after construction
+    // Shared by providers without a breaker of their own; never an HTTP route-group breaker
+    fallback: CircuitBreaker,
+}
+
+impl Default for ProviderBreakers {
+    fn default() -> Self {
+        Self::new(HashMap::new())
+    }
 }
 
 impl ProviderBreakers {
     pub fn new(map: HashMap<String, CircuitBreaker>) -> Self {
         Self {
             inner: Arc::new(map),
+            fallback: CircuitBreaker::with_policy(
+                ApiConfig::default().breaker_policy(PROVIDER_FALLBACK_BREAKER),
+            ),
         }
     }
     pub fn get(&self, name: &str) -> Option<&CircuitBreaker> {
         self.inner.get(name)
     }
+    /// Breaker for `name`, or the shared provider fallback when it has none.
+    pub fn get_or_fallback(&self, name: &str) -> &CircuitBreaker {
+        self.inner.get(name).unwrap_or(&self.fallback)
+    }
+    pub fn fallback(&self) -> &CircuitBreaker {
+        &self.fallback
+    }
     pub fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CircuitBreaker)> {
+        self.inner.iter().map(|(name, b)| (name.as_str(), b))
+    }
+}
+
+/// Path prefixes of the HTTP route groups; the first match wins, anything else is `other`.
+pub const HTTP_ROUTE_GROUPS: &[(&str, &str)] = &[
+    ("/api/messages/", "messages"),
+    ("/api/webhooks/", "webhooks"),
+    ("/api/conversations", "conversations"),
+    ("/api/provider/", "provider_mock"),
+    ("/admin/", "admin"),
+];
+
+/// Group for routes outside [`HTTP_ROUTE_GROUPS`] (e.g. `/metrics`, embedder routes).
+pub const HTTP_FALLBACK_GROUP: &str = "other";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StatusSpec {
+    /// `5xx`: any status in the hundred
+    Class(u16),
+    Exact(u16),
+}
+
+impl StatusSpec {
+    fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim().to_ascii_lowercase();
+        match spec.strip_suffix("xx") {
+            Some(d) => match d.parse::<u16>() {
+                Ok(class @ 1..=5) => Some(Self::Class(class)),
+                _ => None,
+            },
+            None => match spec.parse::<u16>() {
+                Ok(code @ 100..=599) => Some(Self::Exact(code)),
+                _ => None,
+            },
+        }
+    }
+
+    fn matches(&self, status: u16) -> bool {
+        match *self {
+            Self::Class(class) => status / 100 == class,
+            Self::Exact(code) => status == code,
+        }
+    }
+}
+
+/// How an HTTP breaker counts a response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HttpVerdict {
+    Success,
+    Failure,
+    /// Neither (e.g. 4xx): the call only frees its half-open probe slot
+    Neutral,
+}
+
+/// One breaker per HTTP route group (`http.<group>` in the admin API), so failures on one
+/// group of routes do not take the rest of the API down.
+#[derive(Clone)]
+pub struct HttpBreakers {
+    inner: Arc<BTreeMap<&'static str, CircuitBreaker>>,
+    classifier: Arc<RwLock<Classifier>>,
+}
+
+#[derive(Debug, Default)]
+struct Classifier {
+    failure: Vec<StatusSpec>,
+    ignored: Vec<u16>,
+}
+
+impl Classifier {
+    fn from_config(api: &ApiConfig) -> Self {
+        let failure = api
+            .http_breaker_failure_statuses
+            .iter()
+            .filter_map(|s| {
+                let parsed = StatusSpec::parse(s);
+                if parsed.is_none() {
+                    tracing::warn!(target="server", value=%s, "Ignoring invalid http_breaker_failure_statuses entry");
+                }
+                parsed
+            })
+            .collect();
+        Self {
+            failure,
+            ignored: api.http_breaker_ignored_statuses.clone(),
+        }
+    }
+}
+
+/// Whether `spec` is a valid `http_breaker_failure_statuses` entry (`5xx` or `503`).
+pub fn is_valid_status_spec(spec: &str) -> bool {
+    StatusSpec::parse(spec).is_some()
+}
+
+impl HttpBreakers {
+    pub fn from_config(api: &ApiConfig) -> Self {
+        let inner = HTTP_ROUTE_GROUPS
+            .iter()
+            .map(|(_, group)| *group)
+            .chain(std::iter::once(HTTP_FALLBACK_GROUP))
+            .map(|group| {
+                let policy = api.breaker_policy(&http_breaker_name(group));
+                (group, CircuitBreaker::with_policy(policy))
+            })
+            .collect();
+        Self {
+            inner: Arc::new(inner),
+            classifier: Arc::new(RwLock::new(Classifier::from_config(api))),
+        }
+    }
+
+    /// Apply reloaded policies and status classification; breaker state is kept.
+    pub fn reconfigure(&self, api: &ApiConfig) {
+        for (group, breaker) in self.inner.iter() {
+            breaker.set_policy(api.breaker_policy(&http_breaker_name(group)));
+        }
+        *self.classifier.write().unwrap() = Classifier::from_config(api);
+    }
+
+    pub fn group_for(path: &str) -> &'static str {
+        HTTP_ROUTE_GROUPS
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix))
+            .map(|(_, group)| *group)
+            .unwrap_or(HTTP_FALLBACK_GROUP)
+    }
+
+    /// Group and breaker guarding `path`.
+    pub fn for_path(&self, path: &str) -> (&'static str, &CircuitBreaker) {
+        let group = Self::group_for(path);
+        (group, &self.inner[group])
+    }
+
+    pub fn get(&self, group: &str) -> Option<&CircuitBreaker> {
+        self.inner.get(group)
+    }
+
+    pub fn fallback(&self) -> &CircuitBreaker {
+        &self.inner[HTTP_FALLBACK_GROUP]
+    }
+
+    /// Groups in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &CircuitBreaker)> {
+        self.inner.iter().map(|(group, b)| (*group, b))
+    }
+
+    pub fn classify(&self, status: u16) -> HttpVerdict {
+        let c = self.classifier.read().unwrap();
+        if c.ignored.contains(&status) {
+            HttpVerdict::Neutral
+        } else if c.failure.iter().any(|s| s.matches(status)) {
+            HttpVerdict::Failure
+        } else if (200..300).contains(&status) {
+            HttpVerdict::Success
+        } else {
+            HttpVerdict::Neutral
+        }
+    }
+}
+
+/// Admin API name of a route group's breaker.
+pub fn http_breaker_name(group: &str) -> String {
+    format!("{}.{group}", crate::api::breakers::HTTP_BREAKER)
 }
@@ -0,0 +1,32 @@
+//! Progress marker for background workers; `/readyz` reports a worker whose heartbeat has
+//! gone stale as not ready.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Default)]
+pub struct Heartbeat {
+    last_unix_ms: Arc<AtomicU64>, // 0 = never beat
+}
+
+fn now_unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        self.last_unix_ms.store(now_unix_ms(), Ordering::Relaxed);
+    }
+
+    /// Time since the last beat; `None` before the first one.
+    pub fn age(&self) -> Option<Duration> {
+        match self.last_unix_ms.load(Ordering::Relaxed) {
+            0 => None,
+            last => Some(Duration::from_millis(now_unix_ms().saturating_sub(last))),
+        }
+    }
+}
@@ -30,4 +30,10 @@ impl IdempotencyStore {
         m.insert(key.to_string(), now);
         true
     }
+
+    /// Drop a key recorded by `seen_or_insert`, e.g. when the request was rejected before
+    /// anything was accepted, so a client retry with the same key is processed normally.
+    pub fn forget(&self, key: &str) {
+        self.inner.lock().unwrap().remove(key);
+    }
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::sync::{OnceLock, RwLock};
 
+use messaging_core::conversations::key::{ChannelKind, KeyNormalization};
+
 use crate::store::messages::{Channel, StoredMessage};
 use crate::types::{ConversationDto, MessageDto};
 
@@ -17,24 +19,18 @@ fn map() -> &'static RwLock<HashMap<String, Conversation>> {
     CELL.get_or_init(|| RwLock::new(HashMap::new()))
 }
 
-fn normalize_addr(channel: &Channel, s: &str) -> String {
-    match channel {
-        Channel::Email => s.to_ascii_lowercase(),
-        Channel::Sms | Channel::Mms => {
-            let mut out = String::new();
-            for c in s.chars() {
-                if (c == '+' && out.is_empty()) || c.is_ascii_digit() {
-                    out.push(c);
-                }
-            }
-            out
-        }
-    }
+fn normalize_addr(channel: &Channel, s: &str, keys: &KeyNormalization) -> String {
+    let kind = match channel {
+        Channel::Email => ChannelKind::Email,
+        Channel::Sms => ChannelKind::Sms,
+        Channel::Mms => ChannelKind::Mms,
+    };
+    keys.normalize(&kind, s)
 }
 
-fn convo_id(channel: &Channel, from: &str, to: &str) -> (String, String) {
-    let nf = normalize_addr(channel, from);
-    let nt = normalize_addr(channel, to);
+fn convo_id(channel: &Channel, from: &str, to: &str, keys: &KeyNormalization) -> (String, String) {
+    let nf = normalize_addr(channel, from, keys);
+    let nt = normalize_addr(channel, to, keys);
     let (a, b) = if nf <= nt { (nf, nt) } else { (nt, nf) };
     let key = format!(
         "{}:{}<->{}",
@@ -50,8 +46,8 @@ fn convo_id(channel: &Channel, from: &str, to: &str) -> (String, String) {
     (id, key)
 }
 
-pub fn on_message_stored(msg: &StoredMessage) {
-    let (id, key) = convo_id(&msg.channel, &msg.from, &msg.to);
+pub fn on_message_stored(msg: &StoredMessage, keys: &KeyNormalization) {
+    let (id, key) = convo_id(&msg.channel, &msg.from, &msg.to, keys);
     let mut w = map().write().unwrap();
     let entry = w.entry(id.clone()).or_insert(Conversation {
         id,
@@ -95,6 +91,7 @@ pub fn list_messages(
     page: u32,
     page_size: u32,
     snippet_len: usize,
+    keys: &KeyNormalization,
 ) -> (Vec<MessageDto>, u64) {
     // For simplicity, derive messages by scanning the message store and selecting matching convo
     let all = crate::store::messages::all();
@@ -102,7 +99,7 @@ pub fn list_messages(
     let mut msgs: Vec<_> = all
         .into_iter()
         .filter(|m| {
-            let (id, _) = convo_id(&m.channel, &m.from, &m.to);
+            let (id, _) = convo_id(&m.channel, &m.from, &m.to, keys);
             id == conv_id
         })
         .collect();
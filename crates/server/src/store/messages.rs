@@ -3,6 +3,8 @@ use std::sync::{OnceLock, RwLock};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use messaging_core::conversations::key::KeyNormalization;
+
 use super::conversations;
 use crate::types::{EmailInbound, ProviderInboundRequest, SmsInbound};
 
@@ -38,7 +40,7 @@ fn store() -> &'static RwLock<Vec<StoredMessage>> {
     CELL.get_or_init(|| RwLock::new(Vec::new()))
 }
 
-pub fn insert_inbound(req: &ProviderInboundRequest) -> String {
+pub fn insert_inbound(req: &ProviderInboundRequest, keys: &KeyNormalization) -> String {
     let (channel, from, to, body, attachments, timestamp) = match req {
         ProviderInboundRequest::Sms(SmsInbound {
             from,
@@ -109,42 +111,46 @@ pub fn insert_inbound(req: &ProviderInboundRequest) -> String {
     w.push(msg);
     // Update conversation index
     if let Some(last) = w.last() {
-        conversations::on_message_stored(last);
+        conversations::on_message_stored(last, keys);
     }
     id
 }
 
 pub fn insert_outbound_sms(
+    keys: &KeyNormalization,
     from: &str,
     to: &str,
     body: &str,
     attachments: &Option<Vec<String>>,
     timestamp: &str,
 ) -> String {
-    insert_outbound(Channel::Sms, from, to, body, attachments, timestamp)
+    insert_outbound(keys, Channel::Sms, from, to, body, attachments, timestamp)
 }
 
 pub fn insert_outbound_mms(
+    keys: &KeyNormalization,
     from: &str,
     to: &str,
     body: &str,
     attachments: &Option<Vec<String>>,
     timestamp: &str,
 ) -> String {
-    insert_outbound(Channel::Mms, from, to, body, attachments, timestamp)
+    insert_outbound(keys, Channel::Mms, from, to, body, attachments, timestamp)
 }
 
 pub fn insert_outbound_email(
+    keys: &KeyNormalization,
     from: &str,
     to: &str,
     body: &str,
     attachments: &Option<Vec<String>>,
     timestamp: &str,
 ) -> String {
-    insert_outbound(Channel::Email, from, to, body, attachments, timestamp)
+    insert_outbound(keys, Channel::Email, from, to, body, attachments, timestamp)
 }
 
 fn insert_outbound(
+    keys: &KeyNormalization,
     channel: Channel,
     from: &str,
     to: &str,
@@ -168,7 +174,7 @@ fn insert_outbound(
     let mut w = lock.write().unwrap();
     w.push(msg);
     if let Some(last) = w.last() {
-        conversations::on_message_stored(last);
+        conversations::on_message_stored(last, keys);
     }
     id
 }
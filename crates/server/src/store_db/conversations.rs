@@ -21,6 +21,7 @@ pub async fn list_conversations(
     limit: i64,
     offset: i64,
 ) -> Result<Vec<ConversationSummary>> {
+    let _timer = crate::metrics::db_timer("conversations.list");
     // Try durable schema first
     match sqlx::query(
         r#"SELECT id, key, channel, participant_a, participant_b, message_count, last_activity_at
@@ -94,6 +95,7 @@ pub async fn list_messages(
     limit: i64,
     offset: i64,
 ) -> Result<Vec<ConversationMessage>> {
+    let _timer = crate::metrics::db_timer("conversations.list_messages");
     let rows = sqlx::query(
         r#"SELECT m.id, m.direction, m.provider_id, m.sent_at, m.received_at, b.body,
                   c.participant_a, c.participant_b
@@ -140,6 +142,7 @@ pub async fn list_messages(
 }
 
 pub async fn conversations_total(pool: &PgPool) -> Result<i64> {
+    let _timer = crate::metrics::db_timer("conversations.total");
     let row = sqlx::query(r#"SELECT COUNT(*) as count FROM conversations"#)
         .fetch_one(pool)
         .await?;
@@ -148,6 +151,7 @@ pub async fn conversations_total(pool: &PgPool) -> Result<i64> {
 }
 
 pub async fn messages_total(pool: &PgPool, conversation_id: i64) -> Result<i64> {
+    let _timer = crate::metrics::db_timer("conversations.messages_total");
     let row = sqlx::query(r#"SELECT COUNT(*) as count FROM messages WHERE conversation_id = $1"#)
         .bind(conversation_id)
         .fetch_one(pool)
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::BTreeMap;
+
+use crate::correlation::CorrelationId;
 
 /// Insert inbound event idempotently using unique index on (channel, provider_message_id)
 pub async fn insert_inbound_event(
@@ -11,25 +14,31 @@ pub async fn insert_inbound_event(
     provider_message_id: Option<&str>,
     payload: serde_json::Value,
 ) -> Result<()> {
+    let _timer = crate::metrics::db_timer("inbound_events.insert");
+    // Carry the request's trace and correlation ID into the worker that processes this row
+    let mut payload = payload;
+    crate::telemetry::inject_into_payload(&mut payload);
     // attempts/status columns from existing schema: attempts -> attempt_count; status -> pending
     // Map to existing: status 'pending'
     sqlx::query!(
-        r#"INSERT INTO inbound_events (event_type, payload, available_at, status, channel, "from", "to", provider_message_id)
-            VALUES ($1, $2, now(), 'pending', $1, $3, $4, $5)
+        r#"INSERT INTO inbound_events (event_type, payload, available_at, status, channel, "from", "to", provider_message_id, correlation_id)
+            VALUES ($1, $2, now(), 'pending', $1, $3, $4, $5, $6)
             ON CONFLICT (channel, provider_message_id) WHERE provider_message_id IS NOT NULL DO NOTHING"#,
         channel,
         payload,
         from,
         to,
-        provider_message_id
+        provider_message_id,
+        CorrelationId::current().map(|c| c.to_string())
     )
     .execute(pool)
     .await?;
     Ok(())
 }
 
-/// Claim a batch of pending events; set status=processing and return rows
-pub async fn claim_batch(pool: &PgPool, batch_size: i64) -> Result<Vec<i64>> {
+/// Claim a batch of pending events for `processor_id`; set status=processing and return rows
+pub async fn claim_batch(pool: &PgPool, batch_size: i64, processor_id: &str) -> Result<Vec<i64>> {
+    let _timer = crate::metrics::db_timer("inbound_events.claim_batch");
     // Use SKIP LOCKED pattern
     let mut tx: Transaction<'_, Postgres> = pool.begin().await?;
     let rows = sqlx::query!(
@@ -44,9 +53,10 @@ pub async fn claim_batch(pool: &PgPool, batch_size: i64) -> Result<Vec<i64>> {
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     if !ids.is_empty() {
         sqlx::query!(
-            r#"UPDATE inbound_events SET status='processing', updated_at=now()
+            r#"UPDATE inbound_events SET status='processing', processor_id=$2, updated_at=now()
                 WHERE id = ANY($1)"#,
-            &ids
+            &ids,
+            processor_id
         )
         .execute(&mut *tx)
         .await?;
@@ -56,6 +66,7 @@ pub async fn claim_batch(pool: &PgPool, batch_size: i64) -> Result<Vec<i64>> {
 }
 
 pub async fn mark_processed(pool: &PgPool, id: i64) -> Result<()> {
+    let _timer = crate::metrics::db_timer("inbound_events.mark_processed");
     sqlx::query!(
         r#"UPDATE inbound_events SET status='done', processed_at=now(), updated_at=now() WHERE id=$1"#,
         id
@@ -65,18 +76,28 @@ pub async fn mark_processed(pool: &PgPool, id: i64) -> Result<()> {
     Ok(())
 }
 
+/// An inbound event as read back for processing.
+#[derive(Debug, Clone)]
+pub struct FetchedEvent {
+    pub channel: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub payload: serde_json::Value,
+    pub correlation_id: Option<String>,
+}
+
 /// Fetch a single inbound event payload and metadata for processing
-pub async fn fetch_event(
-    pool: &PgPool,
-    id: i64,
-) -> Result<Option<(String, Option<String>, Option<String>, serde_json::Value)>> {
-    let row = sqlx::query!(
-        r#"SELECT channel, "from", "to", payload FROM inbound_events WHERE id=$1"#,
+pub async fn fetch_event(pool: &PgPool, id: i64) -> Result<Option<FetchedEvent>> {
+    let _timer = crate::metrics::db_timer("inbound_events.fetch");
+    let row = sqlx::query_as!(
+        FetchedEvent,
+        r#"SELECT COALESCE(channel, '') AS "channel!", "from", "to", payload, correlation_id
+           FROM inbound_events WHERE id=$1"#,
         id
     )
     .fetch_optional(pool)
     .await?;
-    Ok(row.map(|r| (r.channel.unwrap_or_default(), r.from, r.to, r.payload)))
+    Ok(row)
 }
 
 pub async fn mark_error(
@@ -87,6 +108,7 @@ pub async fn mark_error(
     max_retries: i32,
     backoff_base_ms: i64,
 ) -> Result<bool> {
+    let _timer = crate::metrics::db_timer("inbound_events.mark_error");
     // Increment attempts; compute exponential backoff; set available_at for retry; if exceeds max, mark dead
     let rec = sqlx::query!(r#"SELECT attempts FROM inbound_events WHERE id=$1"#, id)
         .fetch_one(pool)
@@ -133,6 +155,7 @@ pub async fn mark_error(
 
 /// Reap stale processing claims after timeout_secs
 pub async fn reap_stale(pool: &PgPool, timeout_secs: i64) -> Result<u64> {
+    let _timer = crate::metrics::db_timer("inbound_events.reap_stale");
     let cutoff: DateTime<Utc> = Utc::now() - chrono::Duration::seconds(timeout_secs);
     let res = sqlx::query!(
         r#"UPDATE inbound_events SET status='pending', updated_at=now()
@@ -143,3 +166,67 @@ pub async fn reap_stale(pool: &PgPool, timeout_secs: i64) -> Result<u64> {
     .await?;
     Ok(res.rows_affected())
 }
+
+/// Return claimed-but-unprocessed events to `pending` (graceful shutdown) so another worker
+/// can pick them up immediately instead of waiting for `reap_stale`.
+pub async fn release_claims(pool: &PgPool, ids: &[i64]) -> Result<u64> {
+    let _timer = crate::metrics::db_timer("inbound_events.release_claims");
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let res = sqlx::query(
+        r#"UPDATE inbound_events SET status='pending', processor_id=NULL, updated_at=now()
+            WHERE id = ANY($1) AND status='processing'"#,
+    )
+    .bind(ids)
+    .execute(pool)
+    .await?;
+    Ok(res.rows_affected())
+}
+
+/// Point-in-time view of `inbound_events` for `/admin/queues` and the queue gauges.
+#[derive(Debug, Clone, Default)]
+pub struct InboundQueueStats {
+    /// Row counts keyed by status (`pending`, `processing`, `done`, `dead`)
+    pub by_status: BTreeMap<String, i64>,
+    /// Seconds since the oldest pending event was received
+    pub oldest_pending_age_secs: Option<f64>,
+    /// Seconds the oldest *ready* pending event (available_at <= now) has been waiting
+    pub lag_secs: Option<f64>,
+    /// Processing claims keyed by worker `processor_id` (`unknown` for legacy claims)
+    pub processing_by_worker: BTreeMap<String, i64>,
+}
+
+impl InboundQueueStats {
+    pub fn count(&self, status: &str) -> i64 {
+        self.by_status.get(status).copied().unwrap_or(0)
+    }
+}
+
+pub async fn queue_stats(pool: &PgPool) -> Result<InboundQueueStats> {
+    let _timer = crate::metrics::db_timer("inbound_events.queue_stats");
+    let by_status: Vec<(String, i64)> =
+        sqlx::query_as("SELECT status, COUNT(*)::BIGINT FROM inbound_events GROUP BY status")
+            .fetch_all(pool)
+            .await?;
+    let (oldest_pending_age_secs, lag_secs): (Option<f64>, Option<f64>) = sqlx::query_as(
+        r#"SELECT
+            EXTRACT(EPOCH FROM (now() - MIN(received_at)))::FLOAT8,
+            EXTRACT(EPOCH FROM (now() - MIN(available_at) FILTER (WHERE available_at <= now())))::FLOAT8
+            FROM inbound_events WHERE status='pending'"#,
+    )
+    .fetch_one(pool)
+    .await?;
+    let processing_by_worker: Vec<(String, i64)> = sqlx::query_as(
+        r#"SELECT COALESCE(processor_id, 'unknown'), COUNT(*)::BIGINT FROM inbound_events
+            WHERE status='processing' GROUP BY 1"#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(InboundQueueStats {
+        by_status: by_status.into_iter().collect(),
+        oldest_pending_age_secs,
+        lag_secs,
+        processing_by_worker: processing_by_worker.into_iter().collect(),
+    })
+}
@@ -6,21 +6,25 @@ use sqlx::{PgPool, Row};
 use tracing::{instrument, warn};
 use twox_hash::xxh3::hash64;
 
+use crate::correlation::CorrelationId;
 // legacy helper (to be removed)
 use crate::logging::message_persisted;
 use messaging_core::conversations::{
-    key::ChannelKind,
+    key::{ChannelKind, KeyNormalization},
     logging::log_upsert_outcome,
     metrics::metrics,
     upsert::{upsert_conversation, UpsertOutcome},
 };
 
 /// Persist an inbound message using simplified bootstrap rules:
+/// - Stores the current [`CorrelationId`] (the worker scopes it from the `inbound_events` row)
 /// - Assumes a single bootstrap customer (id=1) and provider (id=1) already exist (future migration may ensure this)
 /// - Upserts a conversation keyed by normalized endpoints+channel (temporary table-less approach: search existing messages for latest conversation id matching provider+participants)
-#[instrument(skip(pool, body, attachments))]
+#[instrument(skip(pool, keys, body, attachments))]
+#[allow(clippy::too_many_arguments)]
 pub async fn insert_from_inbound(
     pool: &PgPool,
+    keys: &KeyNormalization,
     channel: &str,
     from: &str,
     to: &str,
@@ -28,6 +32,7 @@ pub async fn insert_from_inbound(
     attachments: &[String],
     timestamp: &str,
 ) -> Result<i64> {
+    let _timer = crate::metrics::db_timer("messages.insert_from_inbound");
     // Map channel string to ChannelKind
     let channel_kind = match channel {
         "email" => ChannelKind::Email,
@@ -61,7 +66,7 @@ pub async fn insert_from_inbound(
         }
     };
     // Upsert conversation using normalized endpoints
-    let upsert_outcome = upsert_conversation(pool, channel_kind.clone(), from, to, ts).await;
+    let upsert_outcome = upsert_conversation(pool, keys, channel_kind.clone(), from, to, ts).await;
     let (convo_id, conv_key_str) = match &upsert_outcome {
         UpsertOutcome::Created(id, k) => {
             metrics().inc_created();
@@ -91,12 +96,13 @@ pub async fn insert_from_inbound(
     }
     // Insert message referencing body
     let rec = sqlx::query(
-        r#"INSERT INTO messages (conversation_id, provider_id, direction, sent_at, received_at, body_id)
-           VALUES ($1, 1, 'inbound', $2, $2, $3) RETURNING id"#,
+        r#"INSERT INTO messages (conversation_id, provider_id, direction, sent_at, received_at, body_id, correlation_id)
+           VALUES ($1, 1, 'inbound', $2, $2, $3, $4) RETURNING id"#,
     )
     .bind(convo_id)
     .bind(ts)
     .bind(body_id)
+    .bind(CorrelationId::current().map(|c| c.to_string()))
     .fetch_one(pool)
     .await?;
     let message_id: i64 = rec.get("id");
@@ -124,9 +130,12 @@ pub async fn insert_from_inbound(
 /// - direction = 'outbound'
 /// - provider_id currently hard-coded to 1 (bootstrap mock provider)
 /// - body stored/deduplicated identically via message_bodies table
-#[instrument(skip(pool, body, attachments))]
+/// - correlation_id is the calling request's [`CorrelationId`]
+#[instrument(skip(pool, keys, body, attachments))]
+#[allow(clippy::too_many_arguments)]
 pub async fn insert_outbound(
     pool: &PgPool,
+    keys: &KeyNormalization,
     channel: &str,
     from: &str,
     to: &str,
@@ -134,6 +143,7 @@ pub async fn insert_outbound(
     attachments: &[String],
     timestamp: &str,
 ) -> Result<i64> {
+    let _timer = crate::metrics::db_timer("messages.insert_outbound");
     let channel_kind = match channel {
         "email" => ChannelKind::Email,
         "sms" => ChannelKind::Sms,
@@ -161,7 +171,7 @@ pub async fn insert_outbound(
             Some(existing.get("id"))
         }
     };
-    let upsert_outcome = upsert_conversation(pool, channel_kind.clone(), from, to, ts).await;
+    let upsert_outcome = upsert_conversation(pool, keys, channel_kind.clone(), from, to, ts).await;
     let (convo_id, conv_key_str) = match &upsert_outcome {
         UpsertOutcome::Created(id, k) => {
             metrics().inc_created();
@@ -189,12 +199,13 @@ pub async fn insert_outbound(
         return Ok(existing_id);
     }
     let rec = sqlx::query(
-        r#"INSERT INTO messages (conversation_id, provider_id, direction, sent_at, received_at, body_id)
-           VALUES ($1, 1, 'outbound', $2, $2, $3) RETURNING id"#,
+        r#"INSERT INTO messages (conversation_id, provider_id, direction, sent_at, received_at, body_id, correlation_id)
+           VALUES ($1, 1, 'outbound', $2, $2, $3, $4) RETURNING id"#,
     )
     .bind(convo_id)
     .bind(ts)
     .bind(body_id)
+    .bind(CorrelationId::current().map(|c| c.to_string()))
     .fetch_one(pool)
     .await?;
     let message_id: i64 = rec.get("id");
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+/// Newest `crates/db-migrate/migrations_sqlx` version this build's queries rely on.
+/// Bump it together with every new migration.
+pub const REQUIRED_MIGRATION_VERSION: i64 = 19;
+
+/// Round-trip a trivial query to prove the pool can reach the database.
+pub async fn ping(pool: &PgPool) -> Result<()> {
+    let _timer = crate::metrics::db_timer("schema.ping");
+    sqlx::query("SELECT 1").execute(pool).await?;
+    Ok(())
+}
+
+/// Highest successfully applied migration; `None` before `db-migrate apply` has ever run.
+pub async fn applied_migration_version(pool: &PgPool) -> Result<Option<i64>> {
+    let _timer = crate::metrics::db_timer("schema.migration_version");
+    let exists: bool =
+        sqlx::query_scalar("SELECT to_regclass('public._sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if !exists {
+        return Ok(None);
+    }
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+            .fetch_one(pool)
+            .await?;
+    Ok(version)
+}
@@ -0,0 +1,204 @@
+//! OpenTelemetry tracing: OTLP/HTTP span export and W3C trace-context propagation.
+//!
+//! A send request's span has to survive two hops that `tracing` cannot follow by itself: the
+//! in-memory `InboundQueue` and the `inbound_events` table. Both carry a [`TraceCarrier`]
+//! (`traceparent` / `tracestate`), so the outbound and inbound workers continue the same trace.
+
+use axum::http::HeaderMap;
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_otlp::{Protocol, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// W3C trace-context fields (`traceparent`, optional `tracestate`).
+pub type TraceCarrier = HashMap<String, String>;
+
+/// Key under which `inbound_events.payload` stores the producer's [`TraceCarrier`].
+pub const PAYLOAD_TRACE_KEY: &str = "trace_context";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OtlpProtocol {
+    #[default]
+    HttpProtobuf,
+    HttpJson,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// Full OTLP/HTTP traces URL (e.g. `http://localhost:4318/v1/traces`); `None` disables export
+    pub otlp_endpoint: Option<String>,
+    pub protocol: OtlpProtocol,
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            protocol: OtlpProtocol::HttpProtobuf,
+            service_name: "messaging-server".to_string(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// Standard OTel variables: `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` (used as-is) or
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` (+ `/v1/traces`), `OTEL_EXPORTER_OTLP_PROTOCOL`
+    /// (`http/protobuf` or `http/json`) and `OTEL_SERVICE_NAME`.
+    pub fn from_env() -> Self {
+        let var = |k: &str| std::env::var(k).ok().filter(|v| !v.trim().is_empty());
+        let otlp_endpoint = var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT").or_else(|| {
+            var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .map(|base| format!("{}/v1/traces", base.trim_end_matches('/')))
+        });
+        let protocol = match var("OTEL_EXPORTER_OTLP_PROTOCOL").as_deref() {
+            Some("http/json") => OtlpProtocol::HttpJson,
+            _ => OtlpProtocol::HttpProtobuf,
+        };
+        Self {
+            otlp_endpoint,
+            protocol,
+            service_name: var("OTEL_SERVICE_NAME").unwrap_or_else(|| "messaging-server".into()),
+        }
+    }
+}
+
+/// Keeps the tracer provider alive; dropping it flushes and shuts down the exporter.
+pub struct TelemetryGuard {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl TelemetryGuard {
+    pub fn exporting(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    /// Export all finished spans now (blocks until the exporter has sent them).
+    pub fn force_flush(&self) {
+        if let Some(p) = &self.provider {
+            if let Err(e) = p.force_flush() {
+                eprintln!("otel force_flush failed: {e}");
+            }
+        }
+    }
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Some(p) = self.provider.take() {
+            if let Err(e) = p.shutdown() {
+                eprintln!("otel shutdown failed: {e}");
+            }
+        }
+    }
+}
+
+/// Install the global subscriber: env-filtered fmt logging, plus an OpenTelemetry layer when an
+/// OTLP endpoint is configured. Like `messaging_core::logging::init_logging`, a second call
+/// leaves the existing subscriber in place.
+pub fn init(level: &str, cfg: &TelemetryConfig) -> Result<TelemetryGuard, String> {
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level))
+        .map_err(|e| format!("invalid log level or filter: {e}"))?;
+    let provider = match &cfg.otlp_endpoint {
+        Some(endpoint) => Some(build_provider(endpoint, cfg)?),
+        None => None,
+    };
+    let otel_layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer("messaging-server")));
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(otel_layer)
+        .try_init();
+    Ok(TelemetryGuard { provider })
+}
+
+fn build_provider(endpoint: &str, cfg: &TelemetryConfig) -> Result<SdkTracerProvider, String> {
+    let protocol = match cfg.protocol {
+        OtlpProtocol::HttpProtobuf => Protocol::HttpBinary,
+        OtlpProtocol::HttpJson => Protocol::HttpJson,
+    };
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .with_protocol(protocol)
+        .build()
+        .map_err(|e| format!("otlp exporter: {e}"))?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(cfg.service_name.clone())
+                .build(),
+        )
+        .build())
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+fn set_parent(span: &Span, cx: opentelemetry::Context) {
+    if cx.span().span_context().is_valid() {
+        let _ = span.set_parent(cx);
+    }
+}
+
+/// Continue the caller's trace when the request carries a W3C `traceparent` header.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    set_parent(
+        span,
+        TraceContextPropagator::new().extract(&HeaderExtractor(headers)),
+    );
+}
+
+/// Continue the trace captured in `carrier` (from a queued event or payload).
+pub fn set_parent_from_carrier(span: &Span, carrier: &TraceCarrier) {
+    set_parent(span, TraceContextPropagator::new().extract(carrier));
+}
+
+/// Trace context of the current span, for handing work to another task; `None` when the
+/// current span is not being traced (no OTLP export configured).
+pub fn current_carrier() -> Option<TraceCarrier> {
+    let cx = Span::current().context();
+    if !cx.span().span_context().is_valid() {
+        return None;
+    }
+    let mut carrier = TraceCarrier::new();
+    TraceContextPropagator::new().inject_context(&cx, &mut carrier);
+    Some(carrier)
+}
+
+/// Embed the current trace context in an `inbound_events` payload.
+pub fn inject_into_payload(payload: &mut serde_json::Value) {
+    if let (Some(obj), Some(carrier)) = (payload.as_object_mut(), current_carrier()) {
+        obj.insert(
+            PAYLOAD_TRACE_KEY.to_string(),
+            serde_json::to_value(carrier).unwrap_or_default(),
+        );
+    }
+}
+
+pub fn carrier_from_payload(payload: &serde_json::Value) -> Option<TraceCarrier> {
+    payload
+        .get(PAYLOAD_TRACE_KEY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+}
@@ -1,9 +1,104 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use messaging_core::conversations::key::KeyNormalization;
+use messaging_core::conversations::normalize_phone;
 
 use crate::config::ApiConfig;
 
+/// Request body checks against the live `api` config. Phone numbers are checked in the region
+/// of `keys`, the server's key normalization, so a number is accepted exactly when it gets an
+/// E.164 conversation key.
 pub trait Validate {
-    fn validate(&self, api: &ApiConfig) -> Result<(), String>;
+    fn validate(&self, api: &ApiConfig, keys: &KeyNormalization) -> Result<(), ValidationError>;
+}
+
+/// A rejected request body. `details` is returned as `ErrorResponse.details` so clients can
+/// tell which limit was hit without parsing `message`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub message: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl ValidationError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// `field` is over the configured `limit` (an `ApiConfig` key); `details` carries
+    /// `reason: "limit_exceeded"`, the field, the limit key, `max` and `actual`.
+    pub fn limit_exceeded(
+        message: impl Into<String>,
+        field: &str,
+        limit: &str,
+        max: u64,
+        actual: u64,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            details: Some(serde_json::json!({
+                "reason": "limit_exceeded",
+                "field": field,
+                "limit": limit,
+                "max": max,
+                "actual": actual,
+            })),
+        }
+    }
+}
+
+impl From<&str> for ValidationError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<String> for ValidationError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Check that `from` and `to` are phone numbers that convert to E.164 in the region conversation
+/// keys use; `details` names the field and the `PhoneError` code.
+fn check_phones(from: &str, to: &str, keys: &KeyNormalization) -> Result<(), ValidationError> {
+    for (field, value) in [("from", from), ("to", to)] {
+        if let Err(e) = normalize_phone::to_e164(value, keys.phone_region) {
+            return Err(ValidationError {
+                message: format!("'{field}' is not a valid phone number: {e}"),
+                details: Some(serde_json::json!({
+                    "reason": "invalid_phone",
+                    "field": field,
+                    "error": e.code(),
+                })),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check `count` attachments against `max_attachments`.
+fn check_attachments(count: usize, api: &ApiConfig) -> Result<(), ValidationError> {
+    if count > api.max_attachments {
+        return Err(ValidationError::limit_exceeded(
+            format!("too many attachments (max {})", api.max_attachments),
+            "attachments",
+            "max_attachments",
+            api.max_attachments as u64,
+            count as u64,
+        ));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,30 +121,62 @@ pub struct SmsRequest {
 }
 
 impl Validate for SmsRequest {
-    fn validate(&self, api: &ApiConfig) -> Result<(), String> {
+    fn validate(&self, api: &ApiConfig, keys: &KeyNormalization) -> Result<(), ValidationError> {
         if self.from.trim().is_empty() || self.to.trim().is_empty() {
             return Err("'from' and 'to' are required".into());
         }
         if self.body.trim().is_empty() {
             return Err("'body' is required".into());
         }
+        check_phones(&self.from, &self.to, keys)?;
         let t = self.r#type.to_ascii_lowercase();
         if t != "sms" && t != "mms" {
             return Err("'type' must be 'sms' or 'mms'".into());
         }
         if let Some(atts) = &self.attachments {
-            if atts.len() > api.max_attachments {
-                return Err(format!(
-                    "too many attachments (max {})",
-                    api.max_attachments
-                ));
-            }
+            check_attachments(atts.len(), api)?;
             if t == "mms" && atts.is_empty() {
                 return Err("mms requires at least one attachment".into());
             }
         } else if t == "mms" {
             return Err("mms requires at least one attachment".into());
         }
+        if t == "mms" {
+            let payload = self.body.len()
+                + self
+                    .attachments
+                    .iter()
+                    .flatten()
+                    .map(String::len)
+                    .sum::<usize>();
+            if api.max_mms_payload_bytes > 0 && payload > api.max_mms_payload_bytes {
+                return Err(ValidationError::limit_exceeded(
+                    format!(
+                        "mms body and attachments too large (max {} bytes)",
+                        api.max_mms_payload_bytes
+                    ),
+                    "payload",
+                    "max_mms_payload_bytes",
+                    api.max_mms_payload_bytes as u64,
+                    payload as u64,
+                ));
+            }
+        } else if api.max_sms_segments > 0 {
+            // Counted on the body as it will be sent (after any transliteration)
+            let (_, seg) = messaging_core::sms::prepare(&self.body, api.sms_transliterate);
+            if seg.segments > api.max_sms_segments as usize {
+                return Err(ValidationError::limit_exceeded(
+                    format!(
+                        "sms body too long: {} segments (max {})",
+                        seg.segments, api.max_sms_segments
+                    ),
+                    "body",
+                    "max_sms_segments",
+                    api.max_sms_segments as u64,
+                    seg.segments as u64,
+                ));
+            }
+        }
         Ok(())
     }
 }
@@ -64,7 +191,7 @@ pub struct EmailRequest {
 }
 
 impl Validate for EmailRequest {
-    fn validate(&self, api: &ApiConfig) -> Result<(), String> {
+    fn validate(&self, api: &ApiConfig, _keys: &KeyNormalization) -> Result<(), ValidationError> {
         if self.from.trim().is_empty() || self.to.trim().is_empty() {
             return Err("'from' and 'to' are required".into());
         }
@@ -72,12 +199,19 @@ impl Validate for EmailRequest {
             return Err("'body' is required".into());
         }
         if let Some(atts) = &self.attachments {
-            if atts.len() > api.max_attachments {
-                return Err(format!(
-                    "too many attachments (max {})",
-                    api.max_attachments
-                ));
-            }
+            check_attachments(atts.len(), api)?;
+        }
+        if api.max_email_body_bytes > 0 && self.body.len() > api.max_email_body_bytes {
+            return Err(ValidationError::limit_exceeded(
+                format!(
+                    "email body too large (max {} bytes)",
+                    api.max_email_body_bytes
+                ),
+                "body",
+                "max_email_body_bytes",
+                api.max_email_body_bytes as u64,
+                self.body.len() as u64,
+            ));
         }
         Ok(())
     }
@@ -94,6 +228,13 @@ pub struct WebhookSmsRequest {
     pub timestamp: String,
 }
 
+/// Provider webhooks are only checked for parseable phone numbers.
+impl Validate for WebhookSmsRequest {
+    fn validate(&self, _api: &ApiConfig, keys: &KeyNormalization) -> Result<(), ValidationError> {
+        check_phones(&self.from, &self.to, keys)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebhookEmailRequest {
     pub from: String,
@@ -143,6 +284,67 @@ pub struct MessageDto {
     pub timestamp: String,
 }
 
+// --------- Admin: dead letters ---------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterDto {
+    pub id: String,
+    pub event_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_message_id: Option<String>,
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    pub received_at: String,
+    pub dead_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Bulk requeue/purge target: explicit `ids`, or `all: true`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadLetterSelection {
+    #[serde(default)]
+    pub ids: Vec<i64>,
+    #[serde(default)]
+    pub all: bool,
+}
+
+// --------- Admin: queues ---------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuesDto {
+    /// `None` when the server runs without a database
+    pub inbound: Option<InboundQueueDto>,
+    pub outbound: OutboundQueueDto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboundQueueDto {
+    /// Always includes `pending`, `processing`, `done` and `dead` (zero when empty)
+    pub by_status: BTreeMap<String, i64>,
+    /// Seconds since the oldest pending event was received
+    pub oldest_pending_age_secs: Option<f64>,
+    /// Seconds the oldest ready pending event has waited past its `available_at`
+    pub lag_secs: Option<f64>,
+    /// Processing claims per worker `processor_id`
+    pub processing_by_worker: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboundQueueDto {
+    pub depth: usize,
+    pub capacity: usize,
+}
+
 // --------- Provider Mock Inbound (US2) ---------
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -176,24 +378,20 @@ pub struct EmailInbound {
 }
 
 impl Validate for SmsInbound {
-    fn validate(&self, api: &ApiConfig) -> Result<(), String> {
+    fn validate(&self, api: &ApiConfig, keys: &KeyNormalization) -> Result<(), ValidationError> {
         if self.from.trim().is_empty() || self.to.trim().is_empty() {
             return Err("'from' and 'to' are required".into());
         }
         if self.body.trim().is_empty() {
             return Err("'body' is required".into());
         }
+        check_phones(&self.from, &self.to, keys)?;
         let t = self.r#type.to_ascii_lowercase();
         if t != "sms" && t != "mms" {
             return Err("'type' must be 'sms' or 'mms'".into());
         }
         if let Some(atts) = &self.attachments {
-            if atts.len() > api.max_attachments {
-                return Err(format!(
-                    "too many attachments (max {})",
-                    api.max_attachments
-                ));
-            }
+            check_attachments(atts.len(), api)?;
             if t == "mms" && atts.is_empty() {
                 return Err("mms requires at least one attachment".into());
             }
@@ -205,7 +403,7 @@ impl Validate for SmsInbound {
 }
 
 impl Validate for EmailInbound {
-    fn validate(&self, api: &ApiConfig) -> Result<(), String> {
+    fn validate(&self, api: &ApiConfig, _keys: &KeyNormalization) -> Result<(), ValidationError> {
         if self.from.trim().is_empty() || self.to.trim().is_empty() {
             return Err("'from' and 'to' are required".into());
         }
@@ -213,13 +411,129 @@ impl Validate for EmailInbound {
             return Err("'body' is required".into());
         }
         if let Some(atts) = &self.attachments {
-            if atts.len() > api.max_attachments {
-                return Err(format!(
-                    "too many attachments (max {})",
-                    api.max_attachments
-                ));
-            }
+            check_attachments(atts.len(), api)?;
         }
         Ok(())
     }
 }
+
+// --------- Readiness ---------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessDto {
+    /// `ready` (HTTP 200) or `not_ready` (HTTP 503)
+    pub status: String,
+    /// Keyed by component: `database`, `migrations`, `inbound_worker`, `providers`
+    pub checks: BTreeMap<String, ComponentCheckDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentCheckDto {
+    /// `up`, `degraded`, `down` or `disabled`; any `down` makes the instance not ready
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+// --------- Admin: circuit breakers ---------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakersDto {
+    pub breakers: Vec<BreakerDto>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerDto {
+    /// Provider name, or `http.<group>` for an HTTP route-group breaker
+    pub name: String,
+    /// `provider` or `http`
+    pub scope: String,
+    /// `closed`, `open` or `half_open`
+    pub state: String,
+    pub failures: u32,
+    /// Pinned by `open` / `close` until `reset`
+    pub forced: bool,
+    pub state_age_ms: u64,
+    /// Calls in the rolling window (failure-rate mode only)
+    pub window_calls: u32,
+    /// Consecutive trips since the breaker last closed; each one doubles `open_for_secs`
+    pub trips: u32,
+    /// Length of the current (or last) open period
+    pub open_for_secs: u64,
+    pub policy: BreakerPolicyDto,
+}
+
+/// Effective policy of a breaker (see `ApiConfig::breaker_policy`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerPolicyDto {
+    /// `consecutive` or `rate`
+    pub mode: String,
+    pub failure_threshold: u32,
+    pub window_secs: u64,
+    pub failure_rate_pct: u32,
+    pub min_requests: u32,
+    pub half_open_max_probes: u32,
+    pub slow_call_ms: u64,
+    pub open_secs: u64,
+    pub max_open_secs: u64,
+}
+
+impl From<&crate::middleware::circuit_breaker::BreakerPolicy> for BreakerPolicyDto {
+    fn from(p: &crate::middleware::circuit_breaker::BreakerPolicy) -> Self {
+        Self {
+            mode: if p.window_secs == 0 {
+                "consecutive"
+            } else {
+                "rate"
+            }
+            .to_string(),
+            failure_threshold: p.failure_threshold,
+            window_secs: p.window_secs,
+            failure_rate_pct: p.failure_rate_pct,
+            min_requests: p.min_requests,
+            half_open_max_probes: p.half_open_max_probes,
+            slow_call_ms: p.slow_call_ms,
+            open_secs: p.open_secs,
+            max_open_secs: p.max_open_secs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BreakerAction {
+    /// Force open (planned provider maintenance)
+    Open,
+    /// Force closed, ignoring failures
+    Close,
+    /// Return to automatic operation
+    Reset,
+}
+
+/// POST /admin/breakers/{name} body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerActionRequest {
+    pub action: BreakerAction,
+    /// Recorded in the audit log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Result of a config reload (`POST /admin/config/reload`, SIGHUP, mock config PUT).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigReloadDto {
+    /// `admin`, `sighup` or `provider_mock`
+    pub trigger: String,
+    pub changes: Vec<ConfigChangeDto>,
+    /// Changed keys that only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChangeDto {
+    pub key: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
@@ -1,79 +1,332 @@
+use crate::correlation::CorrelationId;
 use crate::store_db::inbound_events::{
-    claim_batch, fetch_event, mark_error, mark_processed, reap_stale,
+    claim_batch, fetch_event, mark_error, mark_processed, reap_stale, release_claims, FetchedEvent,
 };
 use crate::store_db::messages::insert_from_inbound;
+use messaging_core::conversations::key::KeyNormalization;
+use sqlx::postgres::{PgListener, PgPoolOptions};
 use sqlx::PgPool;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::sleep;
-use tracing::{error, instrument, warn};
+use tokio::task::JoinSet;
+use tokio::time::{sleep, sleep_until, Instant};
+use tracing::{debug, error, info, instrument, warn, Instrument};
 
-use crate::config::ApiConfig;
+use crate::config::SharedApiConfig;
 use crate::metrics;
+use crate::shutdown::ShutdownSignal;
+use crate::state::heartbeat::Heartbeat;
+
+/// Postgres NOTIFY channel fired by the `trg_inbound_events_notify` trigger (migration 0017)
+pub const INBOUND_EVENTS_CHANNEL: &str = "inbound_events";
+
+/// Result of the inbound worker's shutdown drain.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InboundDrain {
+    pub released: u64,
+    /// Events aborted mid-processing at the drain deadline; left `processing` for `reap_stale`
+    pub abandoned: u64,
+    pub timed_out: bool,
+}
 
 pub struct InboundWorker {
     pool: PgPool,
-    cfg: ApiConfig,
+    /// Read at the top of every loop iteration, so reloaded worker settings apply to the next batch
+    cfg: SharedApiConfig,
+    id: String,
+    heartbeat: Heartbeat,
+    keys: KeyNormalization,
 }
 
 impl InboundWorker {
-    pub fn new(pool: PgPool, cfg: ApiConfig) -> Self {
-        Self { pool, cfg }
+    pub fn new(pool: PgPool, cfg: impl Into<SharedApiConfig>) -> Self {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let id = format!("inbound-{}-{}", std::process::id(), &suffix[..8]);
+        Self {
+            pool,
+            cfg: cfg.into(),
+            id,
+            heartbeat: Heartbeat::default(),
+            keys: KeyNormalization::default(),
+        }
     }
 
-    #[instrument(skip(self))]
-    pub async fn run(self) {
-        let batch_size = self.cfg.worker_batch_size as i64;
-        loop {
-            match claim_batch(&self.pool, batch_size).await {
+    /// Beat `heartbeat` on every loop iteration and finished event (read by `/readyz`).
+    pub fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    /// Derive the keys of the conversations events land in with `keys` (the server's).
+    pub fn with_keys(mut self, keys: KeyNormalization) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Recorded as `processor_id` on every claim so `/admin/queues` can attribute them.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[instrument(skip(self, shutdown), fields(worker_id = %self.id))]
+    pub async fn run(self, mut shutdown: ShutdownSignal) -> InboundDrain {
+        let mut listener = self.listen().await;
+        let this = Arc::new(self);
+        let mut drain = InboundDrain::default();
+        while !shutdown.is_triggered() {
+            this.heartbeat.beat();
+            let cfg = this.cfg.load();
+            let batch_size = cfg.worker_batch_size as i64;
+            let concurrency = cfg.worker_concurrency.max(1) as usize;
+            let poll_interval = Duration::from_millis(cfg.worker_poll_interval_ms.max(1));
+            match claim_batch(&this.pool, batch_size, &this.id).await {
                 Ok(ids) if ids.is_empty() => {
-                    sleep(Duration::from_millis(500)).await;
+                    tokio::select! {
+                        _ = this.wait_for_work(&mut listener, poll_interval) => {}
+                        _ = shutdown.wait() => {}
+                    }
                 }
                 Ok(ids) => {
                     metrics::record_worker_claimed(ids.len() as u64);
-                    for id in ids {
-                        if let Err(e) = self.process_one(id).await {
-                            // Schedule retry / dead-letter
-                            let dead = match mark_error(
-                                &self.pool,
-                                id,
-                                "process_error",
-                                &format!("{:?}", e),
-                                self.cfg.worker_max_retries as i32,
-                                self.cfg.worker_backoff_base_ms as i64,
-                            )
-                            .await
-                            {
-                                Ok(is_dead) => is_dead,
-                                Err(err) => {
-                                    warn!(error=?err, inbound_event_id=id, "failed to mark_error; keeping pending");
-                                    false
-                                }
-                            };
-                            if dead {
-                                metrics::record_worker_dead_letter();
-                            } else {
-                                metrics::record_worker_error();
-                            }
-                            warn!(error=?e, inbound_event_id=id, dead, "worker process_one failed");
-                        }
-                    }
+                    let batch = this.process_batch(ids, concurrency, &mut shutdown).await;
+                    drain.released += batch.released;
+                    drain.abandoned += batch.abandoned;
+                    drain.timed_out |= batch.timed_out;
                 }
                 Err(e) => {
                     error!(error=?e, "worker claim_batch error");
-                    sleep(Duration::from_millis(1000)).await;
+                    tokio::select! {
+                        _ = sleep(Duration::from_millis(1000)) => {}
+                        _ = shutdown.wait() => {}
+                    }
                 }
             }
             // periodic reap stale
-            if let Err(e) = reap_stale(&self.pool, self.cfg.worker_claim_timeout_secs as i64).await
-            {
+            if let Err(e) = reap_stale(&this.pool, cfg.worker_claim_timeout_secs as i64).await {
                 warn!(error=?e, "worker reap_stale error");
             }
         }
+        info!(
+            target = "server",
+            event = "inbound_drain_done",
+            released = drain.released,
+            abandoned = drain.abandoned,
+            timed_out = drain.timed_out,
+            "inbound worker stopped"
+        );
+        drain
+    }
+
+    /// Process a claimed batch with bounded parallelism. Once shutdown is requested no new
+    /// events start; in-flight ones get until the drain deadline. Claims that never started are
+    /// released back to `pending`. An event aborted at the deadline may already have written its
+    /// message, so its claim stays `processing` until `reap_stale` returns it after
+    /// `worker_claim_timeout_secs`, instead of being handed straight to another worker.
+    async fn process_batch(
+        self: &Arc<Self>,
+        ids: Vec<i64>,
+        concurrency: usize,
+        shutdown: &mut ShutdownSignal,
+    ) -> InboundDrain {
+        let mut queued = ids.into_iter();
+        let mut in_flight = JoinSet::new();
+        let mut running = HashSet::new();
+        let mut deadline = shutdown.is_triggered().then(|| self.drain_deadline());
+        let mut timed_out = false;
+        loop {
+            if deadline.is_none() {
+                while in_flight.len() < concurrency {
+                    let Some(id) = queued.next() else { break };
+                    running.insert(id);
+                    let worker = Arc::clone(self);
+                    in_flight.spawn(async move {
+                        worker.handle_one(id).await;
+                        id
+                    });
+                }
+            }
+            if in_flight.is_empty() {
+                break;
+            }
+            tokio::select! {
+                joined = in_flight.join_next() => {
+                    self.heartbeat.beat();
+                    if let Some(Ok(id)) = joined {
+                        running.remove(&id);
+                    }
+                }
+                _ = shutdown.wait(), if deadline.is_none() => {
+                    deadline = Some(self.drain_deadline());
+                }
+                _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    in_flight.abort_all();
+                    timed_out = true;
+                    break;
+                }
+            }
+        }
+        // Aborted at the deadline (or panicked): their writes may have landed
+        let abandoned = running.len() as u64;
+        if abandoned > 0 {
+            warn!(
+                target = "server",
+                event = "inbound_claims_abandoned",
+                abandoned,
+                "inbound events aborted mid-processing; left for reap_stale"
+            );
+        }
+        // Never started (shutdown)
+        let unfinished: Vec<i64> = queued.collect();
+        let released = match release_claims(&self.pool, &unfinished).await {
+            Ok(n) => n,
+            Err(e) => {
+                warn!(error=?e, count=unfinished.len(), "failed to release claims; left for reap_stale");
+                0
+            }
+        };
+        if released > 0 {
+            info!(
+                target = "server",
+                event = "inbound_claims_released",
+                released,
+                timed_out,
+                "released unprocessed inbound claims"
+            );
+        }
+        InboundDrain {
+            released,
+            abandoned,
+            timed_out,
+        }
+    }
+
+    fn drain_deadline(&self) -> Instant {
+        Instant::now() + Duration::from_secs(self.cfg.load().shutdown_drain_timeout_secs)
+    }
+
+    /// Subscribe to insert notifications; `None` means the worker relies on polling alone.
+    /// The listener holds its own connection, opened with the pool's connect options, so it
+    /// does not take one of the pool's connections away from handlers and workers.
+    async fn listen(&self) -> Option<PgListener> {
+        let own = PgPoolOptions::new()
+            .max_connections(1)
+            .max_lifetime(None)
+            .idle_timeout(None)
+            .connect_with((*self.pool.connect_options()).clone())
+            .await;
+        let connected = match own {
+            Ok(pool) => PgListener::connect_with(&pool).await,
+            Err(e) => Err(e),
+        };
+        let mut listener = match connected {
+            Ok(l) => l,
+            Err(e) => {
+                warn!(error=?e, "worker LISTEN connect failed; falling back to polling");
+                return None;
+            }
+        };
+        // Nothing closes the listener's own pool; it goes away with the listener
+        listener.ignore_pool_close_event(true);
+        if let Err(e) = listener.listen(INBOUND_EVENTS_CHANNEL).await {
+            warn!(error=?e, channel=INBOUND_EVENTS_CHANNEL, "worker LISTEN failed; falling back to polling");
+            return None;
+        }
+        Some(listener)
+    }
+
+    /// Sleep until a NOTIFY arrives or the poll interval elapses, whichever comes first.
+    async fn wait_for_work(&self, listener: &mut Option<PgListener>, poll_interval: Duration) {
+        let Some(l) = listener.as_mut() else {
+            sleep(poll_interval).await;
+            return;
+        };
+        tokio::select! {
+            res = l.recv() => match res {
+                Ok(n) => debug!(payload=%n.payload(), "worker woken by NOTIFY"),
+                Err(e) => {
+                    // PgListener reconnects on the next recv; back off one interval meanwhile
+                    warn!(error=?e, "worker LISTEN recv error");
+                    sleep(poll_interval).await;
+                }
+            },
+            _ = sleep(poll_interval) => {}
+        }
+    }
+
+    async fn handle_one(&self, id: i64) {
+        if let Err(e) = self.process_one(id).await {
+            // Schedule retry / dead-letter
+            let cfg = self.cfg.load();
+            let dead = match mark_error(
+                &self.pool,
+                id,
+                "process_error",
+                &format!("{:?}", e),
+                cfg.worker_max_retries as i32,
+                cfg.worker_backoff_base_ms as i64,
+            )
+            .await
+            {
+                Ok(is_dead) => is_dead,
+                Err(err) => {
+                    warn!(error=?err, inbound_event_id=id, "failed to mark_error; keeping pending");
+                    false
+                }
+            };
+            if dead {
+                metrics::record_worker_dead_letter();
+            } else {
+                metrics::record_worker_error();
+            }
+            warn!(error=?e, inbound_event_id=id, dead, "worker process_one failed");
+        }
     }
 
     async fn process_one(&self, inbound_id: i64) -> anyhow::Result<()> {
         let started = std::time::Instant::now();
-        if let Some((channel, from, to, payload)) = fetch_event(&self.pool, inbound_id).await? {
+        let event = fetch_event(&self.pool, inbound_id).await?;
+        // The span must get its parent before it is first entered, so build it after the fetch;
+        // it continues the trace of the request that inserted this event.
+        let span = tracing::info_span!(
+            "process_inbound_event",
+            inbound_event_id = inbound_id,
+            correlation_id = event.as_ref().and_then(|e| e.correlation_id.as_deref())
+        );
+        if let Some(carrier) = event
+            .as_ref()
+            .and_then(|e| crate::telemetry::carrier_from_payload(&e.payload))
+        {
+            crate::telemetry::set_parent_from_carrier(&span, &carrier);
+        }
+        let correlation_id = event
+            .as_ref()
+            .and_then(|e| e.correlation_id.as_deref())
+            .and_then(CorrelationId::parse);
+        let work = self
+            .persist_event(inbound_id, event, started)
+            .instrument(span);
+        match correlation_id {
+            // Message rows written below pick the ID up from the scope
+            Some(id) => id.scope(work).await,
+            None => work.await,
+        }
+    }
+
+    async fn persist_event(
+        &self,
+        inbound_id: i64,
+        event: Option<FetchedEvent>,
+        started: std::time::Instant,
+    ) -> anyhow::Result<()> {
+        if let Some(FetchedEvent {
+            channel,
+            from,
+            to,
+            payload,
+            ..
+        }) = event
+        {
             // Minimal parse: attempt body + timestamp fields if present
             let body = payload.get("body").and_then(|v| v.as_str()).unwrap_or("");
             let attachments: Vec<String> = payload
@@ -93,6 +346,7 @@ impl InboundWorker {
             // Insert placeholder message representation (no actual DB writes yet)
             let _ = insert_from_inbound(
                 &self.pool,
+                &self.keys,
                 &channel,
                 from.as_deref().unwrap_or("unknown"),
                 to.as_deref().unwrap_or("unknown"),
@@ -0,0 +1,23 @@
+use std::time::Duration;
+use tracing::warn;
+
+use crate::api::queues::{sample_inbound, sample_outbound};
+use crate::shutdown::ShutdownSignal;
+use crate::AppState;
+
+/// Refresh the `queue_*` gauges every `interval` until shutdown, so `/metrics` tracks
+/// backlog without anyone polling `/admin/queues`.
+pub(crate) async fn run(state: AppState, interval: Duration, mut shutdown: ShutdownSignal) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown.wait() => return,
+        }
+        sample_outbound(&state);
+        if let Err(e) = sample_inbound(&state).await {
+            warn!(target="server", event="queue_metrics_error", error=%e, "queue gauge refresh failed");
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Integration test: /admin/queues inspection and the matching queue gauges in /metrics
+use messaging_core::Config;
+use messaging_server::ServerBuilder;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::Arc;
+
+fn test_config() -> Arc<Config> {
+    Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    })
+}
+
+#[tokio::test]
+async fn queues_without_db_reports_outbound_only() {
+    let handle = ServerBuilder::new(test_config())
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let body: serde_json::Value = client
+        .get(format!("{base}/admin/queues"))
+        .send()
+        .await
+        .expect("queues")
+        .json()
+        .await
+        .expect("queues json");
+    assert!(body["inbound"].is_null());
+    assert_eq!(body["outbound"]["capacity"], 1024);
+    assert_eq!(body["outbound"]["depth"], 0);
+
+    let metrics: serde_json::Value = client
+        .get(format!("{base}/metrics"))
+        .send()
+        .await
+        .expect("metrics")
+        .json()
+        .await
+        .expect("metrics json");
+    assert_eq!(metrics["queue_outbound_capacity"], 1024);
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn queues_reports_inbound_backlog_and_claims() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("[admin_queues] Skipping: DATABASE_URL not set");
+        return;
+    };
+    let pool = match PgPoolOptions::new().max_connections(3).connect(&url).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[admin_queues] Skipping: cannot connect to DATABASE_URL ({e:?})");
+            return;
+        }
+    };
+    let handle = ServerBuilder::new(test_config())
+        .pool(pool.clone())
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    // Rows are inserted in their final state so the live worker leaves them alone:
+    // a pending event received an hour ago but not yet available, and a claim by a fake worker
+    let tag = uuid::Uuid::new_v4().to_string();
+    let worker_id = format!("test-worker-{tag}");
+    let pending_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO inbound_events (event_type, payload, received_at, available_at, status, channel, provider_message_id)
+            VALUES ('sms', '{}', now() - interval '1 hour', now() + interval '1 hour', 'pending', 'sms', $1)
+            RETURNING id"#,
+    )
+    .bind(format!("q-pending-{tag}"))
+    .fetch_one(&pool)
+    .await
+    .expect("insert pending");
+    let claimed_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO inbound_events (event_type, payload, available_at, status, channel, provider_message_id, processor_id)
+            VALUES ('sms', '{}', now(), 'processing', 'sms', $1, $2)
+            RETURNING id"#,
+    )
+    .bind(format!("q-claimed-{tag}"))
+    .bind(&worker_id)
+    .fetch_one(&pool)
+    .await
+    .expect("insert processing");
+
+    let body: serde_json::Value = client
+        .get(format!("{base}/admin/queues"))
+        .send()
+        .await
+        .expect("queues")
+        .json()
+        .await
+        .expect("queues json");
+    let inbound = &body["inbound"];
+    for status in ["pending", "processing", "done", "dead"] {
+        assert!(inbound["by_status"][status].is_i64(), "{status} present");
+    }
+    assert!(inbound["by_status"]["pending"].as_i64().unwrap_or(0) >= 1);
+    assert_eq!(inbound["processing_by_worker"][worker_id.as_str()], 1);
+    assert!(inbound["oldest_pending_age_secs"].as_f64().unwrap_or(0.0) >= 3600.0);
+
+    // The request refreshed the gauges
+    let metrics: serde_json::Value = client
+        .get(format!("{base}/metrics"))
+        .send()
+        .await
+        .expect("metrics")
+        .json()
+        .await
+        .expect("metrics json");
+    assert!(metrics["queue_inbound_pending"].as_u64().unwrap_or(0) >= 1);
+    assert!(metrics["queue_inbound_processing"].as_u64().unwrap_or(0) >= 1);
+    assert!(
+        metrics["queue_inbound_oldest_pending_age_ms"]
+            .as_u64()
+            .unwrap_or(0)
+            >= 3_600_000
+    );
+
+    sqlx::query("DELETE FROM inbound_events WHERE id = ANY($1)")
+        .bind(vec![pending_id, claimed_id])
+        .execute(&pool)
+        .await
+        .expect("cleanup");
+    handle.shutdown().await;
+}
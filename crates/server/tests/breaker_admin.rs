@@ -0,0 +1,189 @@
+// Integration test: /admin/breakers lists breaker state and force-open / force-close / reset
+// override automatic behavior until reset
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{
+    ChannelKind, DispatchResult, OutboundMessage, Provider,
+};
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+struct CountingProvider(Arc<AtomicU64>);
+impl Provider for CountingProvider {
+    fn name(&self) -> &str {
+        "email"
+    }
+    fn dispatch(&self, _msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        DispatchResult {
+            provider_name: self.name().to_string(),
+            outcome: Outcome::Success,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+#[tokio::test]
+async fn operators_can_force_and_reset_breakers() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let dispatched = Arc::new(AtomicU64::new(0));
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            breaker_open_secs: 1,
+            ..ApiConfig::default()
+        })
+        .provider(
+            ChannelKind::Email,
+            Arc::new(CountingProvider(dispatched.clone())),
+        )
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let act = |name: &str, body: Value| {
+        client
+            .post(format!("{base}/admin/breakers/{name}"))
+            .json(&body)
+            .send()
+    };
+    let send_email = |n: u32| {
+        client
+            .post(format!("{base}/api/messages/email"))
+            .json(&json!({
+                "from": "breakers@example.com",
+                "to": "b@example.com",
+                "body": format!("message {n}"),
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+
+    // Listing: every provider breaker plus one per HTTP route group, all closed
+    let list: Value = client
+        .get(format!("{base}/admin/breakers"))
+        .send()
+        .await
+        .expect("list")
+        .json()
+        .await
+        .expect("list json");
+    let names: Vec<&str> = list["breakers"]
+        .as_array()
+        .expect("breakers")
+        .iter()
+        .map(|b| b["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "email",
+            "sms-mms",
+            "http.admin",
+            "http.conversations",
+            "http.messages",
+            "http.other",
+            "http.provider_mock",
+            "http.webhooks"
+        ]
+    );
+    assert!(list["breakers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|b| b["state"] == "closed" && b["forced"] == false));
+
+    // Forced open: dispatch short-circuits and the recovery timeout does not half-open it
+    let resp = act(
+        "email",
+        json!({"action": "open", "reason": "provider maintenance"}),
+    )
+    .await
+    .expect("open");
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["state"], "open");
+    assert_eq!(body["forced"], true);
+    assert_eq!(send_email(1).await.expect("send").status(), 202);
+    sleep(Duration::from_millis(1200)).await;
+    assert_eq!(dispatched.load(Ordering::SeqCst), 0);
+    let body: Value = client
+        .get(format!("{base}/admin/breakers/email"))
+        .send()
+        .await
+        .expect("show")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(body["state"], "open");
+    assert!(body["state_age_ms"].as_u64().unwrap() >= 1000);
+
+    // Reset: automatic operation resumes
+    let body: Value = act("email", json!({"action": "reset"}))
+        .await
+        .expect("reset")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(body["state"], "closed");
+    assert_eq!(body["forced"], false);
+    assert_eq!(send_email(2).await.expect("send").status(), 202);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while dispatched.load(Ordering::SeqCst) == 0 {
+        assert!(Instant::now() < deadline, "dispatch did not resume");
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    // A route group's breaker blocks that group only, and never the admin endpoint that
+    // closes it again
+    act("http.messages", json!({"action": "open"}))
+        .await
+        .expect("open http");
+    assert_eq!(send_email(3).await.expect("send").status(), 503);
+    let resp = client
+        .get(format!("{base}/api/conversations"))
+        .send()
+        .await
+        .expect("conversations");
+    assert_ne!(resp.status(), 503);
+    let body: Value = act("http.messages", json!({"action": "close"}))
+        .await
+        .expect("close http")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(body["state"], "closed");
+    assert_eq!(body["forced"], true);
+    assert_eq!(send_email(4).await.expect("send").status(), 202);
+
+    // Unknown breaker / action
+    for name in ["nope", "http", "http.nope"] {
+        let resp = act(name, json!({"action": "open"})).await.expect("unknown");
+        assert_eq!(resp.status(), 404);
+    }
+    let resp = act("email", json!({"action": "explode"}))
+        .await
+        .expect("bad");
+    assert!(resp.status().is_client_error());
+
+    handle.shutdown().await;
+}
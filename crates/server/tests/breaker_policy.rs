@@ -0,0 +1,148 @@
+// Integration test: breaker policies (failure-rate window, half-open probes, slow calls, open backoff)
+use messaging_server::config::ApiConfig;
+use messaging_server::middleware::circuit_breaker::{BreakerPolicy, BreakerState, CircuitBreaker};
+use std::time::Duration;
+
+fn rate_policy() -> BreakerPolicy {
+    BreakerPolicy {
+        failure_threshold: 1,
+        window_secs: 60,
+        failure_rate_pct: 50,
+        min_requests: 4,
+        half_open_max_probes: 2,
+        slow_call_ms: 0,
+        open_secs: 1,
+        max_open_secs: 3,
+    }
+}
+
+#[test]
+fn failure_rate_needs_minimum_volume() {
+    let b = CircuitBreaker::with_policy(rate_policy());
+    // 3 of 3 failed, but below min_requests
+    for _ in 0..3 {
+        assert_eq!(b.before_request(), BreakerState::Closed);
+        b.record_failure();
+    }
+    assert_eq!(b.state(), BreakerState::Closed);
+    // 3 of 4 failed (75% >= 50%) once the volume is reached
+    b.record_failure();
+    assert_eq!(b.state(), BreakerState::Open);
+    assert_eq!(b.before_request(), BreakerState::Open);
+}
+
+#[test]
+fn failure_rate_below_threshold_stays_closed() {
+    let b = CircuitBreaker::with_policy(rate_policy());
+    for i in 0..10 {
+        if i % 4 == 0 {
+            b.record_failure();
+        } else {
+            b.record_success();
+        }
+    }
+    let snap = b.snapshot();
+    assert_eq!(snap.state, BreakerState::Closed);
+    assert_eq!((snap.failures, snap.window_calls), (3, 10));
+}
+
+#[test]
+fn slow_calls_count_as_failures() {
+    let b = CircuitBreaker::with_policy(BreakerPolicy {
+        slow_call_ms: 100,
+        ..BreakerPolicy::consecutive(2, 1)
+    });
+    b.record_call(true, Duration::from_millis(20));
+    b.record_call(true, Duration::from_millis(150));
+    assert_eq!(b.state(), BreakerState::Closed);
+    b.record_call(true, Duration::from_millis(300));
+    assert_eq!(b.state(), BreakerState::Open);
+}
+
+#[test]
+fn half_open_limits_probes_and_backs_off_on_retrip() {
+    let b = CircuitBreaker::with_policy(BreakerPolicy {
+        half_open_max_probes: 2,
+        max_open_secs: 3,
+        ..BreakerPolicy::consecutive(1, 1)
+    });
+    b.record_failure();
+    assert_eq!(b.snapshot().open_for, Duration::from_secs(1));
+
+    std::thread::sleep(Duration::from_millis(1100));
+    assert_eq!(b.before_request(), BreakerState::HalfOpen);
+    assert_eq!(b.before_request(), BreakerState::HalfOpen);
+    assert_eq!(
+        b.before_request(),
+        BreakerState::Open,
+        "third probe rejected"
+    );
+    // A probe ending without a verdict frees its slot
+    b.release();
+    assert_eq!(b.before_request(), BreakerState::HalfOpen);
+
+    // Failed probe re-opens for twice as long
+    b.record_failure();
+    let snap = b.snapshot();
+    assert_eq!(snap.state, BreakerState::Open);
+    assert_eq!((snap.trips, snap.open_for), (2, Duration::from_secs(2)));
+    assert!(b.retry_after() > Duration::from_secs(1));
+
+    // Growth is capped at max_open_secs
+    let policy = b.policy();
+    assert_eq!(policy.open_duration(3), Duration::from_secs(3));
+    assert_eq!(policy.open_duration(10), Duration::from_secs(3));
+
+    // A successful probe closes the breaker and resets the backoff
+    b.reset();
+    b.record_failure();
+    std::thread::sleep(Duration::from_millis(1100));
+    assert_eq!(b.before_request(), BreakerState::HalfOpen);
+    b.record_success();
+    let snap = b.snapshot();
+    assert_eq!((snap.state, snap.trips), (BreakerState::Closed, 0));
+}
+
+#[test]
+fn per_provider_overrides_apply_over_defaults() {
+    let cfg: ApiConfig = toml::from_str(
+        r#"
+        breaker_error_threshold = 7
+        breaker_window_secs = 0
+
+        [breaker_overrides.email]
+        window_secs = 30
+        failure_rate_pct = 25
+        slow_call_ms = 2000
+
+        [breaker_overrides.http]
+        open_secs = 5
+        min_requests = 8
+
+        [breaker_overrides."http.messages"]
+        min_requests = 3
+        "#,
+    )
+    .expect("parse config");
+    let sms = cfg.breaker_policy("sms-mms");
+    assert_eq!((sms.failure_threshold, sms.window_secs), (7, 0));
+    let email = cfg.breaker_policy("email");
+    assert_eq!(email.failure_threshold, 7);
+    assert_eq!(
+        (
+            email.window_secs,
+            email.failure_rate_pct,
+            email.slow_call_ms
+        ),
+        (30, 25, 2000)
+    );
+    assert_eq!(
+        email.min_requests,
+        ApiConfig::default().breaker_min_requests
+    );
+    // Route groups layer `http` then `http.<group>`
+    let messages = cfg.breaker_policy("http.messages");
+    assert_eq!((messages.open_secs, messages.min_requests), (5, 3));
+    let webhooks = cfg.breaker_policy("http.webhooks");
+    assert_eq!((webhooks.open_secs, webhooks.min_requests), (5, 8));
+}
@@ -0,0 +1,195 @@
+// Integration test: per-channel content limits (SMS segments, MMS payload, email body) reject
+// oversized sends with 400 and machine-readable details
+use messaging_core::conversations::key::KeyNormalization;
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::types::{EmailRequest, SmsRequest, Validate};
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn sms(kind: &str, body: String, attachments: Option<Vec<String>>) -> SmsRequest {
+    SmsRequest {
+        from: "+15550001111".into(),
+        to: "+15550002222".into(),
+        r#type: kind.into(),
+        body,
+        attachments,
+        timestamp: "2024-11-01T14:00:00Z".into(),
+    }
+}
+
+#[test]
+fn limits_follow_the_config() {
+    let api = ApiConfig {
+        max_sms_segments: 2,
+        max_mms_payload_bytes: 100,
+        max_email_body_bytes: 50,
+        ..ApiConfig::default()
+    };
+    let keys = KeyNormalization::default();
+    assert!(sms("sms", "a".repeat(306), None)
+        .validate(&api, &keys)
+        .is_ok());
+    let err = sms("sms", "a".repeat(307), None)
+        .validate(&api, &keys)
+        .unwrap_err();
+    assert_eq!(
+        err.details,
+        Some(json!({
+            "reason": "limit_exceeded",
+            "field": "body",
+            "limit": "max_sms_segments",
+            "max": 2,
+            "actual": 3
+        }))
+    );
+    // Smart quotes would need UCS-2 (3 segments); transliterated they fit in one
+    let quoted = "\u{201C}".repeat(140);
+    assert!(sms("sms", quoted.clone(), None)
+        .validate(&api, &keys)
+        .is_err());
+    let transliterating = ApiConfig {
+        sms_transliterate: true,
+        ..api.clone()
+    };
+    assert!(sms("sms", quoted, None)
+        .validate(&transliterating, &keys)
+        .is_ok());
+
+    // MMS counts the body and the attachment URLs
+    let url = format!("https://example.com/{}", "x".repeat(60));
+    assert!(sms("mms", "hi".into(), Some(vec![url.clone()]))
+        .validate(&api, &keys)
+        .is_ok());
+    let err = sms("mms", "hi".into(), Some(vec![url.clone(), url]))
+        .validate(&api, &keys)
+        .unwrap_err();
+    assert_eq!(
+        err.details.as_ref().unwrap()["limit"],
+        "max_mms_payload_bytes"
+    );
+    assert_eq!(err.details.as_ref().unwrap()["actual"], 162);
+
+    let email = |body: String| EmailRequest {
+        from: "a@example.com".into(),
+        to: "b@example.com".into(),
+        body,
+        attachments: None,
+        timestamp: "2024-11-01T14:00:00Z".into(),
+    };
+    assert!(email("x".repeat(50)).validate(&api, &keys).is_ok());
+    assert!(email("x".repeat(51)).validate(&api, &keys).is_err());
+
+    // 0 turns a limit off
+    let unlimited = ApiConfig {
+        max_sms_segments: 0,
+        max_email_body_bytes: 0,
+        ..api
+    };
+    assert!(sms("sms", "a".repeat(5000), None)
+        .validate(&unlimited, &keys)
+        .is_ok());
+    assert!(email("x".repeat(5000)).validate(&unlimited, &keys).is_ok());
+}
+
+#[tokio::test]
+async fn oversized_sends_get_error_details() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            max_sms_segments: 1,
+            max_email_body_bytes: 20,
+            max_attachments: 1,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/api/messages/sms"))
+        .json(&json!({
+            "from": "+15550001111",
+            "to": "+15550002222",
+            "type": "sms",
+            "body": "ж".repeat(71),
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("sms");
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["code"], "bad_request");
+    assert_eq!(body["details"]["limit"], "max_sms_segments");
+    assert_eq!(body["details"]["actual"], 2);
+
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&json!({
+            "from": "a@example.com",
+            "to": "b@example.com",
+            "body": "<p>far too long for the limit</p>",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("email");
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["details"]["field"], "body");
+    assert_eq!(body["details"]["max"], 20);
+
+    let resp = client
+        .post(format!("{base}/api/messages/sms"))
+        .json(&json!({
+            "from": "+15550001111",
+            "to": "+15550002222",
+            "type": "mms",
+            "body": "pics",
+            "attachments": ["https://a.example/1", "https://a.example/2"],
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("mms");
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["details"]["limit"], "max_attachments");
+
+    // Required-field errors carry no details
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&json!({
+            "from": "a@example.com",
+            "to": "b@example.com",
+            "body": " ",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("empty");
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.expect("json");
+    assert!(body.get("details").is_none());
+
+    handle.shutdown().await;
+}
@@ -0,0 +1,201 @@
+// Integration test: ApiConfig reloads (admin endpoint, SIGHUP, mock config PUT) swap live
+// settings, reject invalid files and report what changed
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+fn write(path: &PathBuf, contents: &str) {
+    std::fs::write(path, contents).expect("write config file");
+}
+
+#[tokio::test]
+async fn reload_swaps_live_config_and_rejects_invalid() {
+    let path = std::env::temp_dir().join(format!("api-config-{}.toml", uuid::Uuid::new_v4()));
+    write(
+        &path,
+        "rate_limit_per_sender_per_min = 100\nbreaker_error_threshold = 20\n",
+    );
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig::try_load(Some(&path)).expect("initial config"))
+        .config_file(&path)
+        .reload_on_sighup(true)
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let get_json = |p: &str| {
+        let req = client.get(format!("{base}{p}")).send();
+        async move { req.await.expect("get").json::<Value>().await.expect("json") }
+    };
+    let reload = || {
+        client
+            .post(format!("{base}/admin/config/reload"))
+            .json(&json!({}))
+            .send()
+    };
+    let send_email = |n: u32| {
+        client
+            .post(format!("{base}/api/messages/email"))
+            .json(&json!({
+                "from": "reload@example.com",
+                "to": "r@example.com",
+                "body": format!("message {n}"),
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+
+    assert_eq!(
+        get_json("/admin/config").await["breaker_error_threshold"],
+        20
+    );
+
+    // Valid reload: values swap in, the diff is reported
+    write(
+        &path,
+        "rate_limit_per_sender_per_min = 2\nbreaker_error_threshold = 3\nprovider_error_pct = 40\nmax_body_bytes = 1024\n",
+    );
+    let resp = reload().await.expect("reload");
+    assert_eq!(resp.status(), 200);
+    let report: Value = resp.json().await.expect("json");
+    assert_eq!(report["trigger"], "admin");
+    let keys: Vec<&str> = report["changes"]
+        .as_array()
+        .expect("changes")
+        .iter()
+        .map(|c| c["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "breaker_error_threshold",
+            "max_body_bytes",
+            "provider_error_pct",
+            "rate_limit_per_sender_per_min"
+        ]
+    );
+    assert_eq!(report["changes"][0]["from"], 20);
+    assert_eq!(report["changes"][0]["to"], 3);
+    assert_eq!(report["restart_required"], json!(["max_body_bytes"]));
+
+    // ... and reach the running components
+    assert_eq!(send_email(1).await.expect("send").status(), 202);
+    assert_eq!(send_email(2).await.expect("send").status(), 202);
+    assert_eq!(send_email(3).await.expect("send").status(), 429);
+    let email = get_json("/admin/breakers/email").await;
+    assert_eq!(email["policy"]["failure_threshold"], 3);
+    assert_eq!(get_json("/api/provider/mock/config").await["error_pct"], 40);
+
+    // Invalid or unparsable files are rejected and the current config stays
+    write(
+        &path,
+        "provider_error_pct = 80\nprovider_timeout_pct = 50\n",
+    );
+    let resp = reload().await.expect("reload");
+    assert_eq!(resp.status(), 422);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["code"], "invalid_config");
+    assert!(body["details"]["errors"][0]
+        .as_str()
+        .unwrap()
+        .contains("exceed 100"));
+    write(&path, "provider_error_pct = \"lots\"\n");
+    assert_eq!(reload().await.expect("reload").status(), 422);
+    assert_eq!(get_json("/admin/config").await["provider_error_pct"], 40);
+
+    // The mock config endpoint edits the same live config
+    let resp = client
+        .put(format!("{base}/api/provider/mock/config"))
+        .json(&json!({"timeout_pct": 10, "error_pct": 5, "ratelimit_pct": 0, "seed": 42}))
+        .send()
+        .await
+        .expect("put");
+    assert_eq!(resp.status(), 200);
+    let live = get_json("/admin/config").await;
+    assert_eq!(
+        (
+            &live["provider_timeout_pct"],
+            &live["provider_error_pct"],
+            &live["provider_seed"]
+        ),
+        (&json!(10), &json!(5), &json!(42))
+    );
+    let resp = client
+        .put(format!("{base}/api/provider/mock/config"))
+        .json(&json!({"timeout_pct": 90, "error_pct": 90, "ratelimit_pct": 0, "seed": null}))
+        .send()
+        .await
+        .expect("put");
+    assert_eq!(resp.status(), 422);
+
+    // A file reload keeps the per-provider overrides only a PUT can set
+    let resp = client
+        .put(format!("{base}/api/provider/mock/config"))
+        .json(&json!({
+            "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+            "providers": {"sms-mms": {"error_pct": 30, "seed": 9}}
+        }))
+        .send()
+        .await
+        .expect("put");
+    assert_eq!(resp.status(), 200);
+    write(&path, "worker_batch_size = 5\n");
+    assert_eq!(reload().await.expect("reload").status(), 200);
+    let mock = get_json("/api/provider/mock/config").await;
+    assert_eq!(mock["providers"]["sms-mms"]["error_pct"], 30);
+    assert_eq!(mock["providers"]["sms-mms"]["seed"], 9);
+    assert_eq!(get_json("/admin/config").await["worker_batch_size"], 5);
+
+    // SIGHUP reloads too
+    write(&path, "worker_batch_size = 7\n");
+    let status = std::process::Command::new("kill")
+        .args(["-HUP", &std::process::id().to_string()])
+        .status()
+        .expect("kill -HUP");
+    assert!(status.success());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while get_json("/admin/config").await["worker_batch_size"] != 7 {
+        assert!(Instant::now() < deadline, "SIGHUP did not reload");
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    handle.shutdown().await;
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn missing_keys_default_but_unknown_keys_are_rejected() {
+    let partial: ApiConfig = toml::from_str("worker_concurrency = 2\n").expect("partial config");
+    assert_eq!(partial.worker_concurrency, 2);
+    assert_eq!(
+        partial.worker_batch_size,
+        ApiConfig::default().worker_batch_size
+    );
+    let err = toml::from_str::<ApiConfig>("worker_concurency = 2\n").unwrap_err();
+    assert!(err.to_string().contains("worker_concurency"), "{err}");
+    let shipped =
+        std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/config/default.toml"))
+            .expect("default.toml");
+    toml::from_str::<ApiConfig>(&shipped).expect("default.toml has only known keys");
+}
@@ -0,0 +1,189 @@
+// Integration test: the request's x-request-id follows a send into error bodies, the outbound
+// worker's provider call and (when DATABASE_URL is reachable) inbound_events rows
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::correlation::CorrelationId;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{
+    ChannelKind, DispatchResult, OutboundMessage, Provider,
+};
+use messaging_server::ServerBuilder;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+
+/// Records the correlation ID visible while the outbound worker dispatches.
+struct RecordingProvider(Arc<Mutex<Vec<Option<CorrelationId>>>>);
+impl Provider for RecordingProvider {
+    fn name(&self) -> &str {
+        "recording-email"
+    }
+    fn dispatch(&self, _msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        self.0.lock().unwrap().push(CorrelationId::current());
+        DispatchResult {
+            provider_name: self.name().to_string(),
+            outcome: Outcome::Success,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+fn test_config() -> Arc<Config> {
+    Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    })
+}
+
+fn request_id(resp: &reqwest::Response) -> String {
+    resp.headers()
+        .get("x-request-id")
+        .and_then(|v| v.to_str().ok())
+        .expect("x-request-id echoed")
+        .to_string()
+}
+
+#[tokio::test]
+async fn correlation_id_reaches_errors_and_provider_dispatch() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = ServerBuilder::new(test_config())
+        .provider(
+            ChannelKind::Email,
+            Arc::new(RecordingProvider(seen.clone())),
+        )
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let email = |body: &str| {
+        serde_json::json!({
+            "from": "corr@example.com",
+            "to": "c@example.com",
+            "body": body,
+            "timestamp": "2024-11-01T14:00:00Z"
+        })
+    };
+
+    // Accepted send: the provider runs under the caller's ID
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .header("x-request-id", "corr-send-1")
+        .json(&email("hello"))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+    assert_eq!(request_id(&resp), "corr-send-1");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while seen.lock().unwrap().is_empty() {
+        assert!(Instant::now() < deadline, "provider never dispatched");
+        sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(
+        seen.lock().unwrap()[0].as_ref().map(CorrelationId::as_str),
+        Some("corr-send-1")
+    );
+
+    // Rejected send: the error body names the same ID as the response header
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .header("x-request-id", "corr-bad-1")
+        .json(&email(""))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = resp.json().await.expect("json");
+    assert_eq!(body["correlation_id"], "corr-bad-1");
+
+    // Unusable header values are replaced by a generated ID, which errors still report
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .header("x-request-id", "x".repeat(200))
+        .json(&email(""))
+        .send()
+        .await
+        .expect("send");
+    let generated = request_id(&resp);
+    assert_eq!(generated.len(), 36, "expected a UUID, got {generated}");
+    let body: serde_json::Value = resp.json().await.expect("json");
+    assert_eq!(body["correlation_id"], generated.as_str());
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn correlation_id_is_stored_on_inbound_events() {
+    let url = match std::env::var("DATABASE_URL") {
+        Ok(u) => u,
+        Err(_) => {
+            eprintln!(
+                "[correlation_id_is_stored_on_inbound_events] Skipping: DATABASE_URL not set"
+            );
+            return;
+        }
+    };
+    let pool = match PgPoolOptions::new().max_connections(3).connect(&url).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!(
+                "[correlation_id_is_stored_on_inbound_events] Skipping: cannot connect ({e:?})"
+            );
+            return;
+        }
+    };
+    let handle = ServerBuilder::new(test_config())
+        .pool(pool.clone())
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+
+    let provider_id = format!("corr-{}", uuid::Uuid::new_v4());
+    let resp = reqwest::Client::new()
+        .post(format!("{base}/api/webhooks/sms"))
+        .header("x-request-id", "corr-webhook-1")
+        .json(&serde_json::json!({
+            "from": "+15550008001",
+            "to": "+15550008002",
+            "type": "sms",
+            "messaging_provider_id": provider_id,
+            "body": "correlated webhook",
+            "attachments": null,
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("webhook");
+    assert_eq!(resp.status(), 202);
+
+    let stored: Option<String> = sqlx::query_scalar(
+        "SELECT correlation_id FROM inbound_events WHERE provider_message_id=$1",
+    )
+    .bind(&provider_id)
+    .fetch_one(&pool)
+    .await
+    .expect("stored event");
+    assert_eq!(stored.as_deref(), Some("corr-webhook-1"));
+
+    handle.shutdown().await;
+    sqlx::query("DELETE FROM inbound_events WHERE provider_message_id=$1")
+        .bind(&provider_id)
+        .execute(&pool)
+        .await
+        .expect("cleanup");
+}
@@ -0,0 +1,157 @@
+// Integration test: dead-letter admin endpoints (list, show, requeue, purge)
+use messaging_core::Config;
+use messaging_server::ServerBuilder;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+async fn insert_dead(pool: &PgPool, tag: &str) -> i64 {
+    sqlx::query_scalar(
+        r#"INSERT INTO inbound_events (event_type, payload, available_at, status, channel, "from", "to",
+                provider_message_id, attempts, error_code, error_message)
+            VALUES ('sms', '{"body":"dead"}', now(), 'dead', 'sms', '+15550005555', '+15550006666',
+                $1, 9, 'process_error', 'boom')
+            RETURNING id"#,
+    )
+    .bind(tag)
+    .fetch_one(pool)
+    .await
+    .expect("insert dead event")
+}
+
+#[tokio::test]
+async fn dead_letters_list_show_requeue_purge() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("[dead_letters] Skipping: DATABASE_URL not set");
+        return;
+    };
+    let pool = match PgPoolOptions::new().max_connections(3).connect(&url).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[dead_letters] Skipping: cannot connect to DATABASE_URL ({e:?})");
+            return;
+        }
+    };
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .pool(pool.clone())
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let tag = uuid::Uuid::new_v4().to_string();
+    let requeue_id = insert_dead(&pool, &format!("dl-requeue-{tag}")).await;
+    let purge_id = insert_dead(&pool, &format!("dl-purge-{tag}")).await;
+
+    // List includes both with error details
+    let list: serde_json::Value = client
+        .get(format!("{base}/admin/dead-letters?pageSize=50"))
+        .send()
+        .await
+        .expect("list")
+        .json()
+        .await
+        .expect("list json");
+    assert!(list["meta"]["total"].as_u64().unwrap_or(0) >= 2);
+    // The largest page number is past the end, not an overflow
+    let resp = client
+        .get(format!("{base}/admin/dead-letters?page={}", u32::MAX))
+        .send()
+        .await
+        .expect("last page");
+    assert_eq!(resp.status(), 200);
+    let last: serde_json::Value = resp.json().await.expect("last page json");
+    assert_eq!(last["items"].as_array().map(Vec::len), Some(0));
+    let listed = list["items"]
+        .as_array()
+        .expect("items")
+        .iter()
+        .find(|i| i["id"].as_str() == Some(purge_id.to_string().as_str()))
+        .expect("purge candidate listed")
+        .clone();
+    assert_eq!(listed["error_code"], "process_error");
+    assert!(listed.get("payload").is_none(), "list omits payload");
+
+    // Show returns the payload
+    let shown: serde_json::Value = client
+        .get(format!("{base}/admin/dead-letters/{purge_id}"))
+        .send()
+        .await
+        .expect("show")
+        .json()
+        .await
+        .expect("show json");
+    assert_eq!(shown["payload"]["body"], "dead");
+    assert_eq!(shown["error_message"], "boom");
+
+    // Requeue one: back to pending with a fresh retry budget
+    let resp = client
+        .post(format!("{base}/admin/dead-letters/requeue"))
+        .json(&serde_json::json!({"ids": [requeue_id]}))
+        .send()
+        .await
+        .expect("requeue");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.json::<serde_json::Value>().await.expect("json")["requeued"],
+        1
+    );
+    let (status, attempts): (String, i32) =
+        sqlx::query_as("SELECT status, attempts FROM inbound_events WHERE id=$1")
+            .bind(requeue_id)
+            .fetch_one(&pool)
+            .await
+            .expect("requeued row");
+    assert_ne!(status, "dead");
+    assert!(
+        attempts <= 1,
+        "attempts reset (worker may have retried once)"
+    );
+
+    // Purge one; it no longer resolves
+    let resp = client
+        .delete(format!("{base}/admin/dead-letters/{purge_id}"))
+        .send()
+        .await
+        .expect("purge");
+    assert_eq!(resp.status(), 200);
+    let resp = client
+        .get(format!("{base}/admin/dead-letters/{purge_id}"))
+        .send()
+        .await
+        .expect("show purged");
+    assert_eq!(resp.status(), 404);
+
+    // Bulk selection must be explicit
+    let resp = client
+        .post(format!("{base}/admin/dead-letters/purge"))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .expect("purge empty");
+    assert_eq!(resp.status(), 400);
+
+    sqlx::query("DELETE FROM inbound_events WHERE id = ANY($1)")
+        .bind(vec![requeue_id, purge_id])
+        .execute(&pool)
+        .await
+        .ok();
+    handle.shutdown().await;
+}
@@ -1,10 +1,10 @@
 // Feature 008 - US3 Deterministic unit test (T031)
 // Verifies that with a fixed seed, predicted outcome distribution matches actual rolls.
 use messaging_server::config::ApiConfig;
-use messaging_server::providers::common::{
-    init_rng_seeds, pick_outcome_for_provider, predict_outcomes_from_seed, seed_provider_rng,
-};
+use messaging_server::providers::common::{predict_outcomes_from_seed, ProviderRng};
 use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{ChannelKind, OutboundMessage, Provider};
+use messaging_server::providers::sms_mms::SmsMmsMockProvider;
 
 #[test]
 fn sms_provider_deterministic_sequence_matches_prediction() {
@@ -16,8 +16,8 @@ fn sms_provider_deterministic_sequence_matches_prediction() {
         provider_sms_seed: Some(12345),
         ..Default::default()
     };
-    init_rng_seeds(&cfg);
-    seed_provider_rng("sms-mms", 12345);
+    // A fresh RNG seeds itself from the configured seed on its first draw
+    let rng = ProviderRng::new();
 
     // Predict outcomes for first N rolls from the seed
     let n = 50usize;
@@ -28,7 +28,7 @@ fn sms_provider_deterministic_sequence_matches_prediction() {
     let mut err_a = 0u32;
     let mut to_a = 0u32;
     for _ in 0..n {
-        let (outcome, _roll) = pick_outcome_for_provider("sms-mms", &cfg);
+        let (outcome, _roll) = rng.pick_outcome("sms-mms", &cfg);
         match outcome {
             Outcome::Success => succ_a += 1,
             Outcome::RateLimited => rate_a += 1,
@@ -41,3 +41,46 @@ fn sms_provider_deterministic_sequence_matches_prediction() {
     assert_eq!(err_a, err_p, "error count should match prediction");
     assert_eq!(to_a, to_p, "timeout count should match prediction");
 }
+
+#[test]
+fn provider_instances_keep_their_own_sequences() {
+    let cfg = ApiConfig {
+        provider_error_pct: 30,
+        provider_ratelimit_pct: 20,
+        provider_seed: Some(777),
+        ..Default::default()
+    };
+    let msg = OutboundMessage {
+        channel: ChannelKind::Sms,
+        to: "+15550001111".into(),
+        from: "+15550002222".into(),
+        body: "seq".into(),
+        attachments: vec![],
+        idempotency_key: None,
+    };
+    // Interleaved dispatches through two instances (two registries) do not disturb each other
+    let (a, b) = (SmsMmsMockProvider::new(), SmsMmsMockProvider::new());
+    let mut seq_a = Vec::new();
+    let mut seq_b = Vec::new();
+    for _ in 0..30 {
+        seq_a.push(a.dispatch(&msg, &cfg).outcome);
+        seq_b.push(b.dispatch(&msg, &cfg).outcome);
+        seq_b.push(b.dispatch(&msg, &cfg).outcome);
+    }
+    assert_eq!(seq_a[..], seq_b[..30]);
+    let rng = ProviderRng::seeded(777);
+    let expected: Vec<_> = (0..60)
+        .map(|_| rng.pick_outcome("sms-mms", &cfg).0)
+        .collect();
+    assert_eq!(seq_b, expected);
+
+    // Reset restarts the sequence from the configured seed
+    a.reset();
+    assert_eq!(a.dispatch(&msg, &cfg).outcome, expected[0]);
+
+    // Unknown provider names no longer share a stream: each RNG is its own
+    let (x, y) = (ProviderRng::seeded(5), ProviderRng::seeded(5));
+    let rolls_x: Vec<_> = (0..10).map(|_| x.pick_outcome("voice", &cfg).1).collect();
+    let rolls_y: Vec<_> = (0..10).map(|_| y.pick_outcome("fax", &cfg).1).collect();
+    assert_eq!(rolls_x, rolls_y);
+}
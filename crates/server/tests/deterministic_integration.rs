@@ -52,8 +52,8 @@ async fn run_sequence_collect() -> Vec<String> {
     for i in 0..25 {
         // 50 total events (sms+email interleaved) processed by worker
         let sms_body = serde_json::json!({
-            "from": "+1555SMS",
-            "to": format!("+1555TO{}", i),
+            "from": "+15550009999",
+            "to": format!("+1555001{:04}", i),
             "type": "sms",
             "body": format!("msg {}", i),
             "timestamp": chrono::Utc::now().to_rfc3339(),
@@ -27,8 +27,8 @@ async fn sms_and_email_route_to_distinct_providers() {
 
     // Send SMS
     let sms_body = serde_json::json!({
-        "from": "+15550001",
-        "to": "+15550002",
+        "from": "+15550001001",
+        "to": "+15550002002",
         "type": "sms",
         "body": "hello sms",
         "timestamp": chrono::Utc::now().to_rfc3339(),
@@ -0,0 +1,112 @@
+// Integration test: email addresses in conversation keys follow the per-domain rules from
+// `email_domain_rules`, merged over the built-in Gmail rules
+use messaging_core::conversations::normalize_email::normalize_with;
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::store_db::normalize::conversation_key;
+use messaging_server::ServerBuilder;
+use serde_json::json;
+use std::sync::Arc;
+
+const RULES: &str = r#"
+[email_domain_rules."Corp.example"]
+case_sensitive = true
+strip_plus_tags = false
+
+[email_domain_rules."*"]
+strip_plus_tags = false
+"#;
+
+#[test]
+fn configured_rules_merge_over_builtins() {
+    let api: ApiConfig = toml::from_str(RULES).expect("toml");
+    assert!(api.validate().is_ok());
+    let rules = api.email_rules();
+    let norm = |raw| normalize_with(raw, &rules).expect("parse");
+    assert_eq!(
+        norm("\"Ops\" <Ops+Alerts@CORP.example>"),
+        "Ops+Alerts@corp.example"
+    );
+    assert_eq!(norm("Bob+list@Other.org"), "bob+list@other.org");
+    // Gmail keeps its built-in rule
+    assert_eq!(norm("B.o.b+list@gmail.com"), "bob@gmail.com");
+    // Defaults strip plus-tags everywhere
+    let defaults = ApiConfig::default().email_rules();
+    assert_eq!(
+        normalize_with("Bob+list@Other.org", &defaults).unwrap(),
+        "bob@other.org"
+    );
+
+    let bad: ApiConfig = toml::from_str(
+        r#"
+[email_domain_rules."not a domain"]
+ignore_dots = true
+"#,
+    )
+    .expect("toml");
+    let errors = bad.validate().unwrap_err();
+    assert!(errors.iter().any(|e| e.contains("email_domain_rules")));
+}
+
+#[tokio::test]
+async fn conversation_keys_use_the_configured_rules() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let api: ApiConfig = toml::from_str(RULES).expect("toml");
+    let keys = api.key_normalization();
+    let handle = ServerBuilder::new(cfg)
+        .api_config(api)
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+
+    assert_eq!(
+        conversation_key(
+            "email",
+            "\"Bob\" <b.o.b+x@GMail.com>",
+            "Ops@corp.example",
+            &keys
+        ),
+        conversation_key("email", "bob@gmail.com", "Ops@Corp.Example", &keys)
+    );
+    assert_ne!(
+        conversation_key("email", "bob@gmail.com", "Ops@corp.example", &keys),
+        conversation_key("email", "bob@gmail.com", "ops@corp.example", &keys)
+    );
+    // Keys of a server with the default rules lowercase every local part
+    let defaults = ApiConfig::default().key_normalization();
+    assert_eq!(
+        conversation_key("email", "bob@gmail.com", "Ops@corp.example", &defaults),
+        conversation_key("email", "bob@gmail.com", "ops@corp.example", &defaults)
+    );
+
+    let resp = reqwest::Client::new()
+        .post(format!("{base}/api/messages/email"))
+        .json(&json!({
+            "from": "\"Bob\" <bob+test@gmail.com>",
+            "to": "Ops@corp.example",
+            "body": "hello",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+
+    handle.shutdown().await;
+}
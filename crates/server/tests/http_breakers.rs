@@ -0,0 +1,122 @@
+// Integration test: HTTP breakers are per route group, and only statuses classified as failures
+// trip them
+use axum::http::StatusCode;
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::state::breakers::{HttpBreakers, HttpVerdict};
+use messaging_server::ServerBuilder;
+use serde_json::json;
+use std::sync::Arc;
+
+#[test]
+fn classification_follows_config() {
+    let breakers = HttpBreakers::from_config(&ApiConfig {
+        http_breaker_failure_statuses: vec!["5xx".into(), "429".into(), "bogus".into()],
+        http_breaker_ignored_statuses: vec![501],
+        ..ApiConfig::default()
+    });
+    assert_eq!(breakers.classify(200), HttpVerdict::Success);
+    assert_eq!(breakers.classify(202), HttpVerdict::Success);
+    assert_eq!(breakers.classify(500), HttpVerdict::Failure);
+    assert_eq!(breakers.classify(503), HttpVerdict::Failure);
+    assert_eq!(breakers.classify(429), HttpVerdict::Failure);
+    assert_eq!(breakers.classify(501), HttpVerdict::Neutral);
+    assert_eq!(breakers.classify(400), HttpVerdict::Neutral);
+
+    assert_eq!(HttpBreakers::group_for("/api/messages/sms"), "messages");
+    assert_eq!(
+        HttpBreakers::group_for("/api/conversations/1/messages"),
+        "conversations"
+    );
+    assert_eq!(HttpBreakers::group_for("/extra"), "other");
+}
+
+#[tokio::test]
+async fn failing_route_group_does_not_block_others() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            breaker_error_threshold: 2,
+            ..ApiConfig::default()
+        })
+        .routes(
+            axum::Router::new()
+                .route(
+                    "/boom",
+                    axum::routing::get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+                )
+                .route(
+                    "/unsupported",
+                    axum::routing::get(|| async { StatusCode::NOT_IMPLEMENTED }),
+                ),
+        )
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let get = |path: &str| client.get(format!("{base}{path}")).send();
+
+    // Client-induced 5xx (501 by default) never trip the breaker
+    for _ in 0..5 {
+        assert_eq!(get("/unsupported").await.expect("501").status(), 501);
+    }
+    assert_eq!(get("/boom").await.expect("boom").status(), 500);
+    assert_eq!(get("/boom").await.expect("boom").status(), 500);
+
+    // The `other` group is now open ...
+    let resp = get("/boom").await.expect("boom");
+    assert_eq!(resp.status(), 503);
+    assert!(resp.headers().contains_key("retry-after"));
+    assert_eq!(get("/unsupported").await.expect("501").status(), 503);
+
+    // ... while the messages group keeps serving
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&json!({
+            "from": "groups@example.com",
+            "to": "g@example.com",
+            "body": "still up",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+
+    let body: serde_json::Value = client
+        .get(format!("{base}/admin/breakers/http.other"))
+        .send()
+        .await
+        .expect("show")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(body["state"], "open");
+    let body: serde_json::Value = client
+        .get(format!("{base}/admin/breakers/http.messages"))
+        .send()
+        .await
+        .expect("show")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(body["state"], "closed");
+
+    handle.shutdown().await;
+}
@@ -0,0 +1,92 @@
+// Integration test: HTTP and provider dispatch latency histograms with configured buckets
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::ServerBuilder;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+async fn metrics_text(client: &reqwest::Client, base: &str) -> String {
+    client
+        .get(format!("{base}/metrics"))
+        .header("Accept", "text/plain")
+        .send()
+        .await
+        .expect("metrics")
+        .text()
+        .await
+        .expect("metrics text")
+}
+
+#[tokio::test]
+async fn histograms_use_route_templates_and_configured_buckets() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            // Bounds must be strictly increasing; build() rejects anything else
+            http_latency_buckets: vec![0.25, 7.0],
+            dispatch_latency_buckets: vec![3.0],
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .get(format!("{base}/api/conversations/42/messages"))
+        .send()
+        .await
+        .expect("messages");
+    let status = resp.status().as_u16();
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&serde_json::json!({
+            "from": "latency@example.com",
+            "to": "l@example.com",
+            "body": "timed",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let text = loop {
+        let text = metrics_text(&client, &base).await;
+        if text.contains("messaging_provider_dispatch_duration_seconds_count") {
+            break text;
+        }
+        assert!(Instant::now() < deadline, "dispatch never observed");
+        sleep(Duration::from_millis(20)).await;
+    };
+
+    // Route template, not the raw path, with the configured bounds
+    let route_series = format!(
+        r#"messaging_http_request_duration_seconds_bucket{{method="GET",route="/api/conversations/{{id}}/messages",status="{status}",le="7"}} 1"#
+    );
+    assert!(text.contains(&route_series), "missing {route_series}");
+    assert!(text.contains(r#"route="/api/conversations/{id}/messages",status="#));
+    assert!(!text.contains("/api/conversations/42/messages"));
+    assert!(text.contains(r#"le="0.25""#));
+    assert!(text.contains(
+        r#"messaging_provider_dispatch_duration_seconds_bucket{outcome="success",provider="email",le="3"} 1"#
+    ));
+    handle.shutdown().await;
+}
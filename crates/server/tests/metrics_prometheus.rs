@@ -0,0 +1,122 @@
+// Integration test: /metrics content negotiation (Prometheus text vs JSON) and provider labels
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{
+    ChannelKind, DispatchResult, OutboundMessage, Provider,
+};
+use messaging_server::ServerBuilder;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+struct AcmeProvider;
+impl Provider for AcmeProvider {
+    fn name(&self) -> &str {
+        "acme-email"
+    }
+    fn dispatch(&self, _msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        DispatchResult {
+            provider_name: self.name().to_string(),
+            outcome: Outcome::Success,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+const PROMETHEUS_ACCEPT: &str =
+    "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5,*/*;q=0.1";
+
+#[tokio::test]
+async fn metrics_negotiates_prometheus_text_and_labels_new_providers() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .provider(ChannelKind::Email, Arc::new(AcmeProvider))
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&serde_json::json!({
+            "from": "metrics@example.com",
+            "to": "m@example.com",
+            "body": "hello",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+
+    // JSON stays the default and gains a per-provider map keyed by provider name
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let json: serde_json::Value = client
+            .get(format!("{base}/metrics"))
+            .send()
+            .await
+            .expect("metrics json")
+            .json()
+            .await
+            .expect("json body");
+        if json["providers"]["acme-email"]["success"].as_u64() == Some(1) {
+            break;
+        }
+        assert!(Instant::now() < deadline, "acme-email never dispatched");
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    // A Prometheus scraper's Accept header gets the text exposition
+    let text_resp = client
+        .get(format!("{base}/metrics"))
+        .header("Accept", PROMETHEUS_ACCEPT)
+        .send()
+        .await
+        .expect("metrics text");
+    assert_eq!(text_resp.status(), 200);
+    let content_type = text_resp
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    assert!(content_type.starts_with("text/plain"), "{content_type}");
+    let text = text_resp.text().await.expect("text body");
+    assert!(text.contains(r#"messaging_provider_dispatch_attempts_total{provider="acme-email"} 1"#));
+    assert!(text.contains(
+        r#"messaging_provider_dispatch_results_total{outcome="success",provider="acme-email"} 1"#
+    ));
+    assert!(text.contains("# TYPE messaging_worker_processing_seconds histogram"));
+    assert!(text.contains("messaging_queue_outbound_capacity 1024"));
+
+    // Explicit JSON still wins
+    let json_resp = client
+        .get(format!("{base}/metrics"))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .expect("metrics json accept");
+    assert!(json_resp
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json")));
+    handle.shutdown().await;
+}
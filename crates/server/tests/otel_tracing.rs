@@ -0,0 +1,178 @@
+// Integration test: OTLP export to a collector stand-in, with W3C traceparent continued across
+// the outbound queue and (when DATABASE_URL is reachable) the inbound_events table
+use axum::{extract::State, routing::post, Json, Router};
+use messaging_core::Config;
+use messaging_server::telemetry::{self, OtlpProtocol, TelemetryConfig};
+use messaging_server::ServerBuilder;
+use sqlx::postgres::PgPoolOptions;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+
+const API_TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const WEBHOOK_TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+
+type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+
+async fn collect(
+    State(received): State<Received>,
+    Json(body): Json<serde_json::Value>,
+) -> Json<serde_json::Value> {
+    received.lock().unwrap().push(body);
+    Json(serde_json::json!({}))
+}
+
+/// Span names exported so far for `trace_id`.
+fn span_names(received: &Received, trace_id: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for req in received.lock().unwrap().iter() {
+        for rs in req["resourceSpans"].as_array().into_iter().flatten() {
+            for ss in rs["scopeSpans"].as_array().into_iter().flatten() {
+                for span in ss["spans"].as_array().into_iter().flatten() {
+                    if span["traceId"].as_str() == Some(trace_id) {
+                        names.push(span["name"].as_str().unwrap_or_default().to_string());
+                    }
+                }
+            }
+        }
+    }
+    names
+}
+
+async fn wait_for_span(
+    guard: &telemetry::TelemetryGuard,
+    received: &Received,
+    trace_id: &str,
+    name: &str,
+) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        tokio::task::block_in_place(|| guard.force_flush());
+        if span_names(received, trace_id).iter().any(|n| n == name) {
+            return;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "span {name} not exported for trace {trace_id}; got {:?}",
+            span_names(received, trace_id)
+        );
+        sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn traceparent_is_continued_across_queues_and_exported() {
+    // Collector stand-in: accepts OTLP/HTTP JSON on /v1/traces
+    let received: Received = Arc::default();
+    let collector = Router::new()
+        .route("/v1/traces", post(collect))
+        .with_state(received.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let collector_addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, collector).await });
+
+    let guard = telemetry::init(
+        "info",
+        &TelemetryConfig {
+            otlp_endpoint: Some(format!("http://{collector_addr}/v1/traces")),
+            protocol: OtlpProtocol::HttpJson,
+            service_name: "messaging-test".into(),
+        },
+    )
+    .expect("telemetry init");
+    assert!(guard.exporting());
+
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let pool = match std::env::var("DATABASE_URL") {
+        Ok(url) => PgPoolOptions::new()
+            .max_connections(3)
+            .connect(&url)
+            .await
+            .map_err(|e| eprintln!("[otel_tracing] Skipping DB leg: cannot connect ({e:?})"))
+            .ok(),
+        Err(_) => {
+            eprintln!("[otel_tracing] Skipping DB leg: DATABASE_URL not set");
+            None
+        }
+    };
+    let mut builder = ServerBuilder::new(cfg);
+    if let Some(p) = &pool {
+        builder = builder.pool(p.clone());
+    }
+    let handle = builder.build().await.expect("build").start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    // API send: request span and the outbound worker's dispatch span share the caller's trace
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .header(
+            "traceparent",
+            format!("00-{API_TRACE_ID}-00f067aa0ba902b7-01"),
+        )
+        .json(&serde_json::json!({
+            "from": "otel@example.com",
+            "to": "o@example.com",
+            "body": "traced",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert_eq!(resp.status(), 202);
+    wait_for_span(&guard, &received, API_TRACE_ID, "http_request").await;
+    wait_for_span(&guard, &received, API_TRACE_ID, "dispatch_event").await;
+
+    // Webhook with a DB: the trace context rides in inbound_events.payload to the worker
+    if let Some(pool) = &pool {
+        let provider_id = format!("otel-{}", uuid::Uuid::new_v4());
+        let resp = client
+            .post(format!("{base}/api/webhooks/sms"))
+            .header(
+                "traceparent",
+                format!("00-{WEBHOOK_TRACE_ID}-b7ad6b7169203331-01"),
+            )
+            .json(&serde_json::json!({
+                "from": "+15550007001",
+                "to": "+15550007002",
+                "type": "sms",
+                "messaging_provider_id": provider_id,
+                "body": "traced webhook",
+                "attachments": null,
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+            .await
+            .expect("webhook");
+        assert_eq!(resp.status(), 202);
+        let traceparent: Option<String> = sqlx::query_scalar(
+            "SELECT payload->'trace_context'->>'traceparent' FROM inbound_events WHERE provider_message_id=$1",
+        )
+        .bind(&provider_id)
+        .fetch_one(pool)
+        .await
+        .expect("stored event");
+        assert!(traceparent.unwrap_or_default().contains(WEBHOOK_TRACE_ID));
+        wait_for_span(&guard, &received, WEBHOOK_TRACE_ID, "process_inbound_event").await;
+        sqlx::query("DELETE FROM inbound_events WHERE provider_message_id=$1")
+            .bind(&provider_id)
+            .execute(pool)
+            .await
+            .expect("cleanup");
+    }
+
+    handle.shutdown().await;
+}
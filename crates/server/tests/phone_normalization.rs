@@ -0,0 +1,217 @@
+// Integration test: SMS numbers are validated and normalized to E.164 in the configured default
+// region, so national and international spellings share a conversation key
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::store_db::normalize::conversation_key;
+use messaging_server::types::{SmsRequest, Validate};
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn sms(from: &str, to: &str) -> SmsRequest {
+    SmsRequest {
+        from: from.into(),
+        to: to.into(),
+        r#type: "sms".into(),
+        body: "hi".into(),
+        attachments: None,
+        timestamp: "2024-11-01T14:00:00Z".into(),
+    }
+}
+
+#[test]
+fn numbers_are_checked_in_the_configured_region() {
+    let us = ApiConfig::default();
+    let us_keys = us.key_normalization();
+    assert!(sms("(555) 000-1234", "+44 20 7946 0958")
+        .validate(&us, &us_keys)
+        .is_ok());
+    let err = sms("+15550001234", "+1555000123")
+        .validate(&us, &us_keys)
+        .unwrap_err();
+    assert_eq!(
+        err.details,
+        Some(json!({
+            "reason": "invalid_phone",
+            "field": "to",
+            "error": "invalid_length"
+        }))
+    );
+    let err = sms("+1555SMS", "+15550001234")
+        .validate(&us, &us_keys)
+        .unwrap_err();
+    assert_eq!(err.details.as_ref().unwrap()["field"], "from");
+    assert_eq!(err.details.as_ref().unwrap()["error"], "invalid_characters");
+
+    // A GB national number is only valid once GB is the default region
+    let gb = ApiConfig {
+        phone_default_region: "GB".into(),
+        ..ApiConfig::default()
+    };
+    let gb_keys = gb.key_normalization();
+    assert!(sms("020 7946 0958", "+15550001234")
+        .validate(&us, &us_keys)
+        .is_err());
+    assert!(sms("020 7946 0958", "+15550001234")
+        .validate(&gb, &gb_keys)
+        .is_ok());
+
+    // Both spellings give the same conversation key, and a GB national number only matches
+    // its international spelling in a GB server's keys
+    assert_eq!(
+        conversation_key("sms", "555.000.4321", "1 (555) 000-8765", &us_keys),
+        conversation_key("sms", "+1 555 000 4321", "+15550008765", &us_keys)
+    );
+    assert_eq!(
+        conversation_key("sms", "020 7946 0958", "+15550008765", &gb_keys),
+        conversation_key("sms", "+44 20 7946 0958", "+15550008765", &gb_keys)
+    );
+    assert_ne!(
+        conversation_key("sms", "020 7946 0958", "+15550008765", &us_keys),
+        conversation_key("sms", "020 7946 0958", "+15550008765", &gb_keys)
+    );
+
+    let unknown = ApiConfig {
+        phone_default_region: "XX".into(),
+        ..ApiConfig::default()
+    };
+    assert!(unknown.validate().is_err());
+}
+
+#[tokio::test]
+async fn sends_accept_national_numbers_and_reject_invalid_ones() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig::default())
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let send = |from: &str, to: &str| {
+        client
+            .post(format!("{base}/api/messages/sms"))
+            .json(&json!({
+                "from": from,
+                "to": to,
+                "type": "sms",
+                "body": "hello",
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+
+    assert_eq!(
+        send("+1 555 000 4321", "+15550008765")
+            .await
+            .expect("send")
+            .status(),
+        202
+    );
+    assert_eq!(
+        send("555.000.4321", "1 (555) 000-8765")
+            .await
+            .expect("send")
+            .status(),
+        202
+    );
+
+    let resp = send("+15550004321", "12345").await.expect("send");
+    assert_eq!(resp.status(), 400);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["code"], "bad_request");
+    assert_eq!(body["details"]["reason"], "invalid_phone");
+    assert_eq!(body["details"]["field"], "to");
+
+    let resp = client
+        .post(format!("{base}/api/webhooks/sms"))
+        .json(&json!({
+            "from": "not a number",
+            "to": "+15550004321",
+            "type": "sms",
+            "messaging_provider_id": "m-1",
+            "body": "hi",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("webhook");
+    assert_eq!(resp.status(), 400);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn reloaded_region_waits_for_a_restart() {
+    let path = std::env::temp_dir().join(format!("api-config-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(&path, "phone_default_region = \"US\"\n").expect("write config file");
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig::try_load(Some(&path)).expect("initial config"))
+        .config_file(&path)
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let send = |from: &str| {
+        client
+            .post(format!("{base}/api/messages/sms"))
+            .json(&json!({
+                "from": from,
+                "to": "+15550008765",
+                "type": "sms",
+                "body": "hello",
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+
+    std::fs::write(&path, "phone_default_region = \"GB\"\n").expect("write config file");
+    let resp = client
+        .post(format!("{base}/admin/config/reload"))
+        .json(&json!({}))
+        .send()
+        .await
+        .expect("reload");
+    assert_eq!(resp.status(), 200);
+    let report: Value = resp.json().await.expect("json");
+    assert_eq!(report["restart_required"], json!(["phone_default_region"]));
+
+    // Sends are still checked in the region the keys use until the restart
+    assert_eq!(send("555-000-4321").await.expect("send").status(), 202);
+    assert_eq!(send("020 7946 0958").await.expect("send").status(), 400);
+
+    handle.shutdown().await;
+    let _ = std::fs::remove_file(&path);
+}
@@ -36,8 +36,8 @@ async fn sms_breaker_opens_while_email_remains_closed() {
 
     // Send one SMS (expected error -> breaker opens)
     let sms_body = serde_json::json!({
-        "from": "+15550001",
-        "to": "+15550002",
+        "from": "+15550001001",
+        "to": "+15550002002",
         "type": "sms",
         "body": "trigger error",
         "timestamp": chrono::Utc::now().to_rfc3339(),
@@ -16,3 +16,25 @@ fn provider_breakers_hold_distinct_instances() {
         "breakers should be distinct instances (isolation)"
     );
 }
+
+#[test]
+fn unknown_providers_share_a_provider_fallback_not_an_http_breaker() {
+    use messaging_server::config::ApiConfig;
+    use messaging_server::middleware::circuit_breaker::BreakerState;
+    use messaging_server::state::breakers::HttpBreakers;
+    use std::time::Duration;
+
+    let mut map = std::collections::HashMap::new();
+    map.insert("sms-mms".to_string(), CircuitBreaker::new(1, 5));
+    let pb = ProviderBreakers::new(map);
+    let http = HttpBreakers::from_config(&ApiConfig::default());
+
+    let fallback = pb.get_or_fallback("unregistered");
+    assert!(std::ptr::eq(fallback, pb.get_or_fallback("another")));
+    assert!(!std::ptr::eq(fallback, pb.get("sms-mms").unwrap()));
+    for _ in 0..50 {
+        fallback.record_call(false, Duration::from_millis(1));
+    }
+    assert_eq!(fallback.state(), BreakerState::Open);
+    assert_eq!(http.fallback().state(), BreakerState::Closed);
+}
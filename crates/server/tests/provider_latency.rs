@@ -0,0 +1,306 @@
+// Integration test: mock provider latency distributions are sampled from the seeded RNG, leave
+// the outcome sequence alone, and are slept through by the dispatcher up to the dispatch timeout
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::common::{predict_outcomes_from_seed, ProviderRng};
+use messaging_server::providers::latency::{LatencyDistribution, MAX_LATENCY_MS};
+use messaging_server::providers::mock::Outcome;
+use messaging_server::ServerBuilder;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+#[test]
+fn distributions_match_their_parameters() {
+    let mut state = 7u64;
+    let mut unit = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    assert_eq!(
+        LatencyDistribution::Fixed { ms: 25 }.sample(&mut unit),
+        Duration::from_millis(25)
+    );
+    let uniform = LatencyDistribution::Uniform {
+        min_ms: 10,
+        max_ms: 20,
+    };
+    for _ in 0..1000 {
+        let ms = uniform.sample(&mut unit).as_secs_f64() * 1000.0;
+        assert!((10.0..=20.0).contains(&ms), "{ms}");
+    }
+
+    let log_normal = LatencyDistribution::LogNormal {
+        p50_ms: 100,
+        p99_ms: 2000,
+    };
+    let mut samples: Vec<f64> = (0..20_000)
+        .map(|_| log_normal.sample(&mut unit).as_secs_f64() * 1000.0)
+        .collect();
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let p50 = samples[samples.len() / 2];
+    let p99 = samples[samples.len() * 99 / 100];
+    assert!((85.0..115.0).contains(&p50), "p50 {p50}");
+    assert!((1600.0..2500.0).contains(&p99), "p99 {p99}");
+
+    assert!(uniform.validate().is_empty());
+    assert_eq!(
+        LatencyDistribution::LogNormal {
+            p50_ms: 50,
+            p99_ms: 10
+        }
+        .validate()
+        .len(),
+        1
+    );
+
+    // A far tail draw is clamped instead of overflowing `Duration`, and such a tail is rejected
+    let unbounded = LatencyDistribution::LogNormal {
+        p50_ms: 1,
+        p99_ms: u64::MAX,
+    };
+    let mut draws = [0.999_999_999, 0.0].into_iter();
+    assert_eq!(
+        unbounded.sample(|| draws.next().unwrap()),
+        Duration::from_millis(MAX_LATENCY_MS)
+    );
+    assert_eq!(unbounded.validate().len(), 1);
+    assert_eq!(
+        LatencyDistribution::Fixed {
+            ms: MAX_LATENCY_MS + 1
+        }
+        .validate()
+        .len(),
+        1
+    );
+}
+
+#[test]
+fn seeded_latency_repeats_without_shifting_outcomes() {
+    let cfg = ApiConfig {
+        provider_error_pct: 30,
+        provider_seed: Some(99),
+        provider_latency: LatencyDistribution::Uniform {
+            min_ms: 0,
+            max_ms: 1000,
+        },
+        ..ApiConfig::default()
+    };
+    let run = || {
+        let rng = ProviderRng::seeded(99);
+        (0..40)
+            .map(|_| {
+                let latency = rng.sample_latency("email", &cfg);
+                (rng.pick_outcome("email", &cfg).0, latency)
+            })
+            .collect::<Vec<_>>()
+    };
+    let first = run();
+    assert_eq!(first, run());
+    assert!(first.iter().any(|(_, l)| *l != first[0].1));
+
+    // Outcomes still follow the prediction for the seed
+    let errors = first.iter().filter(|(o, _)| *o == Outcome::Error).count() as u32;
+    let (_, _, predicted_errors, _) = predict_outcomes_from_seed("email", &cfg, 99, 40);
+    assert_eq!(errors, predicted_errors);
+}
+
+#[tokio::test]
+async fn dispatcher_sleeps_and_times_out_slow_calls() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            dispatch_latency_buckets: vec![0.02, 0.1, 1.0],
+            provider_latency: LatencyDistribution::Fixed { ms: 30 },
+            provider_latency_overrides: [(
+                "email".to_string(),
+                LatencyDistribution::Fixed { ms: 400 },
+            )]
+            .into(),
+            provider_dispatch_timeout_ms: 150,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("{base}/api/messages/sms"))
+        .json(&serde_json::json!({
+            "from": "+15550005555",
+            "to": "+15550006666",
+            "type": "sms",
+            "body": "slow",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("sms");
+    assert_eq!(resp.status(), 202);
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&serde_json::json!({
+            "from": "latency@example.com",
+            "to": "l@example.com",
+            "body": "too slow",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("email");
+    assert_eq!(resp.status(), 202);
+
+    let email_timeout = r#"messaging_provider_dispatch_duration_seconds_count{outcome="timeout",provider="email"} 1"#;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let text = loop {
+        let text = client
+            .get(format!("{base}/metrics"))
+            .header("Accept", "text/plain")
+            .send()
+            .await
+            .expect("metrics")
+            .text()
+            .await
+            .expect("text");
+        if text.contains(email_timeout) {
+            break text;
+        }
+        assert!(Instant::now() < deadline, "email dispatch never timed out");
+        sleep(Duration::from_millis(20)).await;
+    };
+    // sms took its 30ms; email was cut off at the 150ms timeout instead of 400ms
+    for series in [
+        r#"messaging_provider_dispatch_duration_seconds_bucket{outcome="success",provider="sms-mms",le="0.02"} 0"#,
+        r#"messaging_provider_dispatch_duration_seconds_bucket{outcome="success",provider="sms-mms",le="0.1"} 1"#,
+        r#"messaging_provider_dispatch_duration_seconds_bucket{outcome="timeout",provider="email",le="0.1"} 0"#,
+        r#"messaging_provider_dispatch_duration_seconds_bucket{outcome="timeout",provider="email",le="1"} 1"#,
+    ] {
+        assert!(text.contains(series), "missing {series}");
+    }
+    let sum = text
+        .lines()
+        .find(|l| l.starts_with(r#"messaging_provider_dispatch_duration_seconds_sum{outcome="timeout",provider="email"}"#))
+        .and_then(|l| l.rsplit(' ').next())
+        .and_then(|v| v.parse::<f64>().ok())
+        .expect("email timeout sum");
+    assert!((0.15..0.4).contains(&sum), "{sum}");
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn slow_dispatches_do_not_block_the_queue_or_the_drain_deadline() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    // Email takes a minute and nothing cuts it off
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            provider_latency_overrides: [(
+                "email".to_string(),
+                LatencyDistribution::Fixed { ms: 60_000 },
+            )]
+            .into(),
+            provider_dispatch_timeout_ms: 0,
+            shutdown_drain_timeout_secs: 1,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+
+    // Metrics are process-wide, so count from here
+    let sms_successes = || async {
+        let text = client
+            .get(format!("{base}/metrics"))
+            .header("Accept", "text/plain")
+            .send()
+            .await
+            .expect("metrics")
+            .text()
+            .await
+            .expect("text");
+        text.lines()
+            .find(|l| {
+                l.starts_with(r#"messaging_provider_dispatch_duration_seconds_count{outcome="success",provider="sms-mms"}"#)
+            })
+            .and_then(|l| l.rsplit(' ').next())
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    let before = sms_successes().await;
+
+    let resp = client
+        .post(format!("{base}/api/messages/email"))
+        .json(&serde_json::json!({
+            "from": "stuck@example.com",
+            "to": "s@example.com",
+            "body": "slow",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("email");
+    assert_eq!(resp.status(), 202);
+    let resp = client
+        .post(format!("{base}/api/messages/sms"))
+        .json(&serde_json::json!({
+            "from": "+15550007777",
+            "to": "+15550008888",
+            "type": "sms",
+            "body": "fast",
+            "timestamp": "2024-11-01T14:00:00Z"
+        }))
+        .send()
+        .await
+        .expect("sms");
+    assert_eq!(resp.status(), 202);
+
+    // The sms goes out while the email is still waiting
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while sms_successes().await == before {
+        assert!(
+            Instant::now() < deadline,
+            "sms queued behind the slow email"
+        );
+        sleep(Duration::from_millis(20)).await;
+    }
+
+    // The email is aborted at the one-second drain deadline
+    let report = tokio::time::timeout(Duration::from_secs(10), handle.shutdown())
+        .await
+        .expect("shutdown honours the drain deadline");
+    assert!(report.timed_out, "report: {report:?}");
+    assert_eq!(report.outbound_abandoned, 1, "report: {report:?}");
+}
@@ -0,0 +1,164 @@
+// Integration test: the mock provider config endpoint sets per-provider behaviour that the next
+// dispatch uses, and restarts seeded outcome sequences
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+fn counter(metrics: &Value, key: &str) -> u64 {
+    metrics.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+#[tokio::test]
+async fn mock_config_drives_dispatch_per_provider() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            rate_limit_per_ip_per_min: 1000,
+            breaker_error_threshold: 1000,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let put = |body: Value| {
+        client
+            .put(format!("{base}/api/provider/mock/config"))
+            .json(&body)
+            .send()
+    };
+    // Send `n` messages on a channel and wait until the worker dispatched them; returns the
+    // provider's error count for the batch
+    let send = |channel: &'static str, n: u32| {
+        let client = client.clone();
+        let base = base.clone();
+        async move {
+            let prefix = if channel == "sms" { "sms_mms" } else { "email" };
+            let attempts = format!("provider_{prefix}_attempts");
+            let errors = format!("provider_{prefix}_error");
+            let get = || async {
+                client
+                    .get(format!("{base}/metrics"))
+                    .send()
+                    .await
+                    .expect("metrics")
+                    .json::<Value>()
+                    .await
+                    .expect("json")
+            };
+            let before = get().await;
+            for i in 0..n {
+                let body = if channel == "sms" {
+                    json!({"from": "+15550001111", "to": format!("+1555000{i:04}"), "type": "sms",
+                           "body": format!("sms {i}"), "timestamp": "2024-11-01T14:00:00Z"})
+                } else {
+                    json!({"from": "mock@example.com", "to": format!("m{i}@example.com"),
+                           "body": format!("email {i}"), "timestamp": "2024-11-01T14:00:00Z"})
+                };
+                let resp = client
+                    .post(format!("{base}/api/messages/{channel}"))
+                    .json(&body)
+                    .send()
+                    .await
+                    .expect("send");
+                assert_eq!(resp.status(), 202);
+            }
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let now = get().await;
+                if counter(&now, &attempts) >= counter(&before, &attempts) + n as u64 {
+                    return counter(&now, &errors) - counter(&before, &errors);
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "{channel} messages not dispatched"
+                );
+                sleep(Duration::from_millis(20)).await;
+            }
+        }
+    };
+
+    let current: Value = client
+        .get(format!("{base}/api/provider/mock/config"))
+        .send()
+        .await
+        .expect("get")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(current["providers"]["email"]["error_pct"], Value::Null);
+    assert_eq!(current["providers"]["sms-mms"]["seed"], Value::Null);
+
+    // Only email fails
+    let resp = put(json!({
+        "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+        "providers": {"email": {"error_pct": 100}}
+    }))
+    .await
+    .expect("put");
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(body["providers"]["email"]["error_pct"], 100);
+    assert_eq!(body["providers"]["sms-mms"]["error_pct"], Value::Null);
+    assert_eq!(send("email", 3).await, 3);
+    assert_eq!(send("sms", 3).await, 0);
+
+    // Omitting `providers` keeps the overrides
+    assert_eq!(
+        put(json!({"timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null}))
+            .await
+            .expect("put")
+            .status(),
+        200
+    );
+    assert_eq!(send("email", 1).await, 1);
+
+    // Unknown providers and invalid totals are rejected
+    let resp = put(json!({
+        "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+        "providers": {"voice": {"error_pct": 10}}
+    }))
+    .await
+    .expect("put");
+    assert_eq!(resp.status(), 422);
+    let resp = put(json!({
+        "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+        "providers": {"sms-mms": {"error_pct": 90, "timeout_pct": 20}}
+    }))
+    .await
+    .expect("put");
+    assert_eq!(resp.status(), 422);
+
+    // Re-applying a seeded config restarts the outcome sequence
+    let seeded = json!({
+        "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+        "providers": {"sms-mms": {"error_pct": 50, "seed": 4242}}
+    });
+    assert_eq!(put(seeded.clone()).await.expect("put").status(), 200);
+    let first = send("sms", 10).await;
+    assert_eq!(put(seeded).await.expect("put").status(), 200);
+    let second = send("sms", 10).await;
+    assert_eq!(first, second);
+    assert!(first > 0 && first < 10, "50% error rate gave {first}/10");
+
+    handle.shutdown().await;
+}
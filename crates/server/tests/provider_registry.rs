@@ -15,6 +15,7 @@ impl Provider for DummyProvider {
         DispatchResult {
             provider_name: self.0.to_string(),
             outcome: Outcome::Success,
+            latency: std::time::Duration::ZERO,
         }
     }
 }
@@ -0,0 +1,205 @@
+// Integration test: scripted mock provider scenarios advance by message count or time, validate,
+// load from TOML, and drive a provider breaker through an outage and its recovery
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::scenario::{Scenario, ScenarioPhase, ScenarioRunner};
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+#[test]
+fn runner_follows_phases_and_repeats() {
+    let scenario = Scenario {
+        phases: vec![
+            ScenarioPhase {
+                messages: Some(2),
+                outcome: Some(Outcome::Timeout),
+                latency_ms: 50,
+                ..ScenarioPhase::default()
+            },
+            ScenarioPhase {
+                messages: Some(3),
+                sequence: vec![Outcome::Error, Outcome::Success],
+                ..ScenarioPhase::default()
+            },
+        ],
+        repeat: true,
+    };
+    let runner = ScenarioRunner::new();
+    let outcomes: Vec<_> = (0..7)
+        .map(|_| runner.step("sms-mms", Some(&scenario)).unwrap())
+        .map(|s| (s.phase, s.outcome.unwrap()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            (0, Outcome::Timeout),
+            (0, Outcome::Timeout),
+            (1, Outcome::Error),
+            (1, Outcome::Success),
+            (1, Outcome::Error),
+            (0, Outcome::Timeout),
+            (0, Outcome::Timeout),
+        ]
+    );
+    let step = runner.step("sms-mms", Some(&scenario)).unwrap();
+    assert_eq!(step.latency, Duration::ZERO);
+
+    // Without `repeat` the provider falls back to its percentages
+    let once = Scenario {
+        repeat: false,
+        ..scenario
+    };
+    runner.reset();
+    for _ in 0..5 {
+        assert!(runner.step("sms-mms", Some(&once)).is_some());
+    }
+    assert_eq!(runner.step("sms-mms", Some(&once)), None);
+    assert_eq!(runner.step("sms-mms", None), None);
+}
+
+#[test]
+fn invalid_scenarios_are_rejected_and_toml_loads() {
+    let mut cfg = ApiConfig::default();
+    cfg.provider_scenarios.insert(
+        "sms-mms".into(),
+        Scenario {
+            phases: vec![ScenarioPhase::default(), ScenarioPhase::default()],
+            repeat: false,
+        },
+    );
+    cfg.provider_scenarios
+        .insert("voice".into(), Scenario::default());
+    let errors = cfg.validate().unwrap_err();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert!(errors[0].contains("phase 0"));
+    assert!(errors[1].contains("unknown provider"));
+
+    let path = std::env::temp_dir().join(format!("scenario-{}.toml", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &path,
+        r#"
+[provider_scenarios.email]
+repeat = true
+[[provider_scenarios.email.phases]]
+name = "outage"
+duration_ms = 30000
+outcome = "timeout"
+[[provider_scenarios.email.phases]]
+messages = 4
+sequence = ["error", "success"]
+latency_ms = 200
+"#,
+    )
+    .expect("write");
+    let cfg = ApiConfig::try_load(Some(&path)).expect("load");
+    let _ = std::fs::remove_file(&path);
+    let email = &cfg.provider_scenarios["email"];
+    assert!(email.repeat);
+    assert_eq!(email.phases[0].duration_ms, Some(30000));
+    assert_eq!(email.phases[1].sequence, [Outcome::Error, Outcome::Success]);
+    assert!(cfg.validate().is_ok());
+}
+
+#[tokio::test]
+async fn outage_scenario_trips_then_recovers_breaker() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            breaker_error_threshold: 2,
+            breaker_open_secs: 1,
+            provider_dispatch_timeout_ms: 50,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let send_sms = |n: u32| {
+        client
+            .post(format!("{base}/api/messages/sms"))
+            .json(&json!({
+                "from": "+15550003333",
+                "to": "+15550004444",
+                "type": "sms",
+                "body": format!("scenario {n}"),
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+    let wait_for_state = |want: &'static str| {
+        let client = client.clone();
+        let base = base.clone();
+        async move {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            loop {
+                let body: Value = client
+                    .get(format!("{base}/admin/breakers/sms-mms"))
+                    .send()
+                    .await
+                    .expect("show")
+                    .json()
+                    .await
+                    .expect("json");
+                if body["state"] == want {
+                    return;
+                }
+                assert!(
+                    Instant::now() < deadline,
+                    "breaker never became {want}: {body}"
+                );
+                sleep(Duration::from_millis(20)).await;
+            }
+        }
+    };
+
+    let resp = client
+        .put(format!("{base}/api/provider/mock/config"))
+        .json(&json!({
+            "timeout_pct": 0, "error_pct": 0, "ratelimit_pct": 0, "seed": null,
+            "providers": {"sms-mms": {"scenario": {"phases": [
+                {"name": "outage", "duration_ms": 300, "outcome": "timeout"},
+                {"name": "recovered", "outcome": "success", "latency_ms": 10}
+            ]}}}
+        }))
+        .send()
+        .await
+        .expect("put");
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.expect("json");
+    assert_eq!(
+        body["providers"]["sms-mms"]["scenario"]["phases"][0]["name"],
+        "outage"
+    );
+
+    // The outage times out every send and opens the breaker
+    for n in 0..2 {
+        assert_eq!(send_sms(n).await.expect("send").status(), 202);
+    }
+    wait_for_state("open").await;
+
+    // Once the open period and the outage are over, the half-open probe succeeds
+    sleep(Duration::from_millis(1100)).await;
+    assert_eq!(send_sms(2).await.expect("send").status(), 202);
+    wait_for_state("closed").await;
+
+    handle.shutdown().await;
+}
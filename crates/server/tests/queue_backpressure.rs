@@ -0,0 +1,141 @@
+// Integration test: a saturated outbound queue yields 503 + Retry-After instead of hanging,
+// and the rejected request can be retried with the same Idempotency-Key
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{
+    ChannelKind, DispatchResult, OutboundMessage, Provider,
+};
+use messaging_server::ServerBuilder;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+/// Blocks the outbound worker for a while per message so the queue backs up.
+struct SlowProvider(Arc<AtomicU64>);
+impl Provider for SlowProvider {
+    fn name(&self) -> &str {
+        "slow-email"
+    }
+    fn dispatch(&self, _msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        std::thread::sleep(std::time::Duration::from_millis(400));
+        self.0.fetch_add(1, Ordering::SeqCst);
+        DispatchResult {
+            provider_name: self.name().to_string(),
+            outcome: Outcome::Success,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+async fn wait_until(what: &str, mut cond: impl FnMut() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !cond() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        sleep(Duration::from_millis(20)).await;
+    }
+}
+
+async fn outbound_depth(client: &reqwest::Client, base: &str) -> u64 {
+    let body: serde_json::Value = client
+        .get(format!("{base}/admin/queues"))
+        .send()
+        .await
+        .expect("queues")
+        .json()
+        .await
+        .expect("queues json");
+    body["outbound"]["depth"].as_u64().expect("depth")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn full_queue_returns_503_with_retry_after() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let dispatched = Arc::new(AtomicU64::new(0));
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            queue_capacity: 1,
+            // One dispatch slot, so a busy worker leaves the queue to fill up
+            outbound_concurrency: 1,
+            queue_enqueue_wait_ms: 0,
+            queue_retry_after_secs: 7,
+            shutdown_drain_timeout_secs: 3,
+            ..ApiConfig::default()
+        })
+        .provider(
+            ChannelKind::Email,
+            Arc::new(SlowProvider(dispatched.clone())),
+        )
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let send = |n: u32| {
+        client
+            .post(format!("{base}/api/messages/email"))
+            .header("Idempotency-Key", format!("backpressure-{n}"))
+            .json(&serde_json::json!({
+                "from": "backpressure@example.com",
+                "to": "b@example.com",
+                "body": format!("message {n}"),
+                "timestamp": "2024-11-01T14:00:00Z"
+            }))
+            .send()
+    };
+
+    // First message is picked up by the (now busy) worker, second fills the only slot
+    assert_eq!(send(1).await.expect("send 1").status(), 202);
+    let mut depth = 1;
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while depth != 0 {
+        assert!(
+            Instant::now() < deadline,
+            "worker never picked up message 1"
+        );
+        sleep(Duration::from_millis(20)).await;
+        depth = outbound_depth(&client, &base).await;
+    }
+    assert_eq!(send(2).await.expect("send 2").status(), 202);
+
+    // Third is rejected without blocking
+    let rejected = send(3).await.expect("send 3");
+    assert_eq!(rejected.status(), 503);
+    assert_eq!(
+        rejected
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok()),
+        Some("7")
+    );
+    let err: serde_json::Value = rejected.json().await.expect("error json");
+    assert_eq!(err["code"], "queue_unavailable");
+
+    // Once the queue drains, retrying with the same key is accepted and actually dispatched
+    wait_until("messages 1 and 2 dispatched", || {
+        dispatched.load(Ordering::SeqCst) >= 2
+    })
+    .await;
+    assert_eq!(send(3).await.expect("retry 3").status(), 202);
+    wait_until("message 3 dispatched", || {
+        dispatched.load(Ordering::SeqCst) >= 3
+    })
+    .await;
+
+    handle.shutdown().await;
+}
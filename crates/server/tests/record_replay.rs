@@ -0,0 +1,185 @@
+// Integration test: a recorded run replays with identical outcomes through the outbound queue,
+// and the replay provider reports mismatches and exhaustion
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::recording::{read_records, DispatchRecord, ReplayProvider};
+use messaging_server::providers::registry::{ChannelKind, OutboundMessage, Provider};
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{name}-{}.jsonl", uuid::Uuid::new_v4()))
+}
+
+fn message(to: &str) -> OutboundMessage {
+    OutboundMessage {
+        channel: ChannelKind::Email,
+        to: to.to_string(),
+        from: "replay@example.com".to_string(),
+        body: "hi".to_string(),
+        attachments: vec![],
+        idempotency_key: None,
+    }
+}
+
+#[test]
+fn replay_follows_the_recording_then_fails() {
+    let records = [
+        ("a@example.com", Outcome::RateLimited),
+        ("b@example.com", Outcome::Success),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, (to, outcome))| DispatchRecord {
+        seq: i as u64 + 1,
+        recorded_at: chrono::Utc::now(),
+        provider: "email".to_string(),
+        message: message(to),
+        outcome: *outcome,
+        latency_ms: 7,
+    })
+    .collect();
+    let replay = ReplayProvider::new("email", records);
+    let cfg = ApiConfig::default();
+    let first = replay.dispatch(&message("a@example.com"), &cfg);
+    assert_eq!(first.outcome, Outcome::RateLimited);
+    assert_eq!(first.latency, Duration::from_millis(7));
+    // A different message still gets the recorded outcome
+    assert_eq!(
+        replay.dispatch(&message("other@example.com"), &cfg).outcome,
+        Outcome::Success
+    );
+    assert_eq!(replay.remaining(), 0);
+    assert_eq!(
+        replay.dispatch(&message("c@example.com"), &cfg).outcome,
+        Outcome::Error
+    );
+}
+
+async fn run(api: ApiConfig) -> Vec<(String, String, Outcome)> {
+    let record_path = api.provider_record_path.clone().expect("record path");
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(api)
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    for i in 0..8 {
+        let (path, body) = if i % 2 == 0 {
+            (
+                "sms",
+                json!({"from": "+15550007777", "to": format!("+155500088{i:02}"), "type": "sms",
+                       "body": format!("replay {i}"), "timestamp": "2024-11-01T14:00:00Z"}),
+            )
+        } else {
+            (
+                "email",
+                json!({"from": "replay@example.com", "to": format!("r{i}@example.com"),
+                       "body": format!("replay {i}"), "timestamp": "2024-11-01T14:00:00Z"}),
+            )
+        };
+        let resp = client
+            .post(format!("{base}/api/messages/{path}"))
+            .json(&body)
+            .send()
+            .await
+            .expect("send");
+        assert_eq!(resp.status(), 202);
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let records = loop {
+        let records = read_records(&record_path).unwrap_or_default();
+        if records.len() == 8 {
+            break records;
+        }
+        assert!(Instant::now() < deadline, "only {} recorded", records.len());
+        sleep(Duration::from_millis(20)).await;
+    };
+    handle.shutdown().await;
+    records
+        .into_iter()
+        .map(|r| (r.provider, r.message.to, r.outcome))
+        .collect()
+}
+
+#[tokio::test]
+async fn replayed_run_matches_recording() {
+    let first = temp_path("record");
+    let second = temp_path("replay");
+    let recorded = run(ApiConfig {
+        provider_error_pct: 30,
+        provider_ratelimit_pct: 30,
+        provider_seed: Some(2024),
+        provider_record_path: Some(first.display().to_string()),
+        ..ApiConfig::default()
+    })
+    .await;
+    assert!(recorded.iter().any(|(_, _, o)| *o != Outcome::Success));
+
+    // The mock would now succeed every time; the replay brings the recorded outcomes back
+    let replayed = run(ApiConfig {
+        provider_replay_path: Some(first.display().to_string()),
+        provider_record_path: Some(second.display().to_string()),
+        ..ApiConfig::default()
+    })
+    .await;
+    assert_eq!(replayed, recorded);
+
+    let line: Value = serde_json::from_str(
+        std::fs::read_to_string(&first)
+            .expect("read")
+            .lines()
+            .next()
+            .expect("line"),
+    )
+    .expect("json");
+    assert_eq!(line["seq"], 1);
+    assert!(line["message"]["channel"].is_string());
+
+    let missing = ServerBuilder::new(Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    }))
+    .api_config(ApiConfig {
+        provider_replay_path: Some(temp_path("missing").display().to_string()),
+        ..ApiConfig::default()
+    })
+    .build()
+    .await;
+    assert!(missing.is_err_and(|e| e.contains("replay file")));
+
+    let _ = std::fs::remove_file(&first);
+    let _ = std::fs::remove_file(&second);
+}
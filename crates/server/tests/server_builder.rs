@@ -0,0 +1,132 @@
+// Integration test: embedding via ServerBuilder with a custom provider and extra routes
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::mock::Outcome;
+use messaging_server::providers::registry::{
+    ChannelKind, DispatchResult, OutboundMessage, Provider,
+};
+use messaging_server::ServerBuilder;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+struct CountingProvider(Arc<AtomicU64>);
+impl Provider for CountingProvider {
+    fn name(&self) -> &str {
+        "counting-email"
+    }
+    fn dispatch(&self, _msg: &OutboundMessage, _cfg: &ApiConfig) -> DispatchResult {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        DispatchResult {
+            provider_name: self.name().to_string(),
+            outcome: Outcome::Success,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+#[tokio::test]
+async fn builder_uses_custom_provider_and_extra_routes() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let dispatched = Arc::new(AtomicU64::new(0));
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            shutdown_drain_timeout_secs: 2,
+            ..ApiConfig::default()
+        })
+        .provider(
+            ChannelKind::Email,
+            Arc::new(CountingProvider(dispatched.clone())),
+        )
+        .routes(axum::Router::new().route(
+            "/extra",
+            axum::routing::get(|| async { axum::Json(serde_json::json!({"extra": true})) }),
+        ))
+        .build()
+        .await
+        .expect("build")
+        .start();
+
+    let client = reqwest::Client::new();
+    let base = format!("http://{}", handle.local_addr());
+    let extra = client
+        .get(format!("{}/extra", base))
+        .send()
+        .await
+        .expect("extra route");
+    assert!(extra.status().is_success());
+    assert_eq!(
+        extra.json::<serde_json::Value>().await.expect("json")["extra"],
+        true
+    );
+
+    let resp = client
+        .post(format!("{}/api/messages/email", base))
+        .json(&serde_json::json!({
+            "from": "builder@example.com",
+            "to": "b@example.com",
+            "body": "via custom provider",
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }))
+        .send()
+        .await
+        .expect("send");
+    assert!(resp.status().is_success());
+
+    for _ in 0..20 {
+        if dispatched.load(Ordering::SeqCst) > 0 {
+            break;
+        }
+        sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(dispatched.load(Ordering::SeqCst), 1);
+
+    let report = handle.shutdown().await;
+    assert!(!report.timed_out, "report: {report:?}");
+}
+
+#[tokio::test]
+async fn build_rejects_an_invalid_api_config() {
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let built = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            worker_batch_size: 0,
+            queue_capacity: 0,
+            ..ApiConfig::default()
+        })
+        .build()
+        .await;
+    let Err(err) = built else {
+        panic!("an invalid api config must not build");
+    };
+    assert!(err.contains("worker_batch_size must be > 0"), "{err}");
+    assert!(err.contains("queue_capacity must be > 0"), "{err}");
+}
@@ -0,0 +1,127 @@
+// Integration test: SMS sends report their segment count and encoding, transliteration keeps
+// smart punctuation in GSM-7 (and is what gets dispatched), and the counts reach /metrics
+use messaging_core::Config;
+use messaging_server::config::ApiConfig;
+use messaging_server::providers::recording::read_records;
+use messaging_server::ServerBuilder;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+#[tokio::test]
+async fn sms_responses_and_metrics_report_segments() {
+    let record = std::env::temp_dir().join(format!("segments-{}.jsonl", uuid::Uuid::new_v4()));
+    let cfg = Arc::new(Config {
+        port: 0,
+        health_path: "/health".to_string(),
+        log_level: "info".to_string(),
+        conversation_snippet_length: 64,
+        auth_session_expiry_min: 30,
+        rate_limit_per_ip_per_min: 120,
+        rate_limit_per_sender_per_min: 60,
+        argon2_memory_mb: 64,
+        argon2_time_cost: 3,
+        argon2_parallelism: 1,
+        security_headers_enabled: true,
+        csp_default_src: "'self'".into(),
+        ssrf_allowlist: vec![],
+    });
+    let handle = ServerBuilder::new(cfg)
+        .api_config(ApiConfig {
+            sms_transliterate: true,
+            provider_record_path: Some(record.display().to_string()),
+            ..ApiConfig::default()
+        })
+        .build()
+        .await
+        .expect("build")
+        .start();
+    let base = format!("http://{}", handle.local_addr());
+    let client = reqwest::Client::new();
+    let send = |kind: &str, body: &str| {
+        let mut payload = json!({
+            "from": "+15550007777",
+            "to": "+15550008888",
+            "type": kind,
+            "body": body,
+            "timestamp": "2024-11-01T14:00:00Z"
+        });
+        if kind == "mms" {
+            payload["attachments"] = json!(["https://example.com/a.png"]);
+        }
+        client
+            .post(format!("{base}/api/messages/sms"))
+            .json(&payload)
+            .send()
+    };
+
+    let cases = [
+        ("a".repeat(161), 2, "gsm7"),
+        ("Price: 5€ {approx}".to_string(), 1, "gsm7"),
+        ("👍".repeat(40), 2, "ucs2"),
+        (
+            "\u{201C}See you\u{201D} \u{2013} later\u{2026}".to_string(),
+            1,
+            "gsm7",
+        ),
+    ];
+    for (body, segments, encoding) in &cases {
+        let resp = send("sms", body).await.expect("send");
+        assert_eq!(resp.status(), 202);
+        let json: Value = resp.json().await.expect("json");
+        assert_eq!(json["segments"], *segments, "{body}");
+        assert_eq!(json["encoding"], *encoding, "{body}");
+    }
+    let resp = send("mms", "picture").await.expect("mms");
+    assert_eq!(resp.status(), 202);
+    let json: Value = resp.json().await.expect("json");
+    assert_eq!(json, json!({"status": "accepted"}));
+
+    // The transliterated body is the one dispatched
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let records = loop {
+        let records = read_records(&record).unwrap_or_default();
+        if records.len() == 5 {
+            break records;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "only {} dispatched",
+            records.len()
+        );
+        sleep(Duration::from_millis(20)).await;
+    };
+    assert!(records
+        .iter()
+        .any(|r| r.message.body == "\"See you\" - later..."));
+
+    let text = client
+        .get(format!("{base}/metrics"))
+        .header("Accept", "text/plain")
+        .send()
+        .await
+        .expect("metrics")
+        .text()
+        .await
+        .expect("text");
+    for series in [
+        r#"messaging_sms_messages_total{encoding="gsm7"} 3"#,
+        r#"messaging_sms_segments_total{encoding="gsm7"} 4"#,
+        r#"messaging_sms_segments_total{encoding="ucs2"} 2"#,
+    ] {
+        assert!(text.contains(series), "missing {series}");
+    }
+    let json: Value = client
+        .get(format!("{base}/metrics"))
+        .send()
+        .await
+        .expect("metrics")
+        .json()
+        .await
+        .expect("json");
+    assert_eq!(json["sms_messages"], 4);
+    assert_eq!(json["sms_segments"], 6);
+
+    handle.shutdown().await;
+    let _ = std::fs::remove_file(&record);
+}
@@ -30,8 +30,8 @@ async fn webhook_sms_and_email_are_accepted() {
 
     // Prepare webhook SMS payload (type=sms)
     let sms_payload = serde_json::json!({
-        "from": "+15550001",
-        "to": "+15550002",
+        "from": "+15550001001",
+        "to": "+15550002002",
         "type": "sms",
         "messaging_provider_id": "prov-msg-1",
         "body": "inbound from provider",
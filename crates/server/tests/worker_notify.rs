@@ -0,0 +1,50 @@
+// Integration test: inbound_events inserts fire NOTIFY so the worker wakes without polling
+use messaging_server::store_db::inbound_events::insert_inbound_event;
+use messaging_server::worker::inbound::INBOUND_EVENTS_CHANNEL;
+use sqlx::postgres::{PgListener, PgPoolOptions};
+use tokio::time::{timeout, Duration};
+
+#[tokio::test]
+async fn insert_notifies_inbound_events_channel() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("[worker_notify] Skipping: DATABASE_URL not set");
+        return;
+    };
+    let pool = match PgPoolOptions::new().max_connections(2).connect(&url).await {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[worker_notify] Skipping: cannot connect to DATABASE_URL ({e:?})");
+            return;
+        }
+    };
+    let mut listener = PgListener::connect_with(&pool).await.expect("listener");
+    listener
+        .listen(INBOUND_EVENTS_CHANNEL)
+        .await
+        .expect("listen");
+
+    let provider_id = format!("notify-{}", uuid::Uuid::new_v4());
+    insert_inbound_event(
+        &pool,
+        "sms",
+        "+15550001111",
+        "+15550002222",
+        Some(&provider_id),
+        serde_json::json!({"body": "wake up"}),
+    )
+    .await
+    .expect("insert inbound event");
+
+    let n = timeout(Duration::from_secs(5), listener.recv())
+        .await
+        .expect("notification within 5s")
+        .expect("recv");
+    assert_eq!(n.channel(), INBOUND_EVENTS_CHANNEL);
+    assert!(n.payload().parse::<i64>().is_ok(), "payload is event id");
+
+    sqlx::query("DELETE FROM inbound_events WHERE provider_message_id = $1")
+        .bind(&provider_id)
+        .execute(&pool)
+        .await
+        .ok();
+}